@@ -1,4 +1,15 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::bigint::BigInt;
+use crate::operators;
+use crate::rational::Rational;
 use crate::token::Token;
+use crate::units::Unit;
+
+/// The precedence of `^` and of unary operators when printing expressions.
+const POWER_PRECEDENCE: u8 = 8;
+/// The precedence of literals, variables, calls and anything else in brackets.
+const ATOM_PRECEDENCE: u8 = 10;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct MatchArm {
@@ -24,25 +35,41 @@ pub enum Expr {
         right: Box<Expr>,
     },
     Grouping(Box<Expr>),
+    /// An operator used as a function, optionally with one operand
+    /// already supplied, e.g. `(+)`, `(< 3)` or `(2 ^)`.
+    Section {
+        op: Token,
+        left: Option<Box<Expr>>,
+        right: Option<Box<Expr>>,
+    },
     Variable(String),
     Assign {
         name: String,
+        /// The `=` sign, used to locate errors.
+        equal: Token,
         expr: Box<Expr>,
     },
     Binding {
         name: String,
+        annotation: Option<TypeAnnotation>,
         expr: Box<Expr>,
     },
     Literal(LiteralValue),
     FunctionDef {
         param: String,
+        param_type: Option<TypeAnnotation>,
         body: Box<Expr>,
+        return_type: Option<TypeAnnotation>,
     },
     FunctionCall {
         func: Box<Expr>,
+        /// The opening parenthesis, used to locate errors.
+        paren: Token,
         arg: Box<Expr>,
     },
     If {
+        /// The `if` keyword, used to locate errors.
+        keyword: Token,
         cond_expr: Box<Expr>,
         then_expr: Box<Expr>,
         else_expr: Box<Expr>,
@@ -53,13 +80,254 @@ pub enum Expr {
     List {
         elements: Vec<Expr>,
     },
+    /// A matrix literal, like `[|1, 2; 3, 4|]`. The vector `[|1, 2|]`
+    /// has a row for each element.
+    Matrix {
+        rows: Vec<Vec<Expr>>,
+    },
+    /// An interval literal, like `[1.9 .. 2.1]`.
+    Interval {
+        low: Box<Expr>,
+        high: Box<Expr>,
+    },
+    /// A formula that is not evaluated, like `sym(x ^ 2 + 1)`.
+    Symbolic(Box<Expr>),
     Empty,
 }
 
+impl Expr {
+    /// How tightly the expression binds when printed, on the scale of operator precedences.
+    /// Expressions with a lower precedence than their context are put in parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { op, .. } => operators::builtin_operator(&op.kind)
+                .map_or(POWER_PRECEDENCE, |info| info.precedence),
+            Expr::Unary { .. } => POWER_PRECEDENCE,
+            Expr::Literal(literal) if literal.is_negative() => POWER_PRECEDENCE,
+            Expr::Program { .. }
+            | Expr::Assign { .. }
+            | Expr::Binding { .. }
+            | Expr::FunctionDef { .. }
+            | Expr::If { .. }
+            | Expr::Match { .. }
+            | Expr::Empty => 0,
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+
+    /// Writes the expression, in parentheses if it binds looser than `min_precedence`.
+    fn fmt_operand(&self, f: &mut Formatter<'_>, min_precedence: u8) -> FmtResult {
+        if self.precedence() < min_precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+fn write_separated(f: &mut Formatter<'_>, items: &[Expr], separator: &str) -> FmtResult {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{separator}")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+/// Writes a function like `x: Number |-> x + 1`.
+pub fn write_function(
+    f: &mut Formatter<'_>,
+    param: &str,
+    param_type: Option<&TypeAnnotation>,
+    body: &Expr,
+    return_type: Option<&TypeAnnotation>,
+) -> FmtResult {
+    write!(f, "{param}")?;
+    if let Some(param_type) = param_type {
+        write!(f, ": {param_type}")?;
+    }
+    write!(f, " |-> {body}")?;
+    if let Some(return_type) = return_type {
+        write!(f, " : {return_type}")?;
+    }
+    Ok(())
+}
+
+/// Prints the expression as source code, with only the parentheses it needs.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Expr::Program { statements } => write_separated(f, statements, ";\n"),
+            Expr::Block { statements } => {
+                write!(f, "{{ ")?;
+                write_separated(f, statements, "; ")?;
+                write!(f, " }}")
+            }
+            Expr::Binary { left, op, right } => {
+                let precedence = self.precedence();
+                if precedence == POWER_PRECEDENCE {
+                    // Right associative, and the right operand may be a unary minus.
+                    left.fmt_operand(f, precedence + 1)?;
+                    write!(f, " {} ", op.lexeme)?;
+                    right.fmt_operand(f, precedence)
+                } else {
+                    left.fmt_operand(f, precedence)?;
+                    write!(f, " {} ", op.lexeme)?;
+                    right.fmt_operand(f, precedence + 1)
+                }
+            }
+            Expr::Unary { op, right } => {
+                write!(f, "{}", op.lexeme)?;
+                match **right {
+                    Expr::Unary { .. } => write!(f, "({right})"),
+                    _ => right.fmt_operand(f, POWER_PRECEDENCE),
+                }
+            }
+            Expr::Grouping(expr) => write!(f, "({expr})"),
+            Expr::Section { op, left, right } => {
+                write!(f, "(")?;
+                if let Some(left) = left {
+                    left.fmt_operand(f, ATOM_PRECEDENCE)?;
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", op.lexeme)?;
+                if let Some(right) = right {
+                    write!(f, " ")?;
+                    right.fmt_operand(f, ATOM_PRECEDENCE)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Variable(name) => write!(f, "{name}"),
+            Expr::Assign { name, expr, .. } => write!(f, "{name} = {expr}"),
+            Expr::Binding {
+                name,
+                annotation,
+                expr,
+            } => match annotation {
+                Some(annotation) => write!(f, "{name}: {annotation} := {expr}"),
+                None => write!(f, "{name} := {expr}"),
+            },
+            Expr::Literal(literal) => write!(f, "{literal}"),
+            Expr::FunctionDef {
+                param,
+                param_type,
+                body,
+                return_type,
+            } => write_function(f, param, param_type.as_ref(), body, return_type.as_ref()),
+            Expr::FunctionCall { func, arg, .. } => {
+                func.fmt_operand(f, ATOM_PRECEDENCE)?;
+                write!(f, "({arg})")
+            }
+            Expr::If {
+                cond_expr,
+                then_expr,
+                else_expr,
+                ..
+            } => write!(f, "if {cond_expr} then {then_expr} else {else_expr}"),
+            Expr::Match { arms } => {
+                write!(f, "match {{ ")?;
+                for (i, arm) in arms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} => {}", arm.pattern, arm.body)?;
+                }
+                write!(f, " }}")
+            }
+            Expr::List { elements } => {
+                write!(f, "[")?;
+                write_separated(f, elements, ", ")?;
+                write!(f, "]")
+            }
+            Expr::Matrix { rows } => {
+                write!(f, "[|")?;
+                for (i, row) in rows.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write_separated(f, row, ", ")?;
+                }
+                // A single row needs a trailing `;`, or it would be a column vector.
+                if rows.len() == 1 && rows[0].len() > 1 {
+                    write!(f, ";")?;
+                }
+                write!(f, "|]")
+            }
+            Expr::Interval { low, high } => write!(f, "[{low} .. {high}]"),
+            Expr::Symbolic(expr) => write!(f, "sym({expr})"),
+            Expr::Empty => Ok(()),
+        }
+    }
+}
+
+/// A type written in the source, like `Number` in `x: Number := 1`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeAnnotation {
+    Any,
+    Number,
+    String,
+    Boolean,
+    Function,
+    Stream,
+    /// `List` on its own is a list of `Any`.
+    List(Box<TypeAnnotation>),
+}
+
+impl std::fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "Any"),
+            Self::Number => write!(f, "Number"),
+            Self::String => write!(f, "String"),
+            Self::Boolean => write!(f, "Boolean"),
+            Self::Function => write!(f, "Function"),
+            Self::Stream => write!(f, "Stream"),
+            Self::List(element) if **element == Self::Any => write!(f, "List"),
+            Self::List(element) => write!(f, "List<{element}>"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum LiteralValue {
     Number(f64),
+    /// An exact integer, like `42`.
+    Integer(BigInt),
+    /// A number followed by a unit, like `9.81 m/s^2`.
+    Quantity(f64, Unit),
+    /// An exact fraction, like `1/3r`.
+    Rational(Rational),
+    /// An imaginary number, like `4i`.
+    Imaginary(f64),
     String(String),
     Boolean(bool),
     Nil,
 }
+
+impl LiteralValue {
+    fn is_negative(&self) -> bool {
+        match self {
+            Self::Number(n) | Self::Quantity(n, _) | Self::Imaginary(n) => n.is_sign_negative(),
+            Self::Integer(n) => n.is_negative(),
+            Self::Rational(r) => r.numerator().is_negative(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for LiteralValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Number(n) if n.fract() == 0.0 && n.is_finite() => write!(f, "{n}.0"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Integer(n) => write!(f, "{n}"),
+            Self::Quantity(n, unit) => write!(f, "{n} {unit}"),
+            Self::Rational(r) => write!(f, "{r}r"),
+            Self::Imaginary(n) => write!(f, "{n}i"),
+            Self::String(value) => write!(f, "\"{value}\""),
+            Self::Boolean(value) => write!(f, "{value}"),
+            Self::Nil => write!(f, "nil"),
+        }
+    }
+}
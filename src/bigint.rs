@@ -0,0 +1,536 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// An integer of any size.
+///
+/// The magnitude is stored as base 2^32 digits, least significant first,
+/// without trailing zeros, so zero has no digits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+// Operations on magnitudes, which are digit vectors without a sign.
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, digit) in long.iter().enumerate() {
+        let sum = *digit as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+/// Subtracts `b` from `a`, which must be at least as large.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, digit) in a.iter().enumerate() {
+        let mut difference = *digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+    trim(&mut result);
+    result
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    result
+}
+
+/// Divides by a single digit, returning the quotient and remainder.
+fn divmod_digit(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, digit) in a.iter().enumerate().rev() {
+        let current = (remainder << 32) | *digit as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn shift_left_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return a.to_vec();
+    }
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for digit in a {
+        result.push((digit << shift) | carry);
+        carry = digit >> (32 - shift);
+    }
+    result.push(carry);
+    result
+}
+
+fn shift_right_bits(a: &[u32], shift: u32) -> Vec<u32> {
+    if shift == 0 {
+        return a.to_vec();
+    }
+    let mut result = Vec::with_capacity(a.len());
+    for i in 0..a.len() {
+        let high = a.get(i + 1).map_or(0, |digit| digit << (32 - shift));
+        result.push((a[i] >> shift) | high);
+    }
+    trim(&mut result);
+    result
+}
+
+/// Long division of magnitudes, using Knuth's algorithm D.
+/// The divisor must not be zero.
+fn divmod_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitudes(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = divmod_digit(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+
+    // Normalise so that the top digit of the divisor has its high bit set,
+    // which keeps each estimated quotient digit within 2 of the true one.
+    let shift = b[b.len() - 1].leading_zeros();
+    let divisor = shift_left_bits(b, shift);
+    let divisor = &divisor[..b.len()];
+    let mut remainder = shift_left_bits(a, shift);
+    if remainder.len() == a.len() {
+        remainder.push(0);
+    }
+
+    let n = divisor.len();
+    let m = remainder.len() - n - 1;
+    let mut quotient = vec![0u32; m + 1];
+    let top = divisor[n - 1] as u64;
+    let second = divisor[n - 2] as u64;
+
+    for j in (0..=m).rev() {
+        let numerator = ((remainder[j + n] as u64) << 32) | remainder[j + n - 1] as u64;
+        let mut estimate = numerator / top;
+        let mut rest = numerator % top;
+        while estimate >= 1 << 32
+            || estimate * second > ((rest << 32) | remainder[j + n - 2] as u64)
+        {
+            estimate -= 1;
+            rest += top;
+            if rest >= 1 << 32 {
+                break;
+            }
+        }
+
+        // Subtract estimate * divisor from the current window of the remainder.
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let product = estimate * divisor[i] as u64 + carry;
+            carry = product >> 32;
+            let difference = remainder[i + j] as i64 - (product & 0xffff_ffff) as i64 - borrow;
+            remainder[i + j] = difference as u32;
+            borrow = if difference < 0 { 1 } else { 0 };
+        }
+        let difference = remainder[j + n] as i64 - carry as i64 - borrow;
+        remainder[j + n] = difference as u32;
+
+        // The estimate was one too large, so add the divisor back.
+        if difference < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = remainder[i + j] as u64 + divisor[i] as u64 + carry;
+                remainder[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            remainder[j + n] = remainder[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    remainder.truncate(n);
+    let remainder = shift_right_bits(&remainder, shift);
+    (quotient, remainder)
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self {
+            negative: false,
+            digits: vec![],
+        }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> Self {
+        trim(&mut digits);
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+        }
+    }
+
+    /// Parses a decimal integer with an optional leading `-`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        // Read nine decimal digits at a time.
+        let mut magnitude: Vec<u32> = vec![];
+        for chunk in digits
+            .as_bytes()
+            .chunks(9)
+            .map(|c| std::str::from_utf8(c).ok())
+        {
+            let chunk = chunk?;
+            let scale = 10u32.pow(chunk.len() as u32);
+            let value: u32 = chunk.parse().ok()?;
+            magnitude = add_magnitudes(&mul_magnitudes(&magnitude, &[scale]), &[value]);
+            trim(&mut magnitude);
+        }
+        Some(Self::from_parts(negative, magnitude))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_one(&self) -> bool {
+        !self.negative && self.digits == [1]
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.digits.first().is_none_or(|digit| digit % 2 == 0)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.digits.clone())
+    }
+
+    /// Division rounding towards zero, with a remainder of the same sign as `self`.
+    /// Returns `None` when dividing by zero.
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divmod_magnitudes(&self.digits, &other.digits);
+        Some((
+            Self::from_parts(self.negative != other.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// Division rounding towards negative infinity, with a remainder of the same sign as `other`.
+    pub fn div_mod_floor(&self, other: &Self) -> Option<(Self, Self)> {
+        let (quotient, remainder) = self.div_rem(other)?;
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            Some((&quotient - &Self::one(), &remainder + other))
+        } else {
+            Some((quotient, remainder))
+        }
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = Self::one();
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// The greatest common divisor, which is never negative.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).expect("b is not zero");
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    /// The number of bits in the magnitude.
+    pub fn bits(&self) -> u64 {
+        match self.digits.last() {
+            Some(top) => self.digits.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, digit| (acc << 32) | *digit as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// The nearest float, which is infinite if the integer is too large.
+    pub fn to_f64(&self) -> f64 {
+        // The top three digits hold more bits than a float can represent.
+        let len = self.digits.len();
+        let start = len.saturating_sub(3);
+        let top = self.digits[start..]
+            .iter()
+            .rev()
+            .fold(0.0, |acc, digit| acc * 4_294_967_296.0 + *digit as f64);
+        let value = top * 2f64.powi(32 * start as i32);
+        if self.negative { -value } else { value }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        Self::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.digits, &other.digits),
+            (true, true) => compare_magnitudes(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.digits, &other.digits));
+        }
+        // The signs differ, so subtract the smaller magnitude from the larger.
+        match compare_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitudes(&other.digits, &self.digits))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitudes(&self.digits, &other.digits),
+        )
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Split into nine decimal digits at a time, least significant first.
+        let mut chunks = vec![];
+        let mut magnitude = self.digits.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = divmod_digit(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        for text in [
+            "0",
+            "7",
+            "-42",
+            "4294967296",
+            "123456789012345678901234567890",
+        ] {
+            assert_eq!(big(text).to_string(), text);
+        }
+        assert_eq!(big("-0"), BigInt::zero());
+        assert!(BigInt::parse("12a").is_none());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(
+            big("2").pow(100).to_string(),
+            "1267650600228229401496703205376"
+        );
+    }
+
+    #[test]
+    fn test_division() {
+        let a = big("1267650600228229401496703205376");
+        let b = big("-98765432109876543210");
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(&(&quotient * &b) + &remainder, a);
+        assert!(remainder.abs() < b.abs());
+        assert!(!remainder.is_negative());
+
+        let (quotient, remainder) = big("-7").div_mod_floor(&big("2")).unwrap();
+        assert_eq!((quotient, remainder), (big("-4"), big("1")));
+        assert!(a.div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn test_division_identity() {
+        // Checks a * b + r == n for many multi-digit values, including
+        // divisors with small top digits that need the most normalising.
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..500 {
+            let n = BigInt::from_parts(false, (0..next() % 8 + 1).map(|_| next() as u32).collect());
+            let mut divisor: Vec<u32> = (0..next() % 4 + 1).map(|_| next() as u32).collect();
+            if let Some(top) = divisor.last_mut() {
+                *top >>= next() % 32;
+            }
+            let b = BigInt::from_parts(false, divisor);
+            if b.is_zero() {
+                continue;
+            }
+            let (quotient, remainder) = n.div_rem(&b).unwrap();
+            assert_eq!(&(&quotient * &b) + &remainder, n);
+            assert!(remainder < b);
+        }
+    }
+
+    #[test]
+    fn test_gcd_and_conversions() {
+        assert_eq!(big("462").gcd(&big("-1071")), big("21"));
+        assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(
+            big("1267650600228229401496703205376").to_f64(),
+            2f64.powi(100)
+        );
+    }
+}
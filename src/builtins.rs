@@ -1,28 +1,628 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::f64::consts::{LN_2, PI, SQRT_2};
+use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::ast::Expr;
+use crate::bigint::BigInt;
+use crate::complex::Complex;
+use crate::dual::Dual;
+use crate::interpreter::Interpreter;
+use crate::interval::Interval;
+use crate::matrix::Matrix;
+use crate::memo::Memo;
+use crate::number_theory;
+use crate::numeric::{self, DEFAULT_TOLERANCE};
+use crate::ode::{self, Event, Method, State};
+use crate::polynomial::Polynomial;
+use crate::random::Rng;
+use crate::rational::Rational;
 use crate::runtime::RuntimeValue;
+use crate::special;
+use crate::stats;
+use crate::stream::Stream;
+use crate::symbolic::{self, Symbolic};
+use crate::units::{Quantity, Unit};
+
+/// Applies a math function to each element of a list. The math functions
+/// call this for lists, so they map over nested lists element by element.
+fn map_elements(
+    value: RuntimeValue,
+    function: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { elements } => Ok(RuntimeValue::List {
+            elements: elements
+                .into_iter()
+                .map(function)
+                .collect::<Result<_, _>>()?,
+        }),
+        _ => function(value),
+    }
+}
 
 pub fn sin(value: RuntimeValue) -> Result<RuntimeValue, String> {
     match value {
-        RuntimeValue::Number(n) => Ok(RuntimeValue::Number(n.sin())),
-        _ => Err("sin() expects a number".into()),
+        RuntimeValue::List { .. } => map_elements(value, sin),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.sin())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.sin())),
+        RuntimeValue::Interval(i) => Ok(RuntimeValue::Interval(i.sin())),
+        _ => match value.as_f64() {
+            Some(n) => Ok(RuntimeValue::Number(n.sin())),
+            None => Err("sin() expects a number".into()),
+        },
     }
 }
 
 pub fn cos(value: RuntimeValue) -> Result<RuntimeValue, String> {
     match value {
-        RuntimeValue::Number(n) => Ok(RuntimeValue::Number(n.cos())),
-        _ => Err("cos() expects a number".into()),
+        RuntimeValue::List { .. } => map_elements(value, cos),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.cos())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.cos())),
+        RuntimeValue::Interval(i) => Ok(RuntimeValue::Interval(i.cos())),
+        _ => match value.as_f64() {
+            Some(n) => Ok(RuntimeValue::Number(n.cos())),
+            None => Err("cos() expects a number".into()),
+        },
     }
 }
 
+// Functions of real numbers only return complex numbers when there is no
+// real result, as for the square root or logarithm of a negative number.
+
 pub fn sqrt(value: RuntimeValue) -> Result<RuntimeValue, String> {
     match value {
-        RuntimeValue::Number(n) => Ok(RuntimeValue::Number(n.sqrt())),
-        _ => Err("sqrt() expects a number".into()),
+        RuntimeValue::List { .. } => map_elements(value, sqrt),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.sqrt())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.sqrt())),
+        RuntimeValue::Interval(i) => Ok(RuntimeValue::Interval(i.sqrt()?)),
+        _ => match value.as_f64() {
+            Some(n) if n < 0.0 => Ok(RuntimeValue::Complex(Complex::real(n).sqrt())),
+            Some(n) => Ok(RuntimeValue::Number(n.sqrt())),
+            None => Err("sqrt() expects a number".into()),
+        },
+    }
+}
+
+pub fn exp(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, exp),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.exp())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.exp())),
+        RuntimeValue::Interval(i) => Ok(RuntimeValue::Interval(i.increasing(f64::exp))),
+        _ => match value.as_f64() {
+            Some(n) => Ok(RuntimeValue::Number(n.exp())),
+            None => Err("exp() expects a number".into()),
+        },
+    }
+}
+
+pub fn ln(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, ln),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.ln())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.ln())),
+        RuntimeValue::Interval(i) if i.low < 0.0 => Err(format!(
+            "ln() expects an interval of non-negative numbers, found {i}"
+        )),
+        RuntimeValue::Interval(i) => Ok(RuntimeValue::Interval(i.increasing(f64::ln))),
+        _ => match value.as_f64() {
+            Some(n) if n < 0.0 => Ok(RuntimeValue::Complex(Complex::real(n).ln())),
+            Some(n) => Ok(RuntimeValue::Number(n.ln())),
+            None => Err("ln() expects a number".into()),
+        },
+    }
+}
+
+/// The absolute value of a real number, or the modulus of a complex number.
+pub fn abs(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, abs),
+        RuntimeValue::Number(n) => Ok(RuntimeValue::Number(n.abs())),
+        RuntimeValue::Integer(n) => Ok(RuntimeValue::Integer(n.abs())),
+        RuntimeValue::Rational(r) if r.numerator().is_negative() => {
+            Ok(RuntimeValue::Rational(r.neg()))
+        }
+        RuntimeValue::Rational(_) => Ok(value),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Number(z.abs())),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.abs())),
+        _ => Err("abs() expects a number".into()),
+    }
+}
+
+/// The angle of a number from the positive real axis, in (-pi, pi].
+pub fn arg(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value.as_complex() {
+        Some(z) => Ok(RuntimeValue::Number(z.arg())),
+        // The angle of a real number is constant, 0 or pi.
+        None if let RuntimeValue::Dual(d) = value => {
+            Ok(RuntimeValue::Number(Complex::real(d.value).arg()))
+        }
+        None if matches!(value, RuntimeValue::List { .. }) => map_elements(value, arg),
+        None => Err("arg() expects a number".into()),
+    }
+}
+
+pub fn conj(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, conj),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.conj())),
+        RuntimeValue::Dual(_) => Ok(value),
+        _ if value.as_f64().is_some() => Ok(value),
+        _ => Err("conj() expects a number".into()),
+    }
+}
+
+pub fn re(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, re),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Number(z.re)),
+        RuntimeValue::Dual(_) => Ok(value),
+        _ if value.as_f64().is_some() => Ok(value),
+        _ => Err("re() expects a number".into()),
+    }
+}
+
+pub fn im(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, im),
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Number(z.im)),
+        RuntimeValue::Dual(_) => Ok(RuntimeValue::Integer(BigInt::zero())),
+        _ if value.as_f64().is_some() => Ok(RuntimeValue::Integer(BigInt::zero())),
+        _ => Err("im() expects a number".into()),
+    }
+}
+
+/// How a function of one real number varies, which decides how it applies to an interval.
+#[derive(Clone, Copy)]
+enum Shape {
+    Increasing,
+    Decreasing,
+    /// A step function like `floor`, which is exact at the bounds.
+    Steps,
+    /// Neither increasing nor decreasing, so the bounds do not bound the result.
+    Varying,
+}
+
+/// Applies a function of one real number to lists element by element,
+/// to dual numbers by the chain rule given its derivative, and to intervals
+/// when its shape allows.
+fn elementary(
+    name: &str,
+    value: RuntimeValue,
+    this: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+    f: fn(f64) -> f64,
+    derivative: fn(f64) -> f64,
+    shape: Shape,
+) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, this),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(d.chain(f(d.value), derivative(d.value)))),
+        RuntimeValue::Interval(i) => {
+            let result = match shape {
+                Shape::Increasing => i.increasing(f),
+                Shape::Decreasing => i.decreasing(f),
+                Shape::Steps => i.steps(f),
+                Shape::Varying => {
+                    return Err(format!("{name}() does not support intervals, found {i}"));
+                }
+            };
+            if result.low.is_nan() || result.high.is_nan() {
+                Err(format!(
+                    "{name}() expects an interval within its domain, found {i}"
+                ))
+            } else {
+                Ok(RuntimeValue::Interval(result))
+            }
+        }
+        _ => match value.as_f64() {
+            Some(x) => Ok(RuntimeValue::Number(f(x))),
+            None => Err(format!("{name}() expects a number, found {value}")),
+        },
+    }
+}
+
+pub fn tan(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.sin() / z.cos())),
+        _ => elementary(
+            "tan",
+            value,
+            tan,
+            f64::tan,
+            |x| 1.0 / (x.cos() * x.cos()),
+            Shape::Varying,
+        ),
+    }
+}
+
+// The inverse trigonometric and hyperbolic functions give nan outside
+// their real domains, as floats do.
+
+pub fn asin(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "asin",
+        value,
+        asin,
+        f64::asin,
+        |x| 1.0 / (1.0 - x * x).sqrt(),
+        Shape::Increasing,
+    )
+}
+
+pub fn acos(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "acos",
+        value,
+        acos,
+        f64::acos,
+        |x| -1.0 / (1.0 - x * x).sqrt(),
+        Shape::Decreasing,
+    )
+}
+
+pub fn atan(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "atan",
+        value,
+        atan,
+        f64::atan,
+        |x| 1.0 / (1.0 + x * x),
+        Shape::Increasing,
+    )
+}
+
+pub fn sinh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(
+            (z.exp() - (-z).exp()) / Complex::real(2.0),
+        )),
+        _ => elementary("sinh", value, sinh, f64::sinh, f64::cosh, Shape::Increasing),
+    }
+}
+
+pub fn cosh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(
+            (z.exp() + (-z).exp()) / Complex::real(2.0),
+        )),
+        _ => elementary("cosh", value, cosh, f64::cosh, f64::sinh, Shape::Varying),
+    }
+}
+
+pub fn tanh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Complex(z) => {
+            let (a, b) = (z.exp(), (-z).exp());
+            Ok(RuntimeValue::Complex((a - b) / (a + b)))
+        }
+        _ => elementary(
+            "tanh",
+            value,
+            tanh,
+            f64::tanh,
+            |x| 1.0 / (x.cosh() * x.cosh()),
+            Shape::Increasing,
+        ),
+    }
+}
+
+pub fn asinh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "asinh",
+        value,
+        asinh,
+        f64::asinh,
+        |x| 1.0 / (x * x + 1.0).sqrt(),
+        Shape::Increasing,
+    )
+}
+
+pub fn acosh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "acosh",
+        value,
+        acosh,
+        f64::acosh,
+        |x| 1.0 / (x * x - 1.0).sqrt(),
+        Shape::Increasing,
+    )
+}
+
+pub fn atanh(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "atanh",
+        value,
+        atanh,
+        f64::atanh,
+        |x| 1.0 / (1.0 - x * x),
+        Shape::Increasing,
+    )
+}
+
+/// A logarithm in another base, which like `ln` is complex for negative numbers.
+fn logarithm(
+    name: &str,
+    value: RuntimeValue,
+    this: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+    f: fn(f64) -> f64,
+    base: f64,
+) -> Result<RuntimeValue, String> {
+    let scale = Complex::real(base.ln());
+    match value {
+        RuntimeValue::Complex(z) => Ok(RuntimeValue::Complex(z.ln() / scale)),
+        RuntimeValue::Dual(d) => Ok(RuntimeValue::Dual(
+            d.chain(f(d.value), 1.0 / (d.value * base.ln())),
+        )),
+        _ => match value.as_f64() {
+            Some(x) if x < 0.0 => Ok(RuntimeValue::Complex(Complex::real(x).ln() / scale)),
+            // Lists and intervals are all that is left, and the derivative is unused.
+            _ => elementary(name, value, this, f, f64::recip, Shape::Increasing),
+        },
+    }
+}
+
+pub fn log10(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    logarithm("log10", value, log10, f64::log10, 10.0)
+}
+
+pub fn log2(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    logarithm("log2", value, log2, f64::log2, 2.0)
+}
+
+/// `ln(1 + x)`, accurate even when `x` is tiny.
+pub fn log1p(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "log1p",
+        value,
+        log1p,
+        f64::ln_1p,
+        |x| 1.0 / (1.0 + x),
+        Shape::Increasing,
+    )
+}
+
+/// `exp(x) - 1`, accurate even when `x` is tiny.
+pub fn expm1(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "expm1",
+        value,
+        expm1,
+        f64::exp_m1,
+        f64::exp,
+        Shape::Increasing,
+    )
+}
+
+pub fn exp2(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "exp2",
+        value,
+        exp2,
+        f64::exp2,
+        |x| x.exp2() * LN_2,
+        Shape::Increasing,
+    )
+}
+
+/// The real cube root, which unlike `x ^ (1/3)` is negative for negative numbers.
+pub fn cbrt(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    elementary(
+        "cbrt",
+        value,
+        cbrt,
+        f64::cbrt,
+        |x| 1.0 / (3.0 * x.cbrt() * x.cbrt()),
+        Shape::Increasing,
+    )
+}
+
+/// Applies a rounding function, keeping exact numbers exact.
+/// Dual numbers have derivative 0, since the steps are flat.
+fn rounding(
+    name: &str,
+    value: RuntimeValue,
+    this: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+    f: fn(f64) -> f64,
+    exact: fn(&Rational) -> BigInt,
+) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Integer(_) => Ok(value),
+        RuntimeValue::Rational(r) => Ok(RuntimeValue::Integer(exact(&r))),
+        _ => elementary(name, value, this, f, |_| 0.0, Shape::Steps),
+    }
+}
+
+fn ceil_rational(r: &Rational) -> BigInt {
+    -&r.neg().floor()
+}
+
+/// Rounds toward zero.
+fn trunc_rational(r: &Rational) -> BigInt {
+    if r.numerator().is_negative() {
+        ceil_rational(r)
+    } else {
+        r.floor()
     }
 }
 
+/// Rounds to the nearest integer, and halves away from zero.
+fn round_rational(r: &Rational) -> BigInt {
+    let half = Rational::new(BigInt::one(), BigInt::from(2)).expect("2 is not 0");
+    if r.numerator().is_negative() {
+        -&r.neg().add(&half).floor()
+    } else {
+        r.add(&half).floor()
+    }
+}
+
+pub fn floor(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    rounding("floor", value, floor, f64::floor, Rational::floor)
+}
+
+pub fn ceil(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    rounding("ceil", value, ceil, f64::ceil, ceil_rational)
+}
+
+/// Rounds to the nearest integer, and halves away from zero.
+pub fn round(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    rounding("round", value, round, f64::round, round_rational)
+}
+
+/// Rounds toward zero.
+pub fn trunc(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    rounding("trunc", value, trunc, f64::trunc, trunc_rational)
+}
+
+/// The part after the decimal point, `x - trunc(x)`, with the sign of `x`.
+pub fn fract(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Integer(_) => Ok(RuntimeValue::Integer(BigInt::zero())),
+        RuntimeValue::Rational(r) => Ok(RuntimeValue::from_rational(
+            r.sub(&Rational::from_integer(trunc_rational(&r))),
+        )),
+        _ => elementary("fract", value, fract, f64::fract, |_| 1.0, Shape::Varying),
+    }
+}
+
+/// -1, 0 or 1, as the number is negative, zero or positive.
+pub fn sign(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let of_exact = |negative: bool, zero: bool| {
+        RuntimeValue::Integer(BigInt::from(if zero {
+            0
+        } else if negative {
+            -1
+        } else {
+            1
+        }))
+    };
+    match &value {
+        RuntimeValue::Integer(n) => Ok(of_exact(n.is_negative(), n.is_zero())),
+        RuntimeValue::Rational(r) => Ok(of_exact(r.numerator().is_negative(), r.is_zero())),
+        _ => elementary(
+            "sign",
+            value,
+            sign,
+            |x| {
+                if x == 0.0 || x.is_nan() {
+                    x
+                } else {
+                    x.signum()
+                }
+            },
+            |_| 0.0,
+            Shape::Steps,
+        ),
+    }
+}
+
+/// Applies a function of two numbers, like `atan2(y, x)`, to dual numbers
+/// if either is one, and otherwise to reals.
+fn binary(
+    name: &'static str,
+    first: RuntimeValue,
+    f: fn(f64, f64) -> f64,
+    dual: fn(Dual, Dual) -> Dual,
+) -> Result<RuntimeValue, String> {
+    collect_args(name, 1, vec![first], move |args| {
+        let (a, b) = (&args[0], &args[1]);
+        if (matches!(a, RuntimeValue::Dual(_)) || matches!(b, RuntimeValue::Dual(_)))
+            && let (Some(a), Some(b)) = (a.as_dual(), b.as_dual())
+        {
+            return Ok(RuntimeValue::Dual(dual(a, b)));
+        }
+        Ok(RuntimeValue::Number(f(
+            expect_real(name, a)?,
+            expect_real(name, b)?,
+        )))
+    })
+}
+
+/// The angle of the point `(x, y)` from the positive x axis, in (-pi, pi].
+/// The value of a function of real numbers, carrying the derivatives of any
+/// dual numbers among `args` by the chain rule, given the function's
+/// derivatives in each of them.
+fn chain_partials(
+    args: &[RuntimeValue],
+    value: f64,
+    partials: impl FnOnce() -> Vec<f64>,
+) -> RuntimeValue {
+    if !args.iter().any(|arg| matches!(arg, RuntimeValue::Dual(_))) {
+        return RuntimeValue::Number(value);
+    }
+    let derivative = args
+        .iter()
+        .zip(partials())
+        .filter_map(|(arg, partial)| match arg {
+            RuntimeValue::Dual(d) if d.derivative != 0.0 => Some(partial * d.derivative),
+            _ => None,
+        })
+        .sum();
+    RuntimeValue::Dual(Dual::new(value, derivative))
+}
+
+pub fn atan2(y: RuntimeValue) -> Result<RuntimeValue, String> {
+    binary("atan2", y, f64::atan2, Dual::atan2)
+}
+
+/// `sqrt(x ^ 2 + y ^ 2)`, without overflowing for large numbers.
+pub fn hypot(x: RuntimeValue) -> Result<RuntimeValue, String> {
+    binary("hypot", x, f64::hypot, Dual::hypot)
+}
+
+/// The smaller or larger of two numbers, chosen by `pick_first`.
+/// Exact numbers compare exactly, and floats ignore nan, as `f64::min` does.
+fn extremum(
+    name: &'static str,
+    first: RuntimeValue,
+    pick_first: fn(Ordering) -> bool,
+    float: fn(f64, f64) -> f64,
+    interval: fn(&Interval, &Interval) -> Interval,
+) -> Result<RuntimeValue, String> {
+    collect_args(name, 1, vec![first], move |args| {
+        let (a, b) = (&args[0], &args[1]);
+        let expect_interval = |value: &RuntimeValue| {
+            value
+                .as_interval()
+                .ok_or_else(|| format!("{name}() expects a real number, found {value}"))
+        };
+        match (a, b) {
+            (RuntimeValue::Interval(_), _) | (_, RuntimeValue::Interval(_)) => Ok(
+                RuntimeValue::Interval(interval(&expect_interval(a)?, &expect_interval(b)?)),
+            ),
+            (RuntimeValue::Dual(_), _) | (_, RuntimeValue::Dual(_)) => {
+                let (Some(x), Some(y)) = (a.as_dual(), b.as_dual()) else {
+                    return Err(format!("{name}() expects real numbers, found {a} and {b}"));
+                };
+                let first = x.value.partial_cmp(&y.value).is_some_and(pick_first);
+                Ok(RuntimeValue::Dual(if first { x } else { y }))
+            }
+            (RuntimeValue::Number(_), _) | (_, RuntimeValue::Number(_)) => Ok(
+                RuntimeValue::Number(float(expect_real(name, a)?, expect_real(name, b)?)),
+            ),
+            _ => match a.compare_numbers(b) {
+                Some(ordering) if a.as_rational().is_some() && b.as_rational().is_some() => {
+                    Ok(if pick_first(ordering) {
+                        a.clone()
+                    } else {
+                        b.clone()
+                    })
+                }
+                _ => Err(format!("{name}() expects real numbers, found {a} and {b}")),
+            },
+        }
+    })
+}
+
+pub fn min(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    extremum("min", a, Ordering::is_le, f64::min, Interval::min)
+}
+
+pub fn max(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    extremum("max", a, Ordering::is_ge, f64::max, Interval::max)
+}
+
 // This function takes no argument (Nil).
 pub fn clock(value: RuntimeValue) -> Result<RuntimeValue, String> {
     match value {
@@ -58,3 +658,2116 @@ pub fn println(value: RuntimeValue) -> Result<RuntimeValue, String> {
     println!("{value}");
     Ok(RuntimeValue::Nil)
 }
+
+/// The error for a dual number passed to a builtin that cannot carry its derivative.
+fn not_differentiable(name: &str, value: &RuntimeValue) -> String {
+    format!("{name}() cannot be differentiated, found {value}")
+}
+
+/// Converts a non-negative integral number to a count.
+fn expect_count(name: &str, value: &RuntimeValue) -> Result<usize, String> {
+    match value {
+        RuntimeValue::Dual(_) => Err(not_differentiable(name, value)),
+        RuntimeValue::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as usize),
+        RuntimeValue::Integer(n) if !n.is_negative() => n
+            .to_i64()
+            .map(|n| n as usize)
+            .ok_or_else(|| format!("{name}() expects a smaller count, found {value}")),
+        _ => Err(format!(
+            "{name}() expects a non-negative integer, found {value}"
+        )),
+    }
+}
+
+/// Converts an integral number to an `i64`.
+fn expect_i64(name: &str, value: &RuntimeValue) -> Result<i64, String> {
+    match value {
+        RuntimeValue::Dual(_) => Err(not_differentiable(name, value)),
+        RuntimeValue::Number(n) if n.fract() == 0.0 && n.abs() < 2f64.powi(63) => Ok(*n as i64),
+        RuntimeValue::Integer(n) => n
+            .to_i64()
+            .ok_or_else(|| format!("{name}() expects a smaller integer, found {value}")),
+        _ => Err(format!("{name}() expects an integer, found {value}")),
+    }
+}
+
+/// Converts an integral number to an exact integer.
+fn expect_integer(name: &str, value: &RuntimeValue) -> Result<BigInt, String> {
+    match value {
+        RuntimeValue::Integer(n) => Ok(n.clone()),
+        _ => expect_i64(name, value).map(BigInt::from),
+    }
+}
+
+fn expect_function(name: &str, value: &RuntimeValue) -> Result<(), String> {
+    if value.is_callable() {
+        Ok(())
+    } else {
+        Err(format!("{name}() expects a function, found {value}"))
+    }
+}
+
+// Stream constructors.
+// Builtins taking more than one argument are curried, so that
+// `iterate(f, x0)` is the same as `iterate(f)(x0)`.
+
+pub fn iterate(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("iterate", &function)?;
+    Ok(RuntimeValue::native_closure("iterate", move |initial| {
+        Ok(RuntimeValue::Stream(Stream::iterate(
+            function.clone(),
+            initial,
+        )))
+    }))
+}
+
+pub fn repeat(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Stream(Stream::repeat(value)))
+}
+
+pub fn cycle(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { elements } if !elements.is_empty() => {
+            Ok(RuntimeValue::Stream(Stream::cycle(Rc::new(elements), 0)))
+        }
+        _ => Err("cycle() expects a non-empty list".into()),
+    }
+}
+
+pub fn unfold(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("unfold", &function)?;
+    Ok(RuntimeValue::native_closure("unfold", move |seed| {
+        Ok(RuntimeValue::Stream(Stream::unfold(function.clone(), seed)))
+    }))
+}
+
+// Sequence operations.
+// These are lazy on streams and strict on lists.
+
+pub fn map(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("map", &function)?;
+    Ok(RuntimeValue::native_closure(
+        "map",
+        move |sequence| match sequence {
+            RuntimeValue::Stream(stream) => Ok(RuntimeValue::Stream(stream.map(function.clone()))),
+            RuntimeValue::List { elements } => {
+                let elements = elements
+                    .into_iter()
+                    .map(|value| Interpreter::call(&function, value))
+                    .collect::<Result<_, _>>()?;
+                Ok(RuntimeValue::List { elements })
+            }
+            other => Err(format!("map() expects a list or stream, found {other}")),
+        },
+    ))
+}
+
+pub fn filter(predicate: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("filter", &predicate)?;
+    Ok(RuntimeValue::native_closure(
+        "filter",
+        move |sequence| match sequence {
+            RuntimeValue::Stream(stream) => {
+                Ok(RuntimeValue::Stream(stream.filter(predicate.clone())))
+            }
+            RuntimeValue::List { elements } => {
+                let mut kept = vec![];
+                for value in elements {
+                    if Interpreter::call(&predicate, value.clone())?.is_truthy() {
+                        kept.push(value);
+                    }
+                }
+                Ok(RuntimeValue::List { elements: kept })
+            }
+            other => Err(format!("filter() expects a list or stream, found {other}")),
+        },
+    ))
+}
+
+pub fn take_while(predicate: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("take_while", &predicate)?;
+    Ok(RuntimeValue::native_closure(
+        "take_while",
+        move |sequence| match sequence {
+            RuntimeValue::Stream(stream) => {
+                Ok(RuntimeValue::Stream(stream.take_while(predicate.clone())))
+            }
+            RuntimeValue::List { elements } => {
+                let mut kept = vec![];
+                for value in elements {
+                    if !Interpreter::call(&predicate, value.clone())?.is_truthy() {
+                        break;
+                    }
+                    kept.push(value);
+                }
+                Ok(RuntimeValue::List { elements: kept })
+            }
+            other => Err(format!(
+                "take_while() expects a list or stream, found {other}"
+            )),
+        },
+    ))
+}
+
+/// Forces the first `n` elements of a stream into a list.
+pub fn take(count: RuntimeValue) -> Result<RuntimeValue, String> {
+    let count = expect_count("take", &count)?;
+    Ok(RuntimeValue::native_closure(
+        "take",
+        move |sequence| match sequence {
+            RuntimeValue::Stream(stream) => Ok(RuntimeValue::List {
+                elements: stream.take(count)?,
+            }),
+            RuntimeValue::List { mut elements } => {
+                elements.truncate(count);
+                Ok(RuntimeValue::List { elements })
+            }
+            other => Err(format!("take() expects a list or stream, found {other}")),
+        },
+    ))
+}
+
+// List functions.
+// Those reducing a sequence to one value also consume streams,
+// forcing elements only until the result is known.
+
+/// The longest list `range` builds.
+const MAX_RANGE: u64 = 10_000_000;
+
+/// Iterates over the elements of a list or stream.
+fn elements(
+    name: &str,
+    sequence: RuntimeValue,
+) -> Result<Box<dyn Iterator<Item = Result<RuntimeValue, String>>>, String> {
+    match sequence {
+        RuntimeValue::List { elements } => Ok(Box::new(elements.into_iter().map(Ok))),
+        RuntimeValue::Stream(stream) => Ok(Box::new(stream.iter())),
+        other => Err(format!("{name}() expects a list or stream, found {other}")),
+    }
+}
+
+/// The number of elements of a list, or characters of a string.
+pub fn len(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { elements } => Ok(integer_result(elements.len() as u64)),
+        RuntimeValue::String(s) => Ok(integer_result(s.chars().count() as u64)),
+        other => Err(format!("len() expects a list or string, found {other}")),
+    }
+}
+
+pub fn head(sequence: RuntimeValue) -> Result<RuntimeValue, String> {
+    match sequence {
+        RuntimeValue::List { elements } if !elements.is_empty() => {
+            Ok(elements.into_iter().next().unwrap_or(RuntimeValue::Nil))
+        }
+        RuntimeValue::Stream(stream) => match stream.force()? {
+            Some((head, _)) => Ok(head),
+            None => Err("head() expects a non-empty list or stream".into()),
+        },
+        _ => Err(format!(
+            "head() expects a non-empty list or stream, found {sequence}"
+        )),
+    }
+}
+
+/// Everything but the first element, which stays lazy for a stream.
+pub fn tail(sequence: RuntimeValue) -> Result<RuntimeValue, String> {
+    match sequence {
+        RuntimeValue::List { mut elements } if !elements.is_empty() => {
+            elements.remove(0);
+            Ok(RuntimeValue::List { elements })
+        }
+        RuntimeValue::Stream(stream) => match stream.force()? {
+            Some((_, tail)) => Ok(RuntimeValue::Stream(tail)),
+            None => Err("tail() expects a non-empty list or stream".into()),
+        },
+        _ => Err(format!(
+            "tail() expects a non-empty list or stream, found {sequence}"
+        )),
+    }
+}
+
+/// Combines the elements from the left, as `f(f(f(init, x1), x2), x3)`.
+pub fn foldl(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("foldl", &function)?;
+    collect_args("foldl", 2, vec![], move |args| {
+        let [initial, sequence] = <[RuntimeValue; 2]>::try_from(args).expect("two arguments");
+        elements("foldl", sequence)?.try_fold(initial, |accumulator, value| {
+            let partial = Interpreter::call(&function, accumulator)?;
+            Interpreter::call(&partial, value?)
+        })
+    })
+}
+
+/// Combines the elements from the right, as `f(x1, f(x2, f(x3, init)))`.
+pub fn foldr(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("foldr", &function)?;
+    collect_args("foldr", 2, vec![], move |args| {
+        let [initial, sequence] = <[RuntimeValue; 2]>::try_from(args).expect("two arguments");
+        let values = elements("foldr", sequence)?.collect::<Result<Vec<_>, _>>()?;
+        values
+            .into_iter()
+            .rev()
+            .try_fold(initial, |accumulator, value| {
+                let partial = Interpreter::call(&function, value)?;
+                Interpreter::call(&partial, accumulator)
+            })
+    })
+}
+
+/// Pairs up elements at the same position, stopping at the end of the shorter sequence.
+pub fn zip(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    if !matches!(xs, RuntimeValue::List { .. } | RuntimeValue::Stream(_)) {
+        return Err(format!("zip() expects a list or stream, found {xs}"));
+    }
+    Ok(RuntimeValue::native_closure("zip", move |ys| {
+        let pairs = elements("zip", xs.clone())?
+            .zip(elements("zip", ys)?)
+            .map(|(x, y)| {
+                Ok(RuntimeValue::List {
+                    elements: vec![x?, y?],
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(RuntimeValue::List { elements: pairs })
+    }))
+}
+
+/// Pairs each element with its position, counting from 0.
+pub fn enumerate(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let elements = expect_list("enumerate", &xs)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| RuntimeValue::List {
+            elements: vec![integer_result(i as u64), x],
+        })
+        .collect();
+    Ok(RuntimeValue::List { elements })
+}
+
+pub fn reverse(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let mut elements = expect_list("reverse", &xs)?;
+    elements.reverse();
+    Ok(RuntimeValue::List { elements })
+}
+
+/// Sorts values by `key`, keeping equal ones in order.
+/// Values that cannot be compared, like a number and a string, are an error.
+fn sort_by_key(
+    name: &str,
+    values: Vec<RuntimeValue>,
+    keys: Vec<RuntimeValue>,
+) -> Result<RuntimeValue, String> {
+    let mut error = None;
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| {
+        keys[i].partial_cmp(&keys[j]).unwrap_or_else(|| {
+            error.get_or_insert_with(|| {
+                format!("{name}() cannot compare {} and {}", keys[i], keys[j])
+            });
+            Ordering::Equal
+        })
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+    let elements = order.into_iter().map(|i| values[i].clone()).collect();
+    Ok(RuntimeValue::List { elements })
+}
+
+/// Sorts numbers, strings or booleans in increasing order.
+pub fn sort(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let values = expect_list("sort", &xs)?;
+    sort_by_key("sort", values.clone(), values)
+}
+
+/// Sorts by the value of `key` for each element, calling it once per element.
+pub fn sort_by(key: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("sort_by", &key)?;
+    Ok(RuntimeValue::native_closure("sort_by", move |xs| {
+        let values = expect_list("sort_by", &xs)?;
+        let keys = values
+            .iter()
+            .map(|value| Interpreter::call(&key, value.clone()))
+            .collect::<Result<_, _>>()?;
+        sort_by_key("sort_by", values, keys)
+    }))
+}
+
+/// Whether `predicate` holds for some element, stopping at the first that it does.
+pub fn any(predicate: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("any", &predicate)?;
+    Ok(RuntimeValue::native_closure("any", move |sequence| {
+        for value in elements("any", sequence)? {
+            if Interpreter::call(&predicate, value?)?.is_truthy() {
+                return Ok(RuntimeValue::Boolean(true));
+            }
+        }
+        Ok(RuntimeValue::Boolean(false))
+    }))
+}
+
+/// Whether `predicate` holds for every element, stopping at the first that it does not.
+pub fn all(predicate: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("all", &predicate)?;
+    Ok(RuntimeValue::native_closure("all", move |sequence| {
+        for value in elements("all", sequence)? {
+            if !Interpreter::call(&predicate, value?)?.is_truthy() {
+                return Ok(RuntimeValue::Boolean(false));
+            }
+        }
+        Ok(RuntimeValue::Boolean(true))
+    }))
+}
+
+/// The first element satisfying `predicate`, or `nil` if there is none.
+pub fn find(predicate: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("find", &predicate)?;
+    Ok(RuntimeValue::native_closure("find", move |sequence| {
+        for value in elements("find", sequence)? {
+            let value = value?;
+            if Interpreter::call(&predicate, value.clone())?.is_truthy() {
+                return Ok(value);
+            }
+        }
+        Ok(RuntimeValue::Nil)
+    }))
+}
+
+/// Joins a list of lists into one list.
+pub fn flatten(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let mut elements = vec![];
+    for x in expect_list("flatten", &xs)? {
+        match x {
+            RuntimeValue::List { elements: inner } => elements.extend(inner),
+            other => {
+                return Err(format!("flatten() expects a list of lists, found {other}"));
+            }
+        }
+    }
+    Ok(RuntimeValue::List { elements })
+}
+
+/// The integers from `start` up to but excluding `end`.
+pub fn range(start: RuntimeValue) -> Result<RuntimeValue, String> {
+    let start = expect_integer("range", &start)?;
+    Ok(RuntimeValue::native_closure("range", move |end| {
+        let end = expect_integer("range", &end)?;
+        let span = &end - &start;
+        let length = if span.is_negative() {
+            0
+        } else {
+            span.to_i64().unwrap_or(i64::MAX)
+        };
+        if length as u64 > MAX_RANGE {
+            return Err(format!(
+                "range() builds at most {MAX_RANGE} integers, found {length}"
+            ));
+        }
+        let one = BigInt::one();
+        let mut elements = Vec::with_capacity(length as usize);
+        let mut n = start.clone();
+        for _ in 0..length {
+            let next = &n + &one;
+            elements.push(RuntimeValue::Integer(n));
+            n = next;
+        }
+        Ok(RuntimeValue::List { elements })
+    }))
+}
+
+// Memoization
+
+pub fn memo(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("memo", &function)?;
+    Ok(RuntimeValue::Memoized(Memo::new(function, None)))
+}
+
+/// Like `memo`, but keeps at most `capacity` results,
+/// evicting the least recently used one when the cache is full.
+pub fn memo_bounded(capacity: RuntimeValue) -> Result<RuntimeValue, String> {
+    let capacity = match expect_count("memo_bounded", &capacity)? {
+        0 => return Err("memo_bounded() expects a capacity of at least 1".into()),
+        n => n,
+    };
+    Ok(RuntimeValue::native_closure(
+        "memo_bounded",
+        move |function| {
+            expect_function("memo_bounded", &function)?;
+            Ok(RuntimeValue::Memoized(Memo::new(function, Some(capacity))))
+        },
+    ))
+}
+
+pub fn memo_clear(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Memoized(memo) => {
+            memo.clear();
+            Ok(RuntimeValue::Nil)
+        }
+        _ => Err("memo_clear() expects a memoized function".into()),
+    }
+}
+
+pub fn memo_size(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::Memoized(memo) => Ok(RuntimeValue::Number(memo.len() as f64)),
+        _ => Err("memo_size() expects a memoized function".into()),
+    }
+}
+
+/// The derivative of a function of one number, found symbolically,
+/// or of a polynomial.
+pub fn diff(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    match function {
+        RuntimeValue::Polynomial(p) => Ok(RuntimeValue::Polynomial(p.derivative())),
+        _ => symbolic::differentiate_function(&function),
+    }
+}
+
+/// Evaluates a function on dual numbers, with `seed(i)` as the derivative
+/// of the i-th input, and returns its value.
+fn call_with_duals(
+    name: &str,
+    function: &RuntimeValue,
+    input: &RuntimeValue,
+    seed: impl Fn(usize) -> f64,
+) -> Result<RuntimeValue, String> {
+    let dual = |i: usize, value: &RuntimeValue| match value {
+        RuntimeValue::Dual(_) => Err(format!("{name}() cannot be nested")),
+        _ => Ok(RuntimeValue::Dual(Dual::new(
+            expect_real(name, value)?,
+            seed(i),
+        ))),
+    };
+    let input = match input {
+        RuntimeValue::List { elements } => RuntimeValue::List {
+            elements: elements
+                .iter()
+                .enumerate()
+                .map(|(i, value)| dual(i, value))
+                .collect::<Result<_, _>>()?,
+        },
+        _ => dual(0, input)?,
+    };
+    Interpreter::call(function, input)
+}
+
+/// The derivative carried by a result, which is 0 for a constant.
+fn tangent(name: &str, value: &RuntimeValue) -> Result<f64, String> {
+    match value {
+        RuntimeValue::Dual(d) => Ok(d.derivative),
+        _ => match value.as_f64() {
+            Some(_) => Ok(0.0),
+            None => Err(format!(
+                "{name}() expects the function to return a real number, found {value}"
+            )),
+        },
+    }
+}
+
+/// The derivative of a function at a number, or its gradient at a list of
+/// numbers, found exactly with forward-mode automatic differentiation.
+pub fn grad(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("grad", &function)?;
+    Ok(RuntimeValue::native_closure("grad", move |input| {
+        let RuntimeValue::List { elements } = &input else {
+            let result = call_with_duals("grad", &function, &input, |_| 1.0)?;
+            return Ok(RuntimeValue::Number(tangent("grad", &result)?));
+        };
+        let partials = (0..elements.len())
+            .map(|i| {
+                let seed = |j| if i == j { 1.0 } else { 0.0 };
+                let result = call_with_duals("grad", &function, &input, seed)?;
+                Ok(RuntimeValue::Number(tangent("grad", &result)?))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(RuntimeValue::List { elements: partials })
+    }))
+}
+
+/// The matrix of partial derivatives of a function from a list of numbers
+/// to a list of numbers, with a row for each output and a column for each input.
+pub fn jacobian(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("jacobian", &function)?;
+    Ok(RuntimeValue::native_closure("jacobian", move |input| {
+        let inputs = match &input {
+            RuntimeValue::List { elements } => elements.len(),
+            _ => 1,
+        };
+        let mut columns = vec![];
+        for i in 0..inputs {
+            let seed = |j| if i == j { 1.0 } else { 0.0 };
+            let column = match call_with_duals("jacobian", &function, &input, seed)? {
+                RuntimeValue::List { elements } => elements
+                    .iter()
+                    .map(|value| tangent("jacobian", value))
+                    .collect::<Result<Vec<_>, _>>()?,
+                value => vec![tangent("jacobian", &value)?],
+            };
+            columns.push(column);
+        }
+        let rows = (0..columns.first().map_or(0, Vec::len))
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect();
+        Ok(RuntimeValue::Matrix(Matrix::from_rows(rows)?))
+    }))
+}
+
+/// Converts a quantity to another unit of the same dimension,
+/// given as a string like "km/h" or as another quantity.
+pub fn to(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let quantity = match value {
+        RuntimeValue::Quantity(quantity) => quantity,
+        other => match other.as_f64() {
+            Some(n) => Quantity::new(n, Unit::none()),
+            None => return Err(format!("to() expects a quantity, found {other}")),
+        },
+    };
+    Ok(RuntimeValue::native_closure("to", move |unit| {
+        let unit = match unit {
+            RuntimeValue::String(text) => Unit::parse(&text)?,
+            RuntimeValue::Quantity(other) => other.unit().clone(),
+            other => {
+                return Err(format!("to() expects a unit like \"km/h\", found {other}"));
+            }
+        };
+        Ok(RuntimeValue::Quantity(quantity.to(unit)?))
+    }))
+}
+
+// Linear algebra
+
+/// Converts a matrix, a list of numbers (as a vector) or a list of rows
+/// to a matrix.
+fn expect_matrix(name: &str, value: &RuntimeValue) -> Result<Matrix, String> {
+    let number = |value: &RuntimeValue| {
+        value.as_f64().ok_or_else(|| {
+            format!("{name}() expects matrix elements to be real numbers, found {value}")
+        })
+    };
+    match value {
+        RuntimeValue::Matrix(matrix) => Ok(matrix.clone()),
+        RuntimeValue::List { elements }
+            if elements
+                .iter()
+                .all(|row| matches!(row, RuntimeValue::List { .. })) =>
+        {
+            let mut rows = vec![];
+            for row in elements {
+                if let RuntimeValue::List { elements } = row {
+                    rows.push(elements.iter().map(number).collect::<Result<_, _>>()?);
+                }
+            }
+            Matrix::from_rows(rows)
+        }
+        RuntimeValue::List { elements } => Ok(Matrix::vector(
+            elements.iter().map(number).collect::<Result<_, _>>()?,
+        )),
+        other => Err(format!("{name}() expects a matrix, found {other}")),
+    }
+}
+
+pub fn matrix(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Matrix(expect_matrix("matrix", &value)?))
+}
+
+/// Returns `[rows, columns]`.
+pub fn shape(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let matrix = expect_matrix("shape", &value)?;
+    Ok(RuntimeValue::List {
+        elements: vec![
+            RuntimeValue::Integer(BigInt::from(matrix.rows() as i64)),
+            RuntimeValue::Integer(BigInt::from(matrix.cols() as i64)),
+        ],
+    })
+}
+
+pub fn identity(size: RuntimeValue) -> Result<RuntimeValue, String> {
+    match expect_count("identity", &size)? {
+        0 => Err("identity() expects a positive size".into()),
+        n => Ok(RuntimeValue::Matrix(Matrix::identity(n))),
+    }
+}
+
+pub fn transpose(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Matrix(
+        expect_matrix("transpose", &value)?.transpose(),
+    ))
+}
+
+pub fn det(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Number(expect_matrix("det", &value)?.det()?))
+}
+
+pub fn inverse(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Matrix(
+        expect_matrix("inverse", &value)?.inverse()?,
+    ))
+}
+
+/// Solves the linear system `a * x = b` for `x`.
+pub fn solve(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    let a = expect_matrix("solve", &a)?;
+    Ok(RuntimeValue::native_closure("solve", move |b| {
+        let b = expect_matrix("solve", &b)?;
+        Ok(RuntimeValue::Matrix(a.solve(&b)?))
+    }))
+}
+
+/// A list of numbers, which are complex only if they are not real.
+fn complex_list(values: Vec<Complex>) -> RuntimeValue {
+    RuntimeValue::List {
+        elements: values
+            .into_iter()
+            .map(|value| {
+                if value.im == 0.0 {
+                    RuntimeValue::Number(value.re)
+                } else {
+                    RuntimeValue::Complex(value)
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Returns the eigenvalues as a list, with complex numbers for
+/// eigenvalues that are not real.
+pub fn eigenvalues(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let values = expect_matrix("eigenvalues", &value)?.eigenvalues()?;
+    Ok(complex_list(values))
+}
+
+// Intervals
+
+fn expect_interval(name: &str, value: &RuntimeValue) -> Result<Interval, String> {
+    match value {
+        RuntimeValue::Interval(i) => Ok(*i),
+        _ => Err(format!(
+            "{name}() expects an interval like [1.9 .. 2.1], found {value}"
+        )),
+    }
+}
+
+/// The interval from `low` to `high`, the same as `[low .. high]`.
+pub fn interval(low: RuntimeValue) -> Result<RuntimeValue, String> {
+    let low = expect_interval_bound(&low)?.low;
+    Ok(RuntimeValue::native_closure("interval", move |high| {
+        let high = expect_interval_bound(&high)?.high;
+        Ok(RuntimeValue::Interval(Interval::new(low, high)?))
+    }))
+}
+
+/// Reads a bound of `interval`, rounded outward when no float holds it exactly.
+fn expect_interval_bound(value: &RuntimeValue) -> Result<Interval, String> {
+    value
+        .as_interval_bound()
+        .ok_or_else(|| format!("interval() expects a real number, found {value}"))
+}
+
+pub fn width(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Number(
+        expect_interval("width", &value)?.width(),
+    ))
+}
+
+pub fn mid(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Number(expect_interval("mid", &value)?.mid()))
+}
+
+/// Whether an interval contains a number, or all of another interval.
+pub fn contains(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let outer = expect_interval("contains", &value)?;
+    Ok(RuntimeValue::native_closure(
+        "contains",
+        move |inner| match inner {
+            RuntimeValue::Interval(inner) => {
+                Ok(RuntimeValue::Boolean(outer.contains_interval(&inner)))
+            }
+            _ => Ok(RuntimeValue::Boolean(
+                outer.contains(expect_real("contains", &inner)?),
+            )),
+        },
+    ))
+}
+
+// Polynomials
+
+/// Reads a polynomial from a polynomial, a list of real coefficients with the
+/// highest degree first, a formula, or a function whose body is a polynomial.
+fn expect_polynomial(name: &str, value: &RuntimeValue) -> Result<Polynomial, String> {
+    let not_polynomial = || {
+        format!(
+            "{name}() expects a polynomial, coefficients like [1, -3, 2] \
+             or a formula like sym(x ^ 2 - 3 * x + 2), found {value}"
+        )
+    };
+    let real = |coefficient: &RuntimeValue| {
+        coefficient
+            .as_f64()
+            .ok_or_else(|| format!("{name}() expects real coefficients, found {coefficient}"))
+    };
+    let coefficients = match value {
+        RuntimeValue::Polynomial(p) => return Ok(p.clone()),
+        RuntimeValue::List { elements } => {
+            return Ok(Polynomial::new(
+                elements.iter().rev().map(real).collect::<Result<_, _>>()?,
+            ));
+        }
+        RuntimeValue::Symbolic(formula) => match symbolic::polynomial(formula.expr()) {
+            Some((_, coefficients)) => coefficients,
+            None => return Err(not_polynomial()),
+        },
+        RuntimeValue::Function { arg_name, body, .. } => match symbolic::polynomial(body) {
+            Some((None, coefficients)) => coefficients,
+            Some((Some(variable), coefficients)) if variable == *arg_name => coefficients,
+            _ => return Err(not_polynomial()),
+        },
+        _ => return Err(not_polynomial()),
+    };
+    Ok(Polynomial::new(
+        coefficients.iter().map(real).collect::<Result<_, _>>()?,
+    ))
+}
+
+pub fn poly(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(RuntimeValue::Polynomial(expect_polynomial("poly", &value)?))
+}
+
+/// The coefficients, highest degree first, as `poly` takes them.
+pub fn coeffs(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let p = expect_polynomial("coeffs", &value)?;
+    Ok(RuntimeValue::List {
+        elements: p
+            .coefficients()
+            .iter()
+            .rev()
+            .map(|c| RuntimeValue::Number(*c))
+            .collect(),
+    })
+}
+
+pub fn degree(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let p = expect_polynomial("degree", &value)?;
+    Ok(RuntimeValue::Integer(BigInt::from(p.degree() as i64)))
+}
+
+/// The antiderivative of a polynomial that is 0 at 0.
+pub fn integral(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let p = expect_polynomial("integral", &value)?;
+    Ok(RuntimeValue::Polynomial(p.integral()))
+}
+
+/// All roots, repeated by multiplicity, with complex numbers for roots that are not real.
+pub fn roots(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    Ok(complex_list(expect_polynomial("roots", &value)?.roots()?))
+}
+
+/// The real roots, smallest first.
+pub fn real_roots(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let roots = expect_polynomial("real_roots", &value)?.roots()?;
+    Ok(complex_list(
+        roots.into_iter().filter(|root| root.im == 0.0).collect(),
+    ))
+}
+
+/// The monic greatest common divisor of two polynomials.
+pub fn polygcd(p: RuntimeValue) -> Result<RuntimeValue, String> {
+    let p = expect_polynomial("polygcd", &p)?;
+    Ok(RuntimeValue::native_closure("polygcd", move |q| {
+        let q = expect_polynomial("polygcd", &q)?;
+        Ok(RuntimeValue::Polynomial(p.gcd(&q)))
+    }))
+}
+
+/// Fits a polynomial of the given degree to points by least squares.
+pub fn polyfit(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    collect_args("polyfit", 2, vec![xs], |args| {
+        let xs = expect_numbers("polyfit", &args[0])?;
+        let ys = expect_numbers("polyfit", &args[1])?;
+        let degree = expect_count("polyfit", &args[2])?;
+        Ok(RuntimeValue::Polynomial(Polynomial::fit(&xs, &ys, degree)?))
+    })
+}
+
+// Statistics
+
+fn expect_numbers(name: &str, value: &RuntimeValue) -> Result<Vec<f64>, String> {
+    match value {
+        RuntimeValue::List { elements } => elements.iter().map(|x| expect_real(name, x)).collect(),
+        _ => Err(format!("{name}() expects a list of numbers, found {value}")),
+    }
+}
+
+/// Reads a list of numbers, keeping its elements so that dual numbers among them can be carried.
+fn expect_values(
+    name: &str,
+    value: &RuntimeValue,
+) -> Result<(Vec<RuntimeValue>, Vec<f64>), String> {
+    match value {
+        RuntimeValue::List { elements } => {
+            let xs = elements
+                .iter()
+                .map(|x| expect_value(name, x))
+                .collect::<Result<_, _>>()?;
+            Ok((elements.clone(), xs))
+        }
+        _ => Err(format!("{name}() expects a list of numbers, found {value}")),
+    }
+}
+
+/// Applies a statistic of one list of numbers. `gradient` gives its derivatives
+/// in each number from the numbers and the result, to carry dual numbers through it.
+fn statistic(
+    name: &str,
+    value: &RuntimeValue,
+    f: impl Fn(&str, &[f64]) -> Result<f64, String>,
+    gradient: impl FnOnce(&[f64], f64) -> Vec<f64>,
+) -> Result<RuntimeValue, String> {
+    let (elements, xs) = expect_values(name, value)?;
+    let result = f(name, &xs)?;
+    Ok(chain_partials(&elements, result, || gradient(&xs, result)))
+}
+
+/// Defines a statistic of two lists of numbers, like `covariance(xs, ys)`.
+fn paired_statistic(
+    name: &'static str,
+    xs: RuntimeValue,
+    f: impl Fn(&str, &[f64], &[f64]) -> Result<RuntimeValue, String> + 'static,
+) -> Result<RuntimeValue, String> {
+    let xs = expect_numbers(name, &xs)?;
+    Ok(RuntimeValue::native_closure(name, move |ys| {
+        f(name, &xs, &expect_numbers(name, &ys)?)
+    }))
+}
+
+pub fn mean(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic("mean", &xs, stats::mean, |xs, _| {
+        vec![1.0 / xs.len() as f64; xs.len()]
+    })
+}
+
+pub fn median(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic("median", &xs, stats::median, |xs, _| {
+        stats::quantile_weights(xs, 0.5).0
+    })
+}
+
+/// The most common number, or the smallest of the most common numbers.
+pub fn mode(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    stats::mode("mode", &expect_numbers("mode", &xs)?).map(RuntimeValue::Number)
+}
+
+/// The variance of a sample, dividing by `n - 1`.
+pub fn variance(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic(
+        "variance",
+        &xs,
+        |name, xs| stats::variance(name, xs, true),
+        |xs, _| stats::variance_gradient(xs, true),
+    )
+}
+
+/// The variance of a whole population, dividing by `n`.
+pub fn pvariance(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic(
+        "pvariance",
+        &xs,
+        |name, xs| stats::variance(name, xs, false),
+        |xs, _| stats::variance_gradient(xs, false),
+    )
+}
+
+/// The derivatives of a standard deviation, from those of the variance.
+fn stddev_gradient(xs: &[f64], stddev: f64, sample: bool) -> Vec<f64> {
+    stats::variance_gradient(xs, sample)
+        .into_iter()
+        .map(|partial| partial / (2.0 * stddev))
+        .collect()
+}
+
+pub fn stddev(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic(
+        "stddev",
+        &xs,
+        |name, xs| stats::variance(name, xs, true).map(f64::sqrt),
+        |xs, stddev| stddev_gradient(xs, stddev, true),
+    )
+}
+
+pub fn pstddev(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    statistic(
+        "pstddev",
+        &xs,
+        |name, xs| stats::variance(name, xs, false).map(f64::sqrt),
+        |xs, stddev| stddev_gradient(xs, stddev, false),
+    )
+}
+
+/// The number below which a fraction `q` of the numbers lie, like
+/// `quantile(xs, 0.25)` for the first quartile.
+pub fn quantile(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let (elements, xs) = expect_values("quantile", &xs)?;
+    Ok(RuntimeValue::native_closure("quantile", move |q| {
+        let q_value = expect_value("quantile", &q)?;
+        let result = stats::quantile("quantile", &xs, q_value)?;
+        let mut args = elements.clone();
+        args.push(q);
+        Ok(chain_partials(&args, result, || {
+            let (mut partials, q_partial) = stats::quantile_weights(&xs, q_value);
+            partials.push(q_partial);
+            partials
+        }))
+    }))
+}
+
+/// The covariance of a sample of pairs.
+pub fn covariance(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    paired_statistic("covariance", xs, |name, xs, ys| {
+        stats::covariance(name, xs, ys, true).map(RuntimeValue::Number)
+    })
+}
+
+/// The covariance of a whole population of pairs.
+pub fn pcovariance(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    paired_statistic("pcovariance", xs, |name, xs, ys| {
+        stats::covariance(name, xs, ys, false).map(RuntimeValue::Number)
+    })
+}
+
+/// Pearson's correlation coefficient.
+pub fn correlation(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    paired_statistic("correlation", xs, |name, xs, ys| {
+        stats::correlation(name, xs, ys).map(RuntimeValue::Number)
+    })
+}
+
+/// Counts the numbers in each of `bins` equal bins from the smallest to the largest.
+pub fn histogram(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let xs = expect_numbers("histogram", &xs)?;
+    Ok(RuntimeValue::native_closure("histogram", move |bins| {
+        let bins = expect_count("histogram", &bins)?;
+        let counts = stats::histogram("histogram", &xs, bins)?;
+        Ok(RuntimeValue::List {
+            elements: counts
+                .into_iter()
+                .map(|count| RuntimeValue::Integer(BigInt::from(count as i64)))
+                .collect(),
+        })
+    }))
+}
+
+/// Fits a line to pairs by least squares, returning `[slope, intercept, r²]`.
+pub fn linear_regression(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    paired_statistic("linear_regression", xs, |name, xs, ys| {
+        let (slope, intercept, r_squared) = stats::linear_regression(name, xs, ys)?;
+        Ok(RuntimeValue::List {
+            elements: vec![
+                RuntimeValue::Number(slope),
+                RuntimeValue::Number(intercept),
+                RuntimeValue::Number(r_squared),
+            ],
+        })
+    })
+}
+
+// Random numbers
+
+fn expect_non_empty_list(name: &str, value: &RuntimeValue) -> Result<Vec<RuntimeValue>, String> {
+    match value {
+        RuntimeValue::List { elements } if !elements.is_empty() => Ok(elements.clone()),
+        _ => Err(format!("{name}() expects a non-empty list, found {value}")),
+    }
+}
+
+fn integer(n: u64) -> RuntimeValue {
+    RuntimeValue::Integer(BigInt::from(n as i64))
+}
+
+/// Defines a distribution's function of real parameters, like `normal_pdf(mu, sigma, x)`.
+/// `partials` gives its derivatives in each of them, to carry dual numbers through it.
+fn distribution(
+    name: &'static str,
+    arity: usize,
+    first: RuntimeValue,
+    f: impl Fn(&str, &[f64]) -> Result<f64, String> + Clone + 'static,
+    partials: fn(&[f64]) -> Vec<f64>,
+) -> Result<RuntimeValue, String> {
+    collect_args(name, arity - 1, vec![first], move |args| {
+        let xs = args
+            .iter()
+            .map(|arg| expect_value(name, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let result = f(name, &xs)?;
+        Ok(chain_partials(&args, result, || partials(&xs)))
+    })
+}
+
+fn expect_bounds(name: &str, a: f64, b: f64) -> Result<(), String> {
+    if a < b {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name}() expects a lower bound below the upper one, found {a} and {b}"
+        ))
+    }
+}
+
+fn expect_positive(name: &str, what: &str, x: f64) -> Result<(), String> {
+    if x > 0.0 && x.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{name}() expects a positive {what}, found {x}"))
+    }
+}
+
+fn expect_mean(name: &str, mean: f64) -> Result<(), String> {
+    if mean >= 0.0 && mean.is_finite() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name}() expects a non-negative mean, found {mean}"
+        ))
+    }
+}
+
+/// Checks the parameters of a binomial distribution, returning the number of trials.
+fn expect_trials(name: &str, n: f64, p: f64) -> Result<f64, String> {
+    if !(n >= 0.0 && n.fract() == 0.0) {
+        return Err(format!(
+            "{name}() expects a non-negative integer number of trials, found {n}"
+        ));
+    }
+    if !(0.0..=1.0).contains(&p) {
+        return Err(format!(
+            "{name}() expects a probability between 0 and 1, found {p}"
+        ));
+    }
+    Ok(n)
+}
+
+/// Names of the builtins that `random_builtins` makes, for uses without a generator.
+pub const RANDOM_BUILTINS: [&str; 10] = [
+    "seed",
+    "random",
+    "randint",
+    "shuffle",
+    "choice",
+    "random_uniform",
+    "random_normal",
+    "random_exponential",
+    "random_poisson",
+    "random_binomial",
+];
+
+/// Builds the builtins that draw from `rng`. The interpreter owns it,
+/// so seeding it makes a whole program repeatable.
+pub fn random_builtins(rng: &Rc<RefCell<Rng>>) -> Vec<(&'static str, RuntimeValue)> {
+    let rng = Rc::clone(rng);
+    let seed = {
+        let rng = Rc::clone(&rng);
+        RuntimeValue::native_closure("seed", move |seed| {
+            rng.borrow_mut().seed(expect_i64("seed", &seed)? as u64);
+            Ok(RuntimeValue::Nil)
+        })
+    };
+    let random = {
+        let rng = Rc::clone(&rng);
+        RuntimeValue::native_closure("random", move |value| match value {
+            RuntimeValue::Nil => Ok(RuntimeValue::Number(rng.borrow_mut().next_f64())),
+            _ => Err("random() takes no argument".into()),
+        })
+    };
+    let randint = {
+        let rng = Rc::clone(&rng);
+        RuntimeValue::native_closure("randint", move |low| {
+            let low = expect_i64("randint", &low)?;
+            let rng = Rc::clone(&rng);
+            Ok(RuntimeValue::native_closure("randint", move |high| {
+                let high = expect_i64("randint", &high)?;
+                if low > high {
+                    return Err(format!(
+                        "randint() expects a lower bound no greater than the upper one, found {low} and {high}"
+                    ));
+                }
+                let n = rng.borrow_mut().between(low, high);
+                Ok(RuntimeValue::Integer(BigInt::from(n)))
+            }))
+        })
+    };
+    let shuffle = {
+        let rng = Rc::clone(&rng);
+        RuntimeValue::native_closure("shuffle", move |list| match list {
+            RuntimeValue::List { mut elements } => {
+                rng.borrow_mut().shuffle(&mut elements);
+                Ok(RuntimeValue::List { elements })
+            }
+            _ => Err(format!("shuffle() expects a list, found {list}")),
+        })
+    };
+    let choice = {
+        let rng = Rc::clone(&rng);
+        RuntimeValue::native_closure("choice", move |list| {
+            let elements = expect_non_empty_list("choice", &list)?;
+            let i = rng.borrow_mut().below(elements.len() as u64) as usize;
+            Ok(elements[i].clone())
+        })
+    };
+    // Samplers take their parameters curried, then return the sample.
+    let sampler = |name: &'static str,
+                   arity: usize,
+                   sample: fn(&mut Rng, &[f64]) -> Result<RuntimeValue, String>| {
+        let rng = Rc::clone(&rng);
+        collect_args(name, arity, Vec::new(), move |args| {
+            let params = args
+                .iter()
+                .map(|arg| expect_real(name, arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            sample(&mut rng.borrow_mut(), &params)
+        })
+        .expect("a sampler takes parameters")
+    };
+    vec![
+        ("seed", seed),
+        ("random", random),
+        ("randint", randint),
+        ("shuffle", shuffle),
+        ("choice", choice),
+        (
+            "random_uniform",
+            sampler("random_uniform", 2, |rng, params| {
+                let (a, b) = (params[0], params[1]);
+                expect_bounds("random_uniform", a, b)?;
+                Ok(RuntimeValue::Number(a + (b - a) * rng.next_f64()))
+            }),
+        ),
+        (
+            "random_normal",
+            sampler("random_normal", 2, |rng, params| {
+                let (mu, sigma) = (params[0], params[1]);
+                expect_positive("random_normal", "standard deviation", sigma)?;
+                Ok(RuntimeValue::Number(mu + sigma * rng.normal()))
+            }),
+        ),
+        (
+            "random_exponential",
+            sampler("random_exponential", 1, |rng, params| {
+                expect_positive("random_exponential", "rate", params[0])?;
+                Ok(RuntimeValue::Number(rng.exponential() / params[0]))
+            }),
+        ),
+        (
+            "random_poisson",
+            sampler("random_poisson", 1, |rng, params| {
+                expect_mean("random_poisson", params[0])?;
+                Ok(integer(rng.poisson(params[0])))
+            }),
+        ),
+        (
+            "random_binomial",
+            sampler("random_binomial", 2, |rng, params| {
+                let n = expect_trials("random_binomial", params[0], params[1])?;
+                Ok(integer(rng.binomial(n as u64, params[1])))
+            }),
+        ),
+    ]
+}
+
+// Probability distributions.
+// Each takes the distribution's parameters first, like `normal_cdf(mu, sigma, x)`,
+// and is differentiable in the real ones but not in counts like `k`.
+
+pub fn uniform_pdf(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "uniform_pdf",
+        3,
+        a,
+        |name, args| {
+            let (a, b, x) = (args[0], args[1], args[2]);
+            expect_bounds(name, a, b)?;
+            Ok(if (a..=b).contains(&x) {
+                1.0 / (b - a)
+            } else {
+                0.0
+            })
+        },
+        |args| {
+            let (a, b, x) = (args[0], args[1], args[2]);
+            if (a..=b).contains(&x) {
+                let density = 1.0 / (b - a);
+                vec![density * density, -density * density, 0.0]
+            } else {
+                vec![0.0; 3]
+            }
+        },
+    )
+}
+
+pub fn uniform_cdf(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "uniform_cdf",
+        3,
+        a,
+        |name, args| {
+            let (a, b, x) = (args[0], args[1], args[2]);
+            expect_bounds(name, a, b)?;
+            Ok(((x - a) / (b - a)).clamp(0.0, 1.0))
+        },
+        |args| {
+            let (a, b, x) = (args[0], args[1], args[2]);
+            if a < x && x < b {
+                let width = b - a;
+                vec![
+                    (x - b) / (width * width),
+                    (a - x) / (width * width),
+                    1.0 / width,
+                ]
+            } else {
+                vec![0.0; 3]
+            }
+        },
+    )
+}
+
+fn normal_density(mu: f64, sigma: f64, x: f64) -> f64 {
+    let z = (x - mu) / sigma;
+    (-z * z / 2.0).exp() / (sigma * (2.0 * PI).sqrt())
+}
+
+pub fn normal_pdf(mu: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "normal_pdf",
+        3,
+        mu,
+        |name, args| {
+            let (mu, sigma, x) = (args[0], args[1], args[2]);
+            expect_positive(name, "standard deviation", sigma)?;
+            Ok(normal_density(mu, sigma, x))
+        },
+        |args| {
+            let (mu, sigma, x) = (args[0], args[1], args[2]);
+            let (density, z) = (normal_density(mu, sigma, x), (x - mu) / sigma);
+            vec![
+                density * z / sigma,
+                density * (z * z - 1.0) / sigma,
+                -density * z / sigma,
+            ]
+        },
+    )
+}
+
+pub fn normal_cdf(mu: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "normal_cdf",
+        3,
+        mu,
+        |name, args| {
+            let (mu, sigma, x) = (args[0], args[1], args[2]);
+            expect_positive(name, "standard deviation", sigma)?;
+            // `erfc` keeps its precision far into the lower tail.
+            Ok(0.5 * special::erfc((mu - x) / (sigma * SQRT_2)))
+        },
+        |args| {
+            let (mu, sigma, x) = (args[0], args[1], args[2]);
+            let (density, z) = (normal_density(mu, sigma, x), (x - mu) / sigma);
+            vec![-density, -density * z, density]
+        },
+    )
+}
+
+pub fn exponential_pdf(rate: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "exponential_pdf",
+        2,
+        rate,
+        |name, args| {
+            let (rate, x) = (args[0], args[1]);
+            expect_positive(name, "rate", rate)?;
+            Ok(if x < 0.0 {
+                0.0
+            } else {
+                rate * (-rate * x).exp()
+            })
+        },
+        |args| {
+            let (rate, x) = (args[0], args[1]);
+            if x < 0.0 {
+                return vec![0.0; 2];
+            }
+            let decay = (-rate * x).exp();
+            vec![decay * (1.0 - rate * x), -rate * rate * decay]
+        },
+    )
+}
+
+pub fn exponential_cdf(rate: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "exponential_cdf",
+        2,
+        rate,
+        |name, args| {
+            let (rate, x) = (args[0], args[1]);
+            expect_positive(name, "rate", rate)?;
+            Ok(if x < 0.0 { 0.0 } else { -(-rate * x).exp_m1() })
+        },
+        |args| {
+            let (rate, x) = (args[0], args[1]);
+            if x < 0.0 {
+                return vec![0.0; 2];
+            }
+            let decay = (-rate * x).exp();
+            vec![x * decay, rate * decay]
+        },
+    )
+}
+
+/// The Poisson probability of `k` events when `mean` are expected.
+fn poisson_probability(mean: f64, k: f64) -> f64 {
+    if k < 0.0 || k.fract() != 0.0 {
+        0.0
+    } else if mean == 0.0 {
+        if k == 0.0 { 1.0 } else { 0.0 }
+    } else {
+        (k * mean.ln() - mean - special::ln_gamma(k + 1.0)).exp()
+    }
+}
+
+/// The probability of exactly `k` events, which is 0 unless `k` is a natural number.
+pub fn poisson_pmf(mean: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "poisson_pmf",
+        2,
+        mean,
+        |name, args| {
+            let (mean, k) = (args[0], args[1]);
+            expect_mean(name, mean)?;
+            Ok(poisson_probability(mean, k))
+        },
+        |args| {
+            let (mean, k) = (args[0], args[1]);
+            vec![
+                poisson_probability(mean, k - 1.0) - poisson_probability(mean, k),
+                0.0,
+            ]
+        },
+    )
+}
+
+pub fn poisson_cdf(mean: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "poisson_cdf",
+        2,
+        mean,
+        |name, args| {
+            let (mean, k) = (args[0], args[1].floor());
+            expect_mean(name, mean)?;
+            Ok(if k < 0.0 {
+                0.0
+            } else {
+                special::gamma_q(k + 1.0, mean)
+            })
+        },
+        |args| vec![-poisson_probability(args[0], args[1].floor()), 0.0],
+    )
+}
+
+/// The probability of exactly `k` successes in `n` trials, which is 0 unless
+/// `k` is a natural number.
+fn binomial_probability(n: f64, p: f64, k: f64) -> f64 {
+    if k < 0.0 || k > n || k.fract() != 0.0 {
+        return 0.0;
+    }
+    // `0 * ln 0` counts as 0, so that certain outcomes have probability 1.
+    let log_power = |count: f64, log: f64| if count == 0.0 { 0.0 } else { count * log };
+    let log_choose =
+        special::ln_gamma(n + 1.0) - special::ln_gamma(k + 1.0) - special::ln_gamma(n - k + 1.0);
+    (log_choose + log_power(k, p.ln()) + log_power(n - k, (-p).ln_1p())).exp()
+}
+
+/// The probability of exactly `k` successes, which is 0 unless `k` is a natural number.
+pub fn binomial_pmf(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "binomial_pmf",
+        3,
+        n,
+        |name, args| {
+            let (p, k) = (args[1], args[2]);
+            let n = expect_trials(name, args[0], p)?;
+            Ok(binomial_probability(n, p, k))
+        },
+        |args| {
+            let (n, p, k) = (args[0], args[1], args[2]);
+            let fewer = |k: f64| binomial_probability(n - 1.0, p, k);
+            let slope = if n == 0.0 {
+                0.0
+            } else {
+                n * (fewer(k - 1.0) - fewer(k))
+            };
+            vec![0.0, slope, 0.0]
+        },
+    )
+}
+
+pub fn binomial_cdf(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    distribution(
+        "binomial_cdf",
+        3,
+        n,
+        |name, args| {
+            let (p, k) = (args[1], args[2].floor());
+            let n = expect_trials(name, args[0], p)?;
+            Ok(if k < 0.0 {
+                0.0
+            } else if k >= n {
+                1.0
+            } else {
+                special::beta_i(n - k, k + 1.0, 1.0 - p)
+            })
+        },
+        |args| {
+            let (n, p, k) = (args[0], args[1], args[2].floor());
+            let slope = if k < 0.0 || k >= n {
+                0.0
+            } else {
+                -n * binomial_probability(n - 1.0, p, k)
+            };
+            vec![0.0, slope, 0.0]
+        },
+    )
+}
+
+// Number theory and combinatorics
+
+/// The largest limit `primes` sieves up to, which takes about 100 MB.
+const MAX_SIEVE: usize = 100_000_000;
+
+/// The most lists `permutations` and `combinations` build.
+const MAX_ARRANGEMENTS: u64 = 1_000_000;
+
+/// The largest `n` that `factorial` accepts, as multiplying and printing
+/// the result both grow quadratically with its 77,000 digits.
+const MAX_FACTORIAL: usize = 20_000;
+
+fn integer_result(n: u64) -> RuntimeValue {
+    RuntimeValue::Integer(BigInt::from(n as i64))
+}
+
+fn expect_list(name: &str, value: &RuntimeValue) -> Result<Vec<RuntimeValue>, String> {
+    match value {
+        RuntimeValue::List { elements } => Ok(elements.clone()),
+        _ => Err(format!("{name}() expects a list, found {value}")),
+    }
+}
+
+/// Converts an integral number to a positive modulus.
+fn expect_modulus(name: &str, value: &RuntimeValue) -> Result<BigInt, String> {
+    let modulus = expect_integer(name, value)?;
+    if modulus.is_negative() || modulus.is_zero() {
+        return Err(format!(
+            "{name}() expects a positive modulus, found {value}"
+        ));
+    }
+    Ok(modulus)
+}
+
+/// Defines a function of two integers, like `gcd(a, b)`.
+fn integer_pair(
+    name: &'static str,
+    a: RuntimeValue,
+    f: fn(&BigInt, &BigInt) -> BigInt,
+) -> Result<RuntimeValue, String> {
+    let a = expect_integer(name, &a)?;
+    Ok(RuntimeValue::native_closure(name, move |b| {
+        Ok(RuntimeValue::Integer(f(&a, &expect_integer(name, &b)?)))
+    }))
+}
+
+/// The greatest common divisor, which is never negative.
+pub fn gcd(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    integer_pair("gcd", a, BigInt::gcd)
+}
+
+/// The least common multiple, which is never negative.
+pub fn lcm(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    integer_pair("lcm", a, number_theory::lcm)
+}
+
+pub fn factorial(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    let count = expect_count("factorial", &n)?;
+    if count > MAX_FACTORIAL {
+        return Err(format!(
+            "factorial() computes up to at most {MAX_FACTORIAL}, found {n}"
+        ));
+    }
+    Ok(RuntimeValue::Integer(number_theory::factorial(
+        count as u64,
+    )))
+}
+
+/// The number of ways to choose `k` of `n` things, which is 0 when `k > n`.
+pub fn binomial(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    let n = expect_count("binomial", &n)?;
+    Ok(RuntimeValue::native_closure("binomial", move |k| {
+        let k = expect_count("binomial", &k)?;
+        Ok(RuntimeValue::Integer(number_theory::binomial(
+            n as u64, k as u64,
+        )))
+    }))
+}
+
+/// Whether an integer is prime, exactly for every 64-bit integer.
+pub fn is_prime(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    let n = expect_i64("is_prime", &n)?;
+    Ok(RuntimeValue::Boolean(
+        n > 0 && number_theory::is_prime(n as u64),
+    ))
+}
+
+/// The primes up to and including `n`.
+pub fn primes(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    let limit = expect_count("primes", &n)?;
+    if limit > MAX_SIEVE {
+        return Err(format!(
+            "primes() sieves up to at most {MAX_SIEVE}, found {n}"
+        ));
+    }
+    Ok(RuntimeValue::List {
+        elements: number_theory::primes(limit)
+            .into_iter()
+            .map(integer_result)
+            .collect(),
+    })
+}
+
+/// The prime factors of a positive integer in increasing order, with repeats.
+pub fn factorize(n: RuntimeValue) -> Result<RuntimeValue, String> {
+    let value = expect_i64("factorize", &n)?;
+    if value < 1 {
+        return Err(format!("factorize() expects a positive integer, found {n}"));
+    }
+    Ok(RuntimeValue::List {
+        elements: number_theory::factorize(value as u64)
+            .into_iter()
+            .map(integer_result)
+            .collect(),
+    })
+}
+
+/// `base ^ exponent % modulus`, without computing the whole power.
+pub fn mod_pow(base: RuntimeValue) -> Result<RuntimeValue, String> {
+    collect_args("mod_pow", 2, vec![base], |args| {
+        let base = expect_integer("mod_pow", &args[0])?;
+        let exponent = expect_integer("mod_pow", &args[1])?;
+        if exponent.is_negative() {
+            return Err(format!(
+                "mod_pow() expects a non-negative exponent, found {}",
+                args[1]
+            ));
+        }
+        let modulus = expect_modulus("mod_pow", &args[2])?;
+        Ok(RuntimeValue::Integer(number_theory::mod_pow(
+            &base, &exponent, &modulus,
+        )))
+    })
+}
+
+/// The `x` with `a * x % m == 1`, which exists when `a` and `m` share no factor.
+pub fn mod_inverse(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    let a = expect_integer("mod_inverse", &a)?;
+    Ok(RuntimeValue::native_closure("mod_inverse", move |m| {
+        let modulus = expect_modulus("mod_inverse", &m)?;
+        number_theory::mod_inverse(&a, &modulus)
+            .map(RuntimeValue::Integer)
+            .ok_or_else(|| {
+                format!(
+                    "mod_inverse() found no inverse of {a} modulo {modulus}, since they share the factor {}",
+                    a.gcd(&modulus)
+                )
+            })
+    }))
+}
+
+/// Checks that building `count` lists is within `MAX_ARRANGEMENTS`.
+fn expect_arrangements(name: &str, count: BigInt) -> Result<(), String> {
+    match count.to_i64() {
+        Some(count) if count as u64 <= MAX_ARRANGEMENTS => Ok(()),
+        _ => Err(format!(
+            "{name}() would build {count} lists, more than the limit of {MAX_ARRANGEMENTS}"
+        )),
+    }
+}
+
+fn lists(arrangements: Vec<Vec<RuntimeValue>>) -> RuntimeValue {
+    RuntimeValue::List {
+        elements: arrangements
+            .into_iter()
+            .map(|elements| RuntimeValue::List { elements })
+            .collect(),
+    }
+}
+
+/// Every ordering of a list's elements.
+pub fn permutations(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let xs = expect_list("permutations", &xs)?;
+    expect_arrangements("permutations", number_theory::factorial(xs.len() as u64))?;
+    Ok(lists(number_theory::permutations(&xs)))
+}
+
+/// Every choice of `k` of a list's elements, keeping their order.
+pub fn combinations(xs: RuntimeValue) -> Result<RuntimeValue, String> {
+    let xs = expect_list("combinations", &xs)?;
+    Ok(RuntimeValue::native_closure("combinations", move |k| {
+        let k = expect_count("combinations", &k)?;
+        expect_arrangements(
+            "combinations",
+            number_theory::binomial(xs.len() as u64, k as u64),
+        )?;
+        Ok(lists(number_theory::combinations(&xs, k)))
+    }))
+}
+
+/// The gamma function, which extends the factorial: `gamma(n) == factorial(n - 1)`.
+pub fn gamma(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    match value {
+        RuntimeValue::List { .. } => map_elements(value, gamma),
+        RuntimeValue::Dual(d) => {
+            let result = special::gamma(d.value);
+            Ok(RuntimeValue::Dual(
+                d.chain(result, result * special::digamma(d.value)),
+            ))
+        }
+        _ => match value.as_f64() {
+            Some(x) => Ok(RuntimeValue::Number(special::gamma(x))),
+            None => Err(format!("gamma() expects a real number, found {value}")),
+        },
+    }
+}
+
+/// The beta function `gamma(a) * gamma(b) / gamma(a + b)`.
+pub fn beta(a: RuntimeValue) -> Result<RuntimeValue, String> {
+    collect_args("beta", 1, vec![a], |args| {
+        let (a, b) = (
+            expect_value("beta", &args[0])?,
+            expect_value("beta", &args[1])?,
+        );
+        let result = if a > 0.0 && b > 0.0 {
+            // Logarithms keep the gammas of large numbers from overflowing.
+            (special::ln_gamma(a) + special::ln_gamma(b) - special::ln_gamma(a + b)).exp()
+        } else {
+            special::gamma(a) * special::gamma(b) / special::gamma(a + b)
+        };
+        Ok(chain_partials(&args, result, || {
+            let both = special::digamma(a + b);
+            vec![
+                result * (special::digamma(a) - both),
+                result * (special::digamma(b) - both),
+            ]
+        }))
+    })
+}
+
+// Symbolic expressions
+
+fn expect_symbolic(name: &str, value: &RuntimeValue) -> Result<Symbolic, String> {
+    match value {
+        RuntimeValue::Symbolic(symbolic) => Ok(symbolic.clone()),
+        other => Err(format!(
+            "{name}() expects a formula like sym(x + 1), found {other}"
+        )),
+    }
+}
+
+pub fn simplify(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    // Formulas are always kept simplified.
+    Ok(RuntimeValue::Symbolic(expect_symbolic("simplify", &value)?))
+}
+
+/// Multiplies out products and integer powers of sums.
+pub fn expand(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let formula = expect_symbolic("expand", &value)?;
+    let expanded = symbolic::expand(formula.expr())?;
+    Ok(RuntimeValue::Symbolic(formula.with_expr(&expanded)))
+}
+
+/// Factors a polynomial in one variable over the rationals.
+pub fn factor(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let formula = expect_symbolic("factor", &value)?;
+    let factored = symbolic::factor(formula.expr())?;
+    // Simplifying would multiply the factors out again.
+    Ok(RuntimeValue::Symbolic(Symbolic::unsimplified(
+        factored,
+        formula.scope(),
+    )))
+}
+
+/// Replaces a variable, given as a string or as `sym(x)`, with a number or a formula.
+pub fn substitute(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    let formula = expect_symbolic("substitute", &value)?;
+    Ok(RuntimeValue::native_closure(
+        "substitute",
+        move |variable| {
+            let name = match &variable {
+                RuntimeValue::String(name) => name.clone(),
+                RuntimeValue::Symbolic(symbolic) => match symbolic.expr() {
+                    Expr::Variable(name) => name.clone(),
+                    _ => {
+                        return Err(format!(
+                            "substitute() expects a variable name, found {variable}"
+                        ));
+                    }
+                },
+                other => {
+                    return Err(format!(
+                        "substitute() expects a variable name, found {other}"
+                    ));
+                }
+            };
+            let formula = formula.clone();
+            Ok(RuntimeValue::native_closure("substitute", move |value| {
+                let Some(replacement) = symbolic::to_expr(&value) else {
+                    return Err(format!(
+                        "substitute() expects a number or a formula, found {value}"
+                    ));
+                };
+                let substituted = symbolic::substitute(formula.expr(), &name, &replacement);
+                Ok(RuntimeValue::Symbolic(formula.with_expr(&substituted)))
+            }))
+        },
+    ))
+}
+
+/// Evaluates a formula with the values its names have where it was written.
+pub fn eval(value: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_symbolic("eval", &value)?.eval()
+}
+
+// Numerical analysis.
+// Each method has a `_tol` variant that takes the tolerance first,
+// like `integrate_tol(0.000001, f, a, b)`.
+
+fn expect_real(name: &str, value: &RuntimeValue) -> Result<f64, String> {
+    if let RuntimeValue::Dual(_) = value {
+        return Err(not_differentiable(name, value));
+    }
+    value
+        .as_f64()
+        .ok_or_else(|| format!("{name}() expects a real number, found {value}"))
+}
+
+/// Reads a real number, or the value of a dual number whose derivative the caller carries.
+fn expect_value(name: &str, value: &RuntimeValue) -> Result<f64, String> {
+    match value {
+        RuntimeValue::Dual(d) => Ok(d.value),
+        _ => expect_real(name, value),
+    }
+}
+
+fn expect_tolerance(name: &str, value: &RuntimeValue) -> Result<f64, String> {
+    match value.as_f64() {
+        Some(tolerance) if tolerance > 0.0 => Ok(tolerance),
+        _ => Err(format!(
+            "{name}() expects a positive tolerance, found {value}"
+        )),
+    }
+}
+
+/// Wraps a MathFP function of a number, which must return a real number.
+fn real_function(
+    name: &'static str,
+    function: &RuntimeValue,
+) -> impl FnMut(f64) -> Result<f64, String> {
+    move |x| {
+        let value = Interpreter::call(function, RuntimeValue::Number(x))?;
+        value.as_f64().ok_or_else(|| {
+            format!("{name}() expects the function to return a real number, found {value} at {x}")
+        })
+    }
+}
+
+/// Defines a builtin and its `_tol` variant, which take the function
+/// followed by `arity` numbers and apply a numerical method to them.
+fn numerical_method(
+    name: &'static str,
+    tolerance: f64,
+    function: RuntimeValue,
+    arity: usize,
+    method: fn(&'static str, &RuntimeValue, &[RuntimeValue], f64) -> Result<RuntimeValue, String>,
+) -> Result<RuntimeValue, String> {
+    expect_function(name, &function)?;
+    collect_args(name, arity, vec![], move |args| {
+        method(name, &function, &args, tolerance)
+    })
+}
+
+/// Curries a native function over `remaining` more arguments.
+fn collect_args(
+    name: &'static str,
+    remaining: usize,
+    args: Vec<RuntimeValue>,
+    body: impl Fn(Vec<RuntimeValue>) -> Result<RuntimeValue, String> + Clone + 'static,
+) -> Result<RuntimeValue, String> {
+    if remaining == 0 {
+        return body(args);
+    }
+    Ok(RuntimeValue::native_closure(name, move |arg| {
+        let mut args = args.clone();
+        args.push(arg);
+        collect_args(name, remaining - 1, args, body.clone())
+    }))
+}
+
+fn integrate_method(
+    name: &'static str,
+    function: &RuntimeValue,
+    args: &[RuntimeValue],
+    tolerance: f64,
+) -> Result<RuntimeValue, String> {
+    let (a, b) = (expect_real(name, &args[0])?, expect_real(name, &args[1])?);
+    let mut f = real_function(name, function);
+    numeric::integrate(name, &mut f, a, b, tolerance).map(RuntimeValue::Number)
+}
+
+fn root_method(
+    name: &'static str,
+    function: &RuntimeValue,
+    args: &[RuntimeValue],
+    tolerance: f64,
+) -> Result<RuntimeValue, String> {
+    let (a, b) = (expect_real(name, &args[0])?, expect_real(name, &args[1])?);
+    let mut f = real_function(name, function);
+    numeric::root(name, &mut f, a, b, tolerance).map(RuntimeValue::Number)
+}
+
+fn newton_method(
+    name: &'static str,
+    function: &RuntimeValue,
+    args: &[RuntimeValue],
+    tolerance: f64,
+) -> Result<RuntimeValue, String> {
+    let x0 = expect_real(name, &args[0])?;
+    let mut f = real_function(name, function);
+    numeric::newton(name, &mut f, x0, tolerance).map(RuntimeValue::Number)
+}
+
+/// Minimizes over a number, or over a list of numbers with the simplex method.
+fn minimize_method(
+    name: &'static str,
+    function: &RuntimeValue,
+    args: &[RuntimeValue],
+    tolerance: f64,
+) -> Result<RuntimeValue, String> {
+    let RuntimeValue::List { elements } = &args[0] else {
+        let x0 = expect_real(name, &args[0])?;
+        let mut f = real_function(name, function);
+        return numeric::minimize(name, &mut f, x0, tolerance).map(RuntimeValue::Number);
+    };
+    let x0 = elements
+        .iter()
+        .map(|x| expect_real(name, x))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut f = |xs: &[f64]| {
+        let point = RuntimeValue::List {
+            elements: xs.iter().map(|x| RuntimeValue::Number(*x)).collect(),
+        };
+        let value = Interpreter::call(function, point.clone())?;
+        value.as_f64().ok_or_else(|| {
+            format!(
+                "{name}() expects the function to return a real number, found {value} at {point}"
+            )
+        })
+    };
+    let minimum = numeric::minimize_simplex(name, &mut f, &x0, tolerance)?;
+    Ok(RuntimeValue::List {
+        elements: minimum.into_iter().map(RuntimeValue::Number).collect(),
+    })
+}
+
+/// Integrates a function from `a` to `b`.
+pub fn integrate(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    numerical_method(
+        "integrate",
+        DEFAULT_TOLERANCE,
+        function,
+        2,
+        integrate_method,
+    )
+}
+
+pub fn integrate_tol(tolerance: RuntimeValue) -> Result<RuntimeValue, String> {
+    let tolerance = expect_tolerance("integrate_tol", &tolerance)?;
+    Ok(RuntimeValue::native_closure(
+        "integrate_tol",
+        move |function| numerical_method("integrate_tol", tolerance, function, 2, integrate_method),
+    ))
+}
+
+/// Finds a root of a function between `a` and `b`, where it changes sign.
+pub fn root(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    numerical_method("root", DEFAULT_TOLERANCE, function, 2, root_method)
+}
+
+pub fn root_tol(tolerance: RuntimeValue) -> Result<RuntimeValue, String> {
+    let tolerance = expect_tolerance("root_tol", &tolerance)?;
+    Ok(RuntimeValue::native_closure("root_tol", move |function| {
+        numerical_method("root_tol", tolerance, function, 2, root_method)
+    }))
+}
+
+/// Finds a root of a function near `x0`.
+pub fn newton(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    numerical_method("newton", DEFAULT_TOLERANCE, function, 1, newton_method)
+}
+
+pub fn newton_tol(tolerance: RuntimeValue) -> Result<RuntimeValue, String> {
+    let tolerance = expect_tolerance("newton_tol", &tolerance)?;
+    Ok(RuntimeValue::native_closure(
+        "newton_tol",
+        move |function| numerical_method("newton_tol", tolerance, function, 1, newton_method),
+    ))
+}
+
+/// Finds a local minimum of a function near `x0`, which is a number or a list of numbers.
+pub fn minimize(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    numerical_method("minimize", DEFAULT_TOLERANCE, function, 1, minimize_method)
+}
+
+pub fn minimize_tol(tolerance: RuntimeValue) -> Result<RuntimeValue, String> {
+    let tolerance = expect_tolerance("minimize_tol", &tolerance)?;
+    Ok(RuntimeValue::native_closure(
+        "minimize_tol",
+        move |function| numerical_method("minimize_tol", tolerance, function, 1, minimize_method),
+    ))
+}
+
+// Differential equations.
+// `f(t, y)` gives the derivative of the state `y`, which is a number,
+// a list of numbers or a vector, and the solution is a list of `[t, y]` points.
+
+/// The kind of value a state was given as, to return states of the same kind.
+#[derive(Clone, Copy, PartialEq)]
+enum StateKind {
+    Number,
+    List,
+    Vector,
+}
+
+fn expect_state(name: &str, value: &RuntimeValue) -> Result<(State, StateKind), String> {
+    match value {
+        RuntimeValue::List { elements } => Ok((
+            elements
+                .iter()
+                .map(|x| expect_real(name, x))
+                .collect::<Result<_, _>>()?,
+            StateKind::List,
+        )),
+        RuntimeValue::Matrix(matrix) if matrix.is_vector() => {
+            Ok((matrix.elements().to_vec(), StateKind::Vector))
+        }
+        _ => Ok((vec![expect_real(name, value)?], StateKind::Number)),
+    }
+}
+
+fn state_value(state: &[f64], kind: StateKind) -> RuntimeValue {
+    match kind {
+        StateKind::Number => RuntimeValue::Number(state[0]),
+        StateKind::List => RuntimeValue::List {
+            elements: state.iter().map(|x| RuntimeValue::Number(*x)).collect(),
+        },
+        StateKind::Vector => RuntimeValue::Matrix(Matrix::vector(state.to_vec())),
+    }
+}
+
+/// Calls a MathFP function of `t` and `y`.
+fn call_with_state(
+    function: &RuntimeValue,
+    t: f64,
+    y: &[f64],
+    kind: StateKind,
+) -> Result<RuntimeValue, String> {
+    let partial = Interpreter::call(function, RuntimeValue::Number(t))?;
+    Interpreter::call(&partial, state_value(y, kind))
+}
+
+fn solve_ode_with(
+    name: &'static str,
+    method: Method,
+    event: Option<RuntimeValue>,
+    function: RuntimeValue,
+) -> Result<RuntimeValue, String> {
+    expect_function(name, &function)?;
+    collect_args(name, 3, vec![], move |args| {
+        let t0 = expect_real(name, &args[0])?;
+        let (y0, kind) = expect_state(name, &args[1])?;
+        let t1 = expect_real(name, &args[2])?;
+        let mut f = |t: f64, y: &[f64]| {
+            let derivative = call_with_state(&function, t, y, kind)?;
+            match expect_state(name, &derivative) {
+                Ok((state, found)) if found == kind && state.len() == y.len() => Ok(state),
+                _ => Err(format!(
+                    "{name}() expects the derivative to have the shape of the state {}, found {derivative}",
+                    state_value(y, kind)
+                )),
+            }
+        };
+        let mut stop = |t: f64, y: &[f64]| match &event {
+            Some(event) => Ok(call_with_state(event, t, y, kind)?.is_truthy()),
+            None => Ok(false),
+        };
+        let stop: Option<Event> = event.is_some().then_some(&mut stop);
+        let trajectory = ode::solve(name, &mut f, t0, y0, t1, method, stop)?;
+        Ok(RuntimeValue::List {
+            elements: trajectory
+                .iter()
+                .map(|(t, y)| RuntimeValue::List {
+                    elements: vec![RuntimeValue::Number(*t), state_value(y, kind)],
+                })
+                .collect(),
+        })
+    })
+}
+
+/// Solves `y' = f(t, y)` from `y(t0) = y0` to `t1` with adaptive steps.
+pub fn solve_ode(function: RuntimeValue) -> Result<RuntimeValue, String> {
+    let method = Method::Rk45 {
+        tolerance: DEFAULT_TOLERANCE,
+    };
+    solve_ode_with("solve_ode", method, None, function)
+}
+
+pub fn solve_ode_tol(tolerance: RuntimeValue) -> Result<RuntimeValue, String> {
+    let tolerance = expect_tolerance("solve_ode_tol", &tolerance)?;
+    Ok(RuntimeValue::native_closure(
+        "solve_ode_tol",
+        move |function| solve_ode_with("solve_ode_tol", Method::Rk45 { tolerance }, None, function),
+    ))
+}
+
+/// Solves with the classic Runge–Kutta method and a fixed step size.
+pub fn solve_ode_rk4(step: RuntimeValue) -> Result<RuntimeValue, String> {
+    let step = match step.as_f64() {
+        Some(step) if step > 0.0 => step,
+        _ => {
+            return Err(format!(
+                "solve_ode_rk4() expects a positive step size, found {step}"
+            ));
+        }
+    };
+    Ok(RuntimeValue::native_closure(
+        "solve_ode_rk4",
+        move |function| solve_ode_with("solve_ode_rk4", Method::Rk4 { step }, None, function),
+    ))
+}
+
+/// Solves until `event(t, y)` becomes true, or until `t1`.
+pub fn solve_ode_until(event: RuntimeValue) -> Result<RuntimeValue, String> {
+    expect_function("solve_ode_until", &event)?;
+    Ok(RuntimeValue::native_closure(
+        "solve_ode_until",
+        move |function| {
+            let method = Method::Rk45 {
+                tolerance: DEFAULT_TOLERANCE,
+            };
+            solve_ode_with("solve_ode_until", method, Some(event.clone()), function)
+        },
+    ))
+}
@@ -0,0 +1,210 @@
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A complex number with floating-point parts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn real(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    /// The modulus, |z|.
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// The argument, in (-pi, pi].
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn exp(self) -> Self {
+        let scale = self.re.exp();
+        Self::new(scale * self.im.cos(), scale * self.im.sin())
+    }
+
+    /// The principal natural logarithm.
+    pub fn ln(self) -> Self {
+        Self::new(self.abs().ln(), self.arg())
+    }
+
+    /// The principal square root, with a non-negative real part.
+    pub fn sqrt(self) -> Self {
+        let modulus = self.abs();
+        let re = ((modulus + self.re) / 2.0).sqrt();
+        let im = ((modulus - self.re) / 2.0).sqrt();
+        Self::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    /// The principal value of `self ^ exponent`.
+    pub fn pow(self, exponent: Self) -> Self {
+        if exponent == Self::real(0.0) {
+            return Self::real(1.0);
+        }
+        if self == Self::real(0.0) {
+            return Self::real(0.0);
+        }
+        if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= 64.0 {
+            return self.powi(exponent.re as i32);
+        }
+        (exponent * self.ln()).exp()
+    }
+
+    /// Raises to an integer power by repeated squaring, which is exact for
+    /// small Gaussian integers, unlike going through `exp` and `ln`.
+    fn powi(self, exponent: i32) -> Self {
+        let mut result = Self::real(1.0);
+        let mut base = self;
+        let mut power = exponent.unsigned_abs();
+        while power > 0 {
+            if power & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            power >>= 1;
+        }
+        if exponent < 0 {
+            Self::real(1.0) / result
+        } else {
+            result
+        }
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        // Smith's algorithm, which avoids overflow in the denominator.
+        if other.re.abs() >= other.im.abs() {
+            let ratio = other.im / other.re;
+            let denominator = other.re + other.im * ratio;
+            Self::new(
+                (self.re + self.im * ratio) / denominator,
+                (self.im - self.re * ratio) / denominator,
+            )
+        } else {
+            let ratio = other.re / other.im;
+            let denominator = other.re * ratio + other.im;
+            Self::new(
+                (self.re * ratio + self.im) / denominator,
+                (self.im * ratio - self.re) / denominator,
+            )
+        }
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.im.is_sign_negative() && !self.im.is_nan() {
+            write!(f, "{} - {}i", self.re, -self.im)
+        } else {
+            write!(f, "{} + {}i", self.re, self.im)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Complex, expected: Complex) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "expected {expected}, found {actual}"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let z = Complex::new(3.0, 4.0);
+        let w = Complex::new(1.0, -2.0);
+        assert_eq!(z * w, Complex::new(11.0, -2.0));
+        assert_close(z / w, Complex::new(-1.0, 2.0));
+        assert_eq!(z.abs(), 5.0);
+        assert_eq!(z.conj().to_string(), "3 - 4i");
+    }
+
+    #[test]
+    fn test_principal_values() {
+        assert_eq!(Complex::real(-4.0).sqrt(), Complex::new(0.0, 2.0));
+        assert_close(
+            Complex::real(-1.0).ln(),
+            Complex::new(0.0, std::f64::consts::PI),
+        );
+        assert_close(
+            Complex::new(0.0, std::f64::consts::PI).exp(),
+            Complex::real(-1.0),
+        );
+        assert_eq!(
+            Complex::new(0.0, 1.0).pow(Complex::real(2.0)),
+            Complex::real(-1.0)
+        );
+        assert_close(
+            Complex::real(-8.0).pow(Complex::real(1.0 / 3.0)),
+            Complex::new(1.0, 3f64.sqrt()),
+        );
+    }
+}
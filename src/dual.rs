@@ -0,0 +1,201 @@
+use std::fmt::Display;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A dual number `value + derivative * ε`, where `ε ^ 2 = 0`.
+///
+/// Evaluating a function on `x + 1ε` gives `f(x) + f'(x)ε`, which is how
+/// `grad` differentiates functions exactly, through any control flow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivative: f64,
+}
+
+impl Dual {
+    pub fn new(value: f64, derivative: f64) -> Self {
+        Self { value, derivative }
+    }
+
+    /// A value that does not depend on the variable.
+    pub fn constant(value: f64) -> Self {
+        Self::new(value, 0.0)
+    }
+
+    /// The variable itself, whose derivative is 1.
+    pub fn variable(value: f64) -> Self {
+        Self::new(value, 1.0)
+    }
+
+    /// Applies a function given its value and derivative at `self.value`.
+    pub fn chain(self, value: f64, derivative: f64) -> Self {
+        Self::new(value, derivative * self.derivative)
+    }
+
+    /// Raises to a constant power.
+    pub fn powf(self, exponent: f64) -> Self {
+        if exponent == 0.0 {
+            return Self::constant(1.0);
+        }
+        self.chain(
+            self.value.powf(exponent),
+            exponent * self.value.powf(exponent - 1.0),
+        )
+    }
+
+    /// Raises to a power that depends on the variable.
+    pub fn pow(self, exponent: Self) -> Self {
+        if exponent.derivative == 0.0 {
+            return self.powf(exponent.value);
+        }
+        let value = self.value.powf(exponent.value);
+        let derivative = value
+            * (exponent.derivative * self.value.ln()
+                + exponent.value * self.derivative / self.value);
+        Self::new(value, derivative)
+    }
+
+    pub fn sin(self) -> Self {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+
+    pub fn cos(self) -> Self {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+
+    pub fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        self.chain(root, 0.5 / root)
+    }
+
+    pub fn exp(self) -> Self {
+        let value = self.value.exp();
+        self.chain(value, value)
+    }
+
+    pub fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1.0 / self.value)
+    }
+
+    /// The absolute value, whose derivative at 0 is taken to be 0.
+    pub fn abs(self) -> Self {
+        let sign = if self.value == 0.0 {
+            0.0
+        } else {
+            self.value.signum()
+        };
+        self.chain(self.value.abs(), sign)
+    }
+
+    /// The angle of the point `(x, self)` from the positive x axis.
+    pub fn atan2(self, x: Self) -> Self {
+        let y = self;
+        let r2 = x.value * x.value + y.value * y.value;
+        Self::new(
+            y.value.atan2(x.value),
+            (x.value * y.derivative - y.value * x.derivative) / r2,
+        )
+    }
+
+    pub fn hypot(self, other: Self) -> Self {
+        let h = self.value.hypot(other.value);
+        Self::new(
+            h,
+            (self.value * self.derivative + other.value * other.derivative) / h,
+        )
+    }
+}
+
+impl Add for Dual {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.value + other.value, self.derivative + other.derivative)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.value - other.value, self.derivative - other.derivative)
+    }
+}
+
+impl Mul for Dual {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.value * other.value,
+            self.derivative * other.value + self.value * other.derivative,
+        )
+    }
+}
+
+impl Div for Dual {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self::new(
+            self.value / other.value,
+            (self.derivative * other.value - self.value * other.derivative)
+                / (other.value * other.value),
+        )
+    }
+}
+
+impl Neg for Dual {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value, -self.derivative)
+    }
+}
+
+impl Display for Dual {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.derivative.is_sign_negative() && !self.derivative.is_nan() {
+            write!(f, "{} - {}ε", self.value, -self.derivative)
+        } else {
+            write!(f, "{} + {}ε", self.value, self.derivative)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let x = Dual::variable(3.0);
+        assert_eq!(x * x + Dual::constant(2.0) * x, Dual::new(15.0, 8.0));
+        assert_eq!(Dual::constant(1.0) / x, Dual::new(1.0 / 3.0, -1.0 / 9.0));
+        assert_eq!((-x).to_string(), "-3 - 1ε");
+    }
+
+    #[test]
+    fn test_powers() {
+        let x = Dual::variable(2.0);
+        assert_eq!(x.powf(3.0), Dual::new(8.0, 12.0));
+        assert_eq!(Dual::constant(2.0).pow(x), Dual::new(4.0, 4.0 * 2f64.ln()));
+        assert_eq!(x.pow(x), Dual::new(4.0, 4.0 * (2f64.ln() + 1.0)));
+    }
+
+    #[test]
+    fn test_chain_rule() {
+        let x = Dual::variable(0.5);
+        assert_eq!(x.sin().derivative, 0.5f64.cos());
+        assert_eq!((x * x).exp().derivative, 0.25f64.exp());
+        assert_eq!(x.ln().derivative, 2.0);
+        assert_eq!(Dual::variable(-2.0).abs().derivative, -1.0);
+    }
+
+    #[test]
+    fn test_two_arguments() {
+        let (x, y) = (Dual::variable(3.0), Dual::constant(4.0));
+        assert_eq!(x.hypot(y), Dual::new(5.0, 0.6));
+        // d/dx atan2(4, x) = -4 / (x² + 16)
+        assert_eq!(y.atan2(x), Dual::new(4f64.atan2(3.0), -4.0 / 25.0));
+    }
+}
@@ -1,12 +1,24 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::ast::{Expr, LiteralValue, MatchArm};
+use crate::ast::{Expr, LiteralValue, MatchArm, TypeAnnotation};
+use crate::complex::Complex;
+use crate::dual::Dual;
+use crate::interval::Interval;
+use crate::matrix::Matrix;
+use crate::operators::OperatorTable;
+use crate::polynomial::Polynomial;
+use crate::random::Rng;
+use crate::rational::Rational;
 use crate::runtime::{Environment, RuntimeValue};
+use crate::symbolic::{self, Symbolic};
 use crate::token::{Token, TokenType};
+use crate::units::{Quantity, Unit};
 
 pub struct Interpreter {
     globals: Rc<RefCell<Environment>>,
+    operators: RefCell<OperatorTable>,
+    rng: Rc<RefCell<Rng>>,
 }
 
 impl Default for Interpreter {
@@ -17,11 +29,30 @@ impl Default for Interpreter {
 
 impl Interpreter {
     pub fn new() -> Self {
+        Self::with_rng(Rng::from_clock())
+    }
+
+    /// An interpreter whose random numbers are the same on every run.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(Rng::new(seed))
+    }
+
+    fn with_rng(rng: Rng) -> Self {
+        let rng = Rc::new(RefCell::new(rng));
+        let mut globals = Environment::new();
+        globals.bind_random(&rng);
         Self {
-            globals: Rc::new(RefCell::new(Environment::new())),
+            globals: Rc::new(RefCell::new(globals)),
+            operators: RefCell::new(OperatorTable::new()),
+            rng,
         }
     }
 
+    /// Restarts the random numbers, as `seed(n)` does in a program.
+    pub fn seed(&self, seed: u64) {
+        self.rng.borrow_mut().seed(seed);
+    }
+
     pub fn interpret(&self, expr: &Expr) -> Result<RuntimeValue, String> {
         Self::execute(expr, Rc::clone(&self.globals))
     }
@@ -30,6 +61,15 @@ impl Interpreter {
         self.globals.borrow().resolve(name)
     }
 
+    /// The user-defined operators declared by programs run so far.
+    pub fn operators(&self) -> OperatorTable {
+        self.operators.borrow().clone()
+    }
+
+    pub fn set_operators(&self, operators: OperatorTable) {
+        *self.operators.borrow_mut() = operators;
+    }
+
     fn make_unsupported_binary_expr_err(
         left: &RuntimeValue,
         right: &RuntimeValue,
@@ -41,7 +81,10 @@ impl Interpreter {
         ))
     }
 
-    fn execute(expr: &Expr, env: Rc<RefCell<Environment>>) -> Result<RuntimeValue, String> {
+    pub(crate) fn execute(
+        expr: &Expr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<RuntimeValue, String> {
         match expr {
             Expr::Program { statements } => Self::execute_program(statements, Rc::clone(&env)),
             Expr::Literal(literal) => Self::execute_literal(literal),
@@ -50,10 +93,15 @@ impl Interpreter {
             }
             Expr::Unary { op, right } => Self::execute_unary(op, right, Rc::clone(&env)),
             Expr::Grouping(expr) => Self::execute(expr, Rc::clone(&env)),
-            Expr::Binding { name, expr } => {
-                Self::execute_binding(name.clone(), expr, Rc::clone(&env))
+            Expr::Section { op, left, right } => {
+                Self::execute_section(op, left.as_deref(), right.as_deref(), Rc::clone(&env))
             }
-            Expr::Assign { name, expr } => {
+            Expr::Binding {
+                name,
+                annotation,
+                expr,
+            } => Self::execute_binding(name.clone(), annotation.as_ref(), expr, Rc::clone(&env)),
+            Expr::Assign { name, expr, .. } => {
                 Self::execute_assign(name.clone(), expr, Rc::clone(&env))
             }
             Expr::Variable(name) => Self::execute_variable(name.clone(), Rc::clone(&env)),
@@ -61,16 +109,31 @@ impl Interpreter {
                 cond_expr,
                 then_expr,
                 else_expr,
+                ..
             } => Self::execute_if(cond_expr, then_expr, else_expr, Rc::clone(&env)),
             Expr::Match { arms } => Self::execute_match(arms, Rc::clone(&env)),
-            Expr::FunctionDef { param, body } => {
-                Self::execute_function_def(param.clone(), body, Rc::clone(&env))
-            }
+            Expr::FunctionDef {
+                param,
+                param_type,
+                body,
+                return_type,
+            } => Self::execute_function_def(
+                param.clone(),
+                param_type.clone(),
+                body,
+                return_type.clone(),
+                Rc::clone(&env),
+            ),
             Expr::Block { statements } => Self::execute_block(statements, Rc::clone(&env)),
-            Expr::FunctionCall { func, arg } => {
-                Self::execute_function_call(func, arg, Rc::clone(&env))
-            }
+            Expr::FunctionCall {
+                func,
+                paren: _,
+                arg,
+            } => Self::execute_function_call(func, arg, Rc::clone(&env)),
             Expr::List { elements } => Self::execute_list(elements, Rc::clone(&env)),
+            Expr::Matrix { rows } => Self::execute_matrix(rows, Rc::clone(&env)),
+            Expr::Interval { low, high } => Self::execute_interval(low, high, Rc::clone(&env)),
+            Expr::Symbolic(expr) => Ok(RuntimeValue::Symbolic(Symbolic::new(expr, env))),
             Expr::Empty => unreachable!("The program should never contain Empty expressions"),
         }
     }
@@ -89,6 +152,12 @@ impl Interpreter {
     fn execute_literal(literal: &LiteralValue) -> Result<RuntimeValue, String> {
         match literal {
             LiteralValue::Number(n) => Ok(RuntimeValue::Number(*n)),
+            LiteralValue::Integer(n) => Ok(RuntimeValue::Integer(n.clone())),
+            LiteralValue::Imaginary(n) => Ok(RuntimeValue::Complex(Complex::new(0.0, *n))),
+            LiteralValue::Rational(r) => Ok(RuntimeValue::from_rational(r.clone())),
+            LiteralValue::Quantity(value, unit) => {
+                Ok(RuntimeValue::Quantity(Quantity::new(*value, unit.clone())))
+            }
             LiteralValue::String(msg) => Ok(RuntimeValue::String(msg.clone())),
             LiteralValue::Nil => Ok(RuntimeValue::Nil),
             LiteralValue::Boolean(cond) => Ok(RuntimeValue::Boolean(*cond)),
@@ -103,21 +172,84 @@ impl Interpreter {
     ) -> Result<RuntimeValue, String> {
         let left = Self::execute(left, Rc::clone(&env))?;
         let right = Self::execute(right, Rc::clone(&env))?;
+        Self::apply_binary(left, op, right)
+    }
+
+    /// Applies a binary operator to two evaluated operands.
+    pub fn apply_binary(
+        left: RuntimeValue,
+        op: &Token,
+        right: RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        if let TokenType::Dotted(kind) = &op.kind {
+            let op = Token {
+                kind: (**kind).clone(),
+                lexeme: op.lexeme[1..].to_string(),
+                ..op.clone()
+            };
+            return Self::apply_elementwise(left, &op, right);
+        }
+
         match (&left, &right) {
             // For numbers
-            (RuntimeValue::Number(left), RuntimeValue::Number(right)) => match op.kind {
-                TokenType::Plus => Ok(RuntimeValue::Number(left + right)),
-                TokenType::Minus => Ok(RuntimeValue::Number(left - right)),
-                TokenType::Star => Ok(RuntimeValue::Number(left * right)),
-                TokenType::Slash => Ok(RuntimeValue::Number(left / right)),
-                TokenType::Less => Ok(RuntimeValue::Boolean(left < right)),
-                TokenType::LessEqual => Ok(RuntimeValue::Boolean(left <= right)),
-                TokenType::Greater => Ok(RuntimeValue::Boolean(left > right)),
-                TokenType::GreaterEqual => Ok(RuntimeValue::Boolean(left >= right)),
-                TokenType::BangEqual => Ok(RuntimeValue::Boolean(left != right)),
-                TokenType::EqualEqual => Ok(RuntimeValue::Boolean(left == right)),
-                _ => unreachable!("There should be no other binary operators"),
-            },
+            (RuntimeValue::Number(left), RuntimeValue::Number(right)) => {
+                Ok(Self::apply_float(*left, op, *right))
+            }
+            // Integers and fractions stay exact.
+            (
+                RuntimeValue::Integer(_) | RuntimeValue::Rational(_),
+                RuntimeValue::Integer(_) | RuntimeValue::Rational(_),
+            ) => {
+                let (Some(left), Some(right)) = (left.as_rational(), right.as_rational()) else {
+                    unreachable!("Both operands are exact numbers");
+                };
+                Self::apply_rational(&left, op, &right)
+            }
+            // Exact numbers mixed with floats are promoted to floats.
+            (RuntimeValue::Integer(_) | RuntimeValue::Rational(_), RuntimeValue::Number(_))
+            | (RuntimeValue::Number(_), RuntimeValue::Integer(_) | RuntimeValue::Rational(_)) => {
+                let (Some(left), Some(right)) = (left.as_f64(), right.as_f64()) else {
+                    unreachable!("Both operands are numbers");
+                };
+                Ok(Self::apply_float(left, op, right))
+            }
+            // Any number mixed with a complex number is complex.
+            (RuntimeValue::Complex(_), _) | (_, RuntimeValue::Complex(_))
+                if left.as_complex().is_some() && right.as_complex().is_some() =>
+            {
+                Self::apply_complex(&left, op, &right)
+            }
+            (RuntimeValue::Dual(_), _) | (_, RuntimeValue::Dual(_)) => {
+                Self::apply_dual(&left, op, &right)
+            }
+            (RuntimeValue::Interval(_), _) | (_, RuntimeValue::Interval(_)) => {
+                Self::apply_interval(&left, op, &right)
+            }
+            (RuntimeValue::Polynomial(_), _) | (_, RuntimeValue::Polynomial(_)) => {
+                Self::apply_polynomial(&left, op, &right)
+            }
+            (RuntimeValue::Matrix(_), _) | (_, RuntimeValue::Matrix(_)) => {
+                Self::apply_matrix(&left, op, &right)
+            }
+            (RuntimeValue::Symbolic(_), _) | (_, RuntimeValue::Symbolic(_)) => {
+                Self::apply_symbolic(&left, op, &right)
+            }
+            (RuntimeValue::Quantity(_), _) | (_, RuntimeValue::Quantity(_))
+                if matches!(
+                    op.kind,
+                    TokenType::Plus
+                        | TokenType::Minus
+                        | TokenType::Star
+                        | TokenType::Slash
+                        | TokenType::Caret
+                        | TokenType::Less
+                        | TokenType::LessEqual
+                        | TokenType::Greater
+                        | TokenType::GreaterEqual
+                ) =>
+            {
+                Self::apply_quantity(&left, op, &right)
+            }
             (_, _) => match op.kind {
                 // Plus also defined for String and List.
                 TokenType::Plus => match (left, right) {
@@ -132,10 +264,13 @@ impl Interpreter {
                     }),
                     (left, right) => Self::make_unsupported_binary_expr_err(&left, &right, op),
                 },
-                // Minus, Star, Slash not defined for other types.
-                TokenType::Minus | TokenType::Star | TokenType::Slash => {
-                    Self::make_unsupported_binary_expr_err(&left, &right, op)
-                }
+                // The other arithmetic operators are not defined for other types.
+                TokenType::Minus
+                | TokenType::Star
+                | TokenType::Slash
+                | TokenType::Percent
+                | TokenType::Div
+                | TokenType::Caret => Self::make_unsupported_binary_expr_err(&left, &right, op),
                 TokenType::Less => Ok(RuntimeValue::Boolean(left < right)),
                 TokenType::LessEqual => Ok(RuntimeValue::Boolean(left <= right)),
                 TokenType::Greater => Ok(RuntimeValue::Boolean(left > right)),
@@ -147,14 +282,486 @@ impl Interpreter {
         }
     }
 
+    /// Arithmetic on dual numbers and real numbers, which are constants.
+    /// Comparisons only compare the values.
+    fn apply_dual(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let (Some(a), Some(b)) = (left.as_dual(), right.as_dual()) else {
+            return Self::make_unsupported_binary_expr_err(left, right, op);
+        };
+        let result = match op.kind {
+            TokenType::Plus => a + b,
+            TokenType::Minus => a - b,
+            TokenType::Star => a * b,
+            TokenType::Slash => a / b,
+            TokenType::Percent => {
+                let quotient = (a.value / b.value).floor();
+                a - b * Dual::constant(quotient)
+            }
+            TokenType::Div => Dual::constant((a.value / b.value).floor()),
+            TokenType::Caret => a.pow(b),
+            _ => return Ok(Self::apply_float(a.value, op, b.value)),
+        };
+        Ok(RuntimeValue::Dual(result))
+    }
+
+    /// Arithmetic on intervals and real numbers, which are intervals of one number.
+    /// Comparisons are `nil` when the answer depends on where in the intervals
+    /// the numbers are, while `==` compares the intervals themselves.
+    fn apply_interval(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        match op.kind {
+            TokenType::EqualEqual => return Ok(RuntimeValue::Boolean(left == right)),
+            TokenType::BangEqual => return Ok(RuntimeValue::Boolean(left != right)),
+            _ => {}
+        }
+        let (Some(a), Some(b)) = (left.as_interval(), right.as_interval()) else {
+            return Self::make_unsupported_binary_expr_err(left, right, op);
+        };
+        let comparison = |result: Option<bool>| match result {
+            Some(result) => RuntimeValue::Boolean(result),
+            None => RuntimeValue::Nil,
+        };
+        let result = match op.kind {
+            TokenType::Plus => a.add(&b),
+            TokenType::Minus => a.sub(&b),
+            TokenType::Star => a.mul(&b),
+            TokenType::Slash => a.div(&b)?,
+            TokenType::Caret => match right.as_f64() {
+                Some(n) if n >= 0.0 && n.fract() == 0.0 && n <= u32::MAX as f64 => a.powi(n as u32),
+                _ => {
+                    return Err(format!(
+                        "Intervals can only be raised to natural powers, found {right}"
+                    ));
+                }
+            },
+            TokenType::Less => return Ok(comparison(a.less(&b))),
+            TokenType::LessEqual => return Ok(comparison(a.less_equal(&b))),
+            TokenType::Greater => return Ok(comparison(b.less(&a))),
+            TokenType::GreaterEqual => return Ok(comparison(b.less_equal(&a))),
+            _ => return Self::make_unsupported_binary_expr_err(left, right, op),
+        };
+        Ok(RuntimeValue::Interval(result))
+    }
+
+    /// Arithmetic on polynomials and real numbers, which are constant polynomials.
+    /// `div` and `%` give the quotient and remainder of long division.
+    fn apply_polynomial(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        match op.kind {
+            TokenType::EqualEqual => return Ok(RuntimeValue::Boolean(left == right)),
+            TokenType::BangEqual => return Ok(RuntimeValue::Boolean(left != right)),
+            _ => {}
+        }
+        let as_polynomial = |value: &RuntimeValue| match value {
+            RuntimeValue::Polynomial(p) => Some(p.clone()),
+            _ => value.as_f64().map(Polynomial::constant),
+        };
+        let (Some(a), Some(b)) = (as_polynomial(left), as_polynomial(right)) else {
+            return Self::make_unsupported_binary_expr_err(left, right, op);
+        };
+        let result = match (&op.kind, right) {
+            (TokenType::Plus, _) => a.add(&b),
+            (TokenType::Minus, _) => a.sub(&b),
+            (TokenType::Star, _) => a.mul(&b),
+            (TokenType::Div, _) => a.div_rem(&b)?.0,
+            (TokenType::Percent, _) => a.div_rem(&b)?.1,
+            (TokenType::Slash, RuntimeValue::Polynomial(_)) => {
+                return Err(
+                    "Polynomials can only be divided by numbers, use 'div' and '%' for long division"
+                        .to_string(),
+                );
+            }
+            (TokenType::Slash, divisor) => a.scale(1.0 / divisor.as_f64().unwrap_or(f64::NAN)),
+            (TokenType::Caret, RuntimeValue::Polynomial(_)) => {
+                return Self::make_unsupported_binary_expr_err(left, right, op);
+            }
+            (TokenType::Caret, exponent) => match exponent.as_f64() {
+                Some(n) if n >= 0.0 && n.fract() == 0.0 && n <= u32::MAX as f64 => a.pow(n as u32),
+                _ => {
+                    return Err(format!(
+                        "Polynomials can only be raised to natural powers, found {exponent}"
+                    ));
+                }
+            },
+            _ => return Self::make_unsupported_binary_expr_err(left, right, op),
+        };
+        Ok(RuntimeValue::Polynomial(result))
+    }
+
+    /// Builds a formula from arithmetic on formulas and numbers,
+    /// in the scope of the formula on the left if there are two.
+    fn apply_symbolic(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let unsupported = || Self::make_unsupported_binary_expr_err(left, right, op);
+        match op.kind {
+            TokenType::EqualEqual => return Ok(RuntimeValue::Boolean(left == right)),
+            TokenType::BangEqual => return Ok(RuntimeValue::Boolean(left != right)),
+            TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Caret => {}
+            _ => return unsupported(),
+        }
+        let (Some(left_expr), Some(right_expr)) =
+            (symbolic::to_expr(left), symbolic::to_expr(right))
+        else {
+            return unsupported();
+        };
+        let ((RuntimeValue::Symbolic(formula), _) | (_, RuntimeValue::Symbolic(formula))) =
+            (left, right)
+        else {
+            unreachable!("One of the operands is a formula");
+        };
+        Ok(RuntimeValue::Symbolic(formula.with_expr(&Expr::Binary {
+            left: Box::new(left_expr),
+            op: op.clone(),
+            right: Box::new(right_expr),
+        })))
+    }
+
+    fn apply_float(left: f64, op: &Token, right: f64) -> RuntimeValue {
+        match op.kind {
+            TokenType::Plus => RuntimeValue::Number(left + right),
+            TokenType::Minus => RuntimeValue::Number(left - right),
+            TokenType::Star => RuntimeValue::Number(left * right),
+            TokenType::Slash => RuntimeValue::Number(left / right),
+            TokenType::Percent => RuntimeValue::Number(left - right * (left / right).floor()),
+            TokenType::Div => RuntimeValue::Number((left / right).floor()),
+            // A negative base has no real fractional powers, so the
+            // principal complex value is used instead of NaN.
+            TokenType::Caret if left < 0.0 && right.fract() != 0.0 && right.is_finite() => {
+                RuntimeValue::Complex(Complex::real(left).pow(Complex::real(right)))
+            }
+            TokenType::Caret => RuntimeValue::Number(left.powf(right)),
+            TokenType::Less => RuntimeValue::Boolean(left < right),
+            TokenType::LessEqual => RuntimeValue::Boolean(left <= right),
+            TokenType::Greater => RuntimeValue::Boolean(left > right),
+            TokenType::GreaterEqual => RuntimeValue::Boolean(left >= right),
+            TokenType::BangEqual => RuntimeValue::Boolean(left != right),
+            TokenType::EqualEqual => RuntimeValue::Boolean(left == right),
+            _ => unreachable!("There should be no other binary operators"),
+        }
+    }
+
+    /// Exact arithmetic on integers and fractions, where results with a
+    /// denominator of 1 become integers. Only integer powers stay exact.
+    fn apply_rational(
+        left: &Rational,
+        op: &Token,
+        right: &Rational,
+    ) -> Result<RuntimeValue, String> {
+        Ok(match op.kind {
+            TokenType::Plus => RuntimeValue::from_rational(left.add(right)),
+            TokenType::Minus => RuntimeValue::from_rational(left.sub(right)),
+            TokenType::Star => RuntimeValue::from_rational(left.mul(right)),
+            // Dividing by an exact zero gives an infinity or NaN, as it does with floats.
+            TokenType::Slash if right.is_zero() => Self::apply_float(left.to_f64(), op, 0.0),
+            TokenType::Slash => RuntimeValue::from_rational(left.div(right)?),
+            // Division rounds down, so the remainder has the sign of the divisor.
+            TokenType::Div => RuntimeValue::Integer(left.div(right)?.floor()),
+            TokenType::Percent => {
+                let quotient = Rational::from_integer(left.div(right)?.floor());
+                RuntimeValue::from_rational(left.sub(&right.mul(&quotient)))
+            }
+            TokenType::Caret => match right.numerator().to_i64() {
+                Some(power) if right.is_integer() && !(left.is_zero() && power < 0) => {
+                    RuntimeValue::from_rational(left.pow(power)?)
+                }
+                _ => Self::apply_float(left.to_f64(), op, right.to_f64()),
+            },
+            TokenType::Less => RuntimeValue::Boolean(left < right),
+            TokenType::LessEqual => RuntimeValue::Boolean(left <= right),
+            TokenType::Greater => RuntimeValue::Boolean(left > right),
+            TokenType::GreaterEqual => RuntimeValue::Boolean(left >= right),
+            TokenType::BangEqual => RuntimeValue::Boolean(left != right),
+            TokenType::EqualEqual => RuntimeValue::Boolean(left == right),
+            _ => unreachable!("There should be no other binary operators"),
+        })
+    }
+
+    fn apply_complex(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let (Some(a), Some(b)) = (left.as_complex(), right.as_complex()) else {
+            unreachable!("Both operands are numbers");
+        };
+        Ok(match op.kind {
+            TokenType::Plus => RuntimeValue::Complex(a + b),
+            TokenType::Minus => RuntimeValue::Complex(a - b),
+            TokenType::Star => RuntimeValue::Complex(a * b),
+            TokenType::Slash => RuntimeValue::Complex(a / b),
+            TokenType::Caret => RuntimeValue::Complex(a.pow(b)),
+            TokenType::EqualEqual => RuntimeValue::Boolean(a == b),
+            TokenType::BangEqual => RuntimeValue::Boolean(a != b),
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual => {
+                return Err(format!(
+                    "Complex numbers cannot be ordered with '{}': {left}, {right}",
+                    op.lexeme
+                ));
+            }
+            _ => return Self::make_unsupported_binary_expr_err(left, right, op),
+        })
+    }
+
+    /// Arithmetic where at least one operand is a matrix. `*` is the matrix
+    /// product, and matrices can be scaled by real numbers.
+    fn apply_matrix(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let result = match (left, &op.kind, right) {
+            (_, TokenType::EqualEqual, _) => return Ok(RuntimeValue::Boolean(left == right)),
+            (_, TokenType::BangEqual, _) => return Ok(RuntimeValue::Boolean(left != right)),
+            (RuntimeValue::Matrix(a), TokenType::Plus, RuntimeValue::Matrix(b)) => a.add(b)?,
+            (RuntimeValue::Matrix(a), TokenType::Minus, RuntimeValue::Matrix(b)) => a.sub(b)?,
+            (RuntimeValue::Matrix(a), TokenType::Star, RuntimeValue::Matrix(b)) => a.matmul(b)?,
+            (RuntimeValue::Matrix(a), TokenType::Star, scalar)
+            | (scalar, TokenType::Star, RuntimeValue::Matrix(a))
+                if scalar.as_f64().is_some() =>
+            {
+                a.scale(scalar.as_f64().unwrap_or_default())
+            }
+            (RuntimeValue::Matrix(a), TokenType::Slash, scalar) if scalar.as_f64().is_some() => {
+                let divisor = scalar.as_f64().unwrap_or_default();
+                a.map(|x| x / divisor)
+            }
+            _ => return Self::make_unsupported_binary_expr_err(left, right, op),
+        };
+        Ok(RuntimeValue::Matrix(result))
+    }
+
+    /// How many levels of lists a value is nested in, following first elements,
+    /// which is 0 for anything but a list.
+    fn depth(value: &RuntimeValue) -> usize {
+        match value {
+            RuntimeValue::List { elements } => 1 + elements.first().map_or(0, Self::depth),
+            _ => 0,
+        }
+    }
+
+    /// Applies `op` element by element, broadcasting like NumPy: the innermost
+    /// levels of nested lists line up, so an operand with fewer levels is
+    /// combined with each element of the other, and a list of length 1
+    /// stretches to the length of the other list. Matrices are combined
+    /// element by element.
+    fn apply_elementwise(
+        left: RuntimeValue,
+        op: &Token,
+        right: RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let is_arithmetic = matches!(
+            op.kind,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Star
+                | TokenType::Slash
+                | TokenType::Percent
+                | TokenType::Caret
+        );
+        // Matrices only hold real numbers, so there is no complex result for `.^`.
+        let real = |x: f64, y: f64| Self::apply_float(x, op, y).as_f64().unwrap_or(f64::NAN);
+
+        let (left_depth, right_depth) = (Self::depth(&left), Self::depth(&right));
+        let elements: Result<Vec<_>, String> = match (left, right) {
+            // The operand with more levels is split up until the innermost levels line up.
+            (RuntimeValue::List { elements }, right) if left_depth > right_depth => elements
+                .into_iter()
+                .map(|left| Self::apply_elementwise(left, op, right.clone()))
+                .collect(),
+            (left, RuntimeValue::List { elements }) if right_depth > left_depth => elements
+                .into_iter()
+                .map(|right| Self::apply_elementwise(left.clone(), op, right))
+                .collect(),
+            (RuntimeValue::List { elements: left }, RuntimeValue::List { elements: right }) => {
+                let length = match (left.len(), right.len()) {
+                    (a, b) if a == b => a,
+                    (1, b) => b,
+                    (a, 1) => a,
+                    (a, b) => {
+                        return Err(format!(
+                            "Cannot apply '.{}' to lists of lengths {a} and {b}",
+                            op.lexeme
+                        ));
+                    }
+                };
+                (0..length)
+                    .map(|i| {
+                        let left = left[i.min(left.len() - 1)].clone();
+                        let right = right[i.min(right.len() - 1)].clone();
+                        Self::apply_elementwise(left, op, right)
+                    })
+                    .collect()
+            }
+            (RuntimeValue::Matrix(a), RuntimeValue::Matrix(b)) if is_arithmetic => {
+                let verb = format!("apply '.{}' to", op.lexeme);
+                return Ok(RuntimeValue::Matrix(a.zip_with(&b, &verb, real)?));
+            }
+            (RuntimeValue::Matrix(a), scalar) if is_arithmetic && scalar.as_f64().is_some() => {
+                let y = scalar.as_f64().unwrap_or_default();
+                return Ok(RuntimeValue::Matrix(a.map(|x| real(x, y))));
+            }
+            (scalar, RuntimeValue::Matrix(b)) if is_arithmetic && scalar.as_f64().is_some() => {
+                let x = scalar.as_f64().unwrap_or_default();
+                return Ok(RuntimeValue::Matrix(b.map(|y| real(x, y))));
+            }
+            (left @ RuntimeValue::Matrix(_), right) | (left, right @ RuntimeValue::Matrix(_)) => {
+                return Err(format!(
+                    "Unsupported operands for '.{}': {left}, {right}",
+                    op.lexeme
+                ));
+            }
+            (left, right) => return Self::apply_binary(left, op, right),
+        };
+        Ok(RuntimeValue::List {
+            elements: elements?,
+        })
+    }
+
+    /// Arithmetic and ordering where at least one operand is a quantity.
+    /// Plain numbers are treated as dimensionless quantities.
+    fn apply_quantity(
+        left: &RuntimeValue,
+        op: &Token,
+        right: &RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        let as_quantity = |value: &RuntimeValue| match value {
+            RuntimeValue::Quantity(quantity) => Some(quantity.clone()),
+            _ => value.as_f64().map(|n| Quantity::new(n, Unit::none())),
+        };
+        let (Some(left_quantity), Some(right_quantity)) = (as_quantity(left), as_quantity(right))
+        else {
+            return Self::make_unsupported_binary_expr_err(left, right, op);
+        };
+
+        let result = match op.kind {
+            TokenType::Plus => left_quantity.add(&right_quantity)?,
+            TokenType::Minus => left_quantity.sub(&right_quantity)?,
+            TokenType::Star => left_quantity.mul(&right_quantity),
+            TokenType::Slash => left_quantity.div(&right_quantity),
+            TokenType::Caret => match right.as_f64() {
+                Some(n) if n.fract() == 0.0 => left_quantity.pow(n as i32),
+                _ => {
+                    return Err(format!(
+                        "A quantity can only be raised to an integer power, found {right}"
+                    ));
+                }
+            },
+            TokenType::Less => {
+                let result = left_quantity.less(&right_quantity, &op.lexeme)?;
+                return Ok(RuntimeValue::Boolean(result));
+            }
+            TokenType::LessEqual => {
+                let result = left_quantity.less_equal(&right_quantity, &op.lexeme)?;
+                return Ok(RuntimeValue::Boolean(result));
+            }
+            TokenType::Greater => {
+                let result = right_quantity.less(&left_quantity, &op.lexeme)?;
+                return Ok(RuntimeValue::Boolean(result));
+            }
+            TokenType::GreaterEqual => {
+                let result = right_quantity.less_equal(&left_quantity, &op.lexeme)?;
+                return Ok(RuntimeValue::Boolean(result));
+            }
+            _ => unreachable!("Only arithmetic and ordering operators apply to quantities"),
+        };
+        Ok(RuntimeValue::from_quantity(result))
+    }
+
+    fn execute_section(
+        op: &Token,
+        left: Option<&Expr>,
+        right: Option<&Expr>,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<RuntimeValue, String> {
+        // The supplied operand is evaluated once, when the section is created.
+        let left = left
+            .map(|expr| Self::execute(expr, Rc::clone(&env)))
+            .transpose()?;
+        let right = right
+            .map(|expr| Self::execute(expr, Rc::clone(&env)))
+            .transpose()?;
+        let name = match (&left, &right) {
+            (Some(left), _) => format!("({left} {})", op.lexeme),
+            (_, Some(right)) => format!("({} {right})", op.lexeme),
+            (None, None) => format!("({})", op.lexeme),
+        };
+        let op = op.clone();
+
+        Ok(match (left, right) {
+            (Some(left), _) => RuntimeValue::native_closure(&name, move |right| {
+                Self::apply_binary(left.clone(), &op, right)
+            }),
+            (_, Some(right)) => RuntimeValue::native_closure(&name, move |left| {
+                Self::apply_binary(left, &op, right.clone())
+            }),
+            (None, None) => {
+                let partial_name = name.clone();
+                RuntimeValue::native_closure(&name, move |left| {
+                    let op = op.clone();
+                    Ok(RuntimeValue::native_closure(&partial_name, move |right| {
+                        Self::apply_binary(left.clone(), &op, right)
+                    }))
+                })
+            }
+        })
+    }
+
     fn execute_unary(
         op: &Token,
         right: &Expr,
         env: Rc<RefCell<Environment>>,
     ) -> Result<RuntimeValue, String> {
         let r = Self::execute(right, env)?;
+        Self::apply_unary(op, r)
+    }
+
+    /// Applies a unary operator to an evaluated operand.
+    /// Negation maps over the elements of (nested) lists.
+    fn apply_unary(op: &Token, r: RuntimeValue) -> Result<RuntimeValue, String> {
         match (&op.kind, r.clone()) {
+            (TokenType::Minus, RuntimeValue::List { elements }) => Ok(RuntimeValue::List {
+                elements: elements
+                    .into_iter()
+                    .map(|element| Self::apply_unary(op, element))
+                    .collect::<Result<_, _>>()?,
+            }),
             (TokenType::Minus, RuntimeValue::Number(n)) => Ok(RuntimeValue::Number(-n)),
+            (TokenType::Minus, RuntimeValue::Integer(n)) => Ok(RuntimeValue::Integer(-&n)),
+            (TokenType::Minus, RuntimeValue::Matrix(m)) => Ok(RuntimeValue::Matrix(m.scale(-1.0))),
+            (TokenType::Minus, RuntimeValue::Complex(c)) => Ok(RuntimeValue::Complex(-c)),
+            (TokenType::Minus, RuntimeValue::Dual(d)) => Ok(RuntimeValue::Dual(-d)),
+            (TokenType::Minus, RuntimeValue::Interval(i)) => Ok(RuntimeValue::Interval(i.neg())),
+            (TokenType::Minus, RuntimeValue::Polynomial(p)) => {
+                Ok(RuntimeValue::Polynomial(p.scale(-1.0)))
+            }
+            (TokenType::Minus, RuntimeValue::Rational(r)) => Ok(RuntimeValue::Rational(r.neg())),
+            (TokenType::Minus, RuntimeValue::Quantity(q)) => Ok(RuntimeValue::Quantity(q.neg())),
+            (TokenType::Minus, RuntimeValue::Symbolic(s)) => {
+                Ok(RuntimeValue::Symbolic(s.with_expr(&Expr::Unary {
+                    op: op.clone(),
+                    right: Box::new(s.expr().clone()),
+                })))
+            }
             (TokenType::Minus, _) => Err("Operand for unary '-' must be a number".to_string()),
             (TokenType::Bang, RuntimeValue::Boolean(cond)) => Ok(RuntimeValue::Boolean(!cond)),
             (TokenType::Bang, _) => Ok(RuntimeValue::Boolean(!r.is_truthy())),
@@ -164,11 +771,20 @@ impl Interpreter {
 
     fn execute_binding(
         name: String,
+        annotation: Option<&TypeAnnotation>,
         expr: &Expr,
         env: Rc<RefCell<Environment>>,
     ) -> Result<RuntimeValue, String> {
         let value = Self::execute(expr, Rc::clone(&env))?;
-        env.borrow_mut().bind(name, value.clone())?;
+        if let Some(annotation) = annotation
+            && !value.has_type(annotation)
+        {
+            return Err(format!(
+                "Binding '{name}' expects {annotation}, found {value}"
+            ));
+        }
+        env.borrow_mut()
+            .bind_annotated(name, value.clone(), annotation.cloned())?;
         Ok(value)
     }
 
@@ -223,12 +839,16 @@ impl Interpreter {
 
     fn execute_function_def(
         param: String,
+        param_type: Option<TypeAnnotation>,
         body: &Expr,
+        return_type: Option<TypeAnnotation>,
         env: Rc<RefCell<Environment>>,
     ) -> Result<RuntimeValue, String> {
         Ok(RuntimeValue::Function {
             arg_name: param,
+            param_type,
             body: body.clone(),
+            return_type,
             closure: Rc::clone(&env),
         })
     }
@@ -255,28 +875,101 @@ impl Interpreter {
         env: Rc<RefCell<Environment>>,
     ) -> Result<RuntimeValue, String> {
         let function = Self::execute(func, Rc::clone(&env))?;
+        if !function.is_callable() {
+            return Err("Only functions are callable".to_string());
+        }
+
+        // poly(x^2 - 3x + 2) reads a polynomial in a free variable as a formula.
+        if let RuntimeValue::NativeFunction {
+            reads_formula: true,
+            ..
+        } = &function
+            && let Some((Some(variable), _)) = symbolic::polynomial(arg)
+            && env.borrow().resolve(&variable).is_none()
+        {
+            return Self::call(&function, RuntimeValue::Symbolic(Symbolic::new(arg, env)));
+        }
+
+        let arg_value = Self::execute(arg, Rc::clone(&env))?;
+        Self::call(&function, arg_value)
+    }
 
+    /// Calls a function value with an already evaluated argument.
+    /// This allows native functions to call back into MathFP functions.
+    pub fn call(function: &RuntimeValue, arg: RuntimeValue) -> Result<RuntimeValue, String> {
         match function {
             RuntimeValue::Function {
                 arg_name,
+                param_type,
                 body,
+                return_type,
                 closure,
             } => {
-                let arg_value = Self::execute(arg, Rc::clone(&env))?;
+                if let Some(param_type) = param_type
+                    && !arg.has_type(param_type)
+                {
+                    return Err(format!(
+                        "Parameter '{arg_name}' expects {param_type}, found {arg}"
+                    ));
+                }
 
                 // The parent of the new scope is the closure
-                let local_env = Rc::new(RefCell::new(Environment::with_parent(closure)));
-                local_env.borrow_mut().bind(arg_name, arg_value)?;
-                Self::execute(&body, local_env)
-            }
-            RuntimeValue::NativeFunction { name: _, function } => {
-                let arg_value = Self::execute(arg, Rc::clone(&env))?;
-                Ok(function(arg_value)?)
+                let local_env = Rc::new(RefCell::new(Environment::with_parent(Rc::clone(closure))));
+                local_env
+                    .borrow_mut()
+                    .bind_annotated(arg_name.clone(), arg, param_type.clone())?;
+                let result = Self::execute(body, local_env)?;
+
+                match return_type {
+                    Some(return_type) if !result.has_type(return_type) => Err(format!(
+                        "Function of '{arg_name}' expects to return {return_type}, found {result}"
+                    )),
+                    _ => Ok(result),
+                }
             }
+            RuntimeValue::NativeFunction { function, .. } => function(arg),
+            RuntimeValue::NativeClosure { name: _, function } => function.call(arg),
+            RuntimeValue::Memoized(memo) => memo.call(arg),
+            RuntimeValue::Polynomial(p) => Self::evaluate_polynomial(p, arg),
             _ => Err("Only functions are callable".to_string()),
         }
     }
 
+    /// Evaluates a polynomial with Horner's method, through the arithmetic
+    /// operators so that it works on any number, including dual numbers.
+    fn evaluate_polynomial(
+        polynomial: &Polynomial,
+        x: RuntimeValue,
+    ) -> Result<RuntimeValue, String> {
+        if let Some(x) = x.as_f64() {
+            let value = polynomial
+                .coefficients()
+                .iter()
+                .rev()
+                .fold(0.0, |sum, c| sum * x + c);
+            return Ok(RuntimeValue::Number(value));
+        }
+        let plus = Token {
+            kind: TokenType::Plus,
+            lexeme: "+".into(),
+            line: 0,
+            column: 0,
+        };
+        let star = Token {
+            kind: TokenType::Star,
+            lexeme: "*".into(),
+            ..plus.clone()
+        };
+        polynomial
+            .coefficients()
+            .iter()
+            .rev()
+            .try_fold(RuntimeValue::Number(0.0), |sum, c| {
+                let product = Self::apply_binary(sum, &star, x.clone())?;
+                Self::apply_binary(product, &plus, RuntimeValue::Number(*c))
+            })
+    }
+
     fn execute_list(
         elements: &[Expr],
         env: Rc<RefCell<Environment>>,
@@ -288,6 +981,44 @@ impl Interpreter {
 
         Ok(RuntimeValue::List { elements: values })
     }
+
+    fn execute_matrix(
+        rows: &[Vec<Expr>],
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<RuntimeValue, String> {
+        let mut values: Vec<Vec<f64>> = vec![];
+        for row in rows {
+            let mut row_values = vec![];
+            for expr in row {
+                let value = Self::execute(expr, Rc::clone(&env))?;
+                match value.as_f64() {
+                    Some(n) => row_values.push(n),
+                    None => {
+                        return Err(format!(
+                            "Matrix elements must be real numbers, found {value}"
+                        ));
+                    }
+                }
+            }
+            values.push(row_values);
+        }
+        Ok(RuntimeValue::Matrix(Matrix::from_rows(values)?))
+    }
+
+    fn execute_interval(
+        low: &Expr,
+        high: &Expr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<RuntimeValue, String> {
+        let bound = |expr: &Expr| {
+            let value = Self::execute(expr, Rc::clone(&env))?;
+            value
+                .as_interval_bound()
+                .ok_or_else(|| format!("Interval bounds must be real numbers, found {value}"))
+        };
+        let (low, high) = (bound(low)?, bound(high)?);
+        Ok(RuntimeValue::Interval(Interval::new(low.low, high.high)?))
+    }
 }
 
 #[cfg(test)]
@@ -342,6 +1073,7 @@ mod tests {
         // x := 100
         let bind_expr = Expr::Binding {
             name: "x".into(),
+            annotation: None,
             expr: Box::new(Expr::Literal(LiteralValue::Number(100.0))),
         };
         interpreter.interpret(&bind_expr).unwrap();
@@ -362,6 +1094,7 @@ mod tests {
         // true := 5 (should fail)
         let expr = Expr::Binding {
             name: "true".into(),
+            annotation: None,
             expr: Box::new(Expr::Literal(LiteralValue::Number(5.0))),
         };
 
@@ -394,6 +1127,7 @@ mod tests {
 
         // if true then 10 else 20
         let expr = Expr::If {
+            keyword: op_token(TokenType::If),
             cond_expr: Box::new(Expr::Variable("true".into())),
             then_expr: Box::new(Expr::Literal(LiteralValue::Number(10.0))),
             else_expr: Box::new(Expr::Literal(LiteralValue::Number(20.0))),
@@ -405,6 +1139,7 @@ mod tests {
 
         // if false then 10 else 20
         let expr_false = Expr::If {
+            keyword: op_token(TokenType::If),
             cond_expr: Box::new(Expr::Variable("false".into())),
             then_expr: Box::new(Expr::Literal(LiteralValue::Number(10.0))),
             else_expr: Box::new(Expr::Literal(LiteralValue::Number(20.0))),
@@ -424,6 +1159,7 @@ mod tests {
             statements: vec![
                 Expr::Binding {
                     name: "a".into(),
+                    annotation: None,
                     expr: Box::new(Expr::Literal(LiteralValue::Number(1.0))),
                 },
                 Expr::Binary {
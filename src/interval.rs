@@ -0,0 +1,431 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt::Display;
+
+/// A closed interval of real numbers `[low .. high]`, standing for an
+/// uncertain number somewhere within it.
+///
+/// Floats cannot choose their rounding direction, so each bound computed is
+/// moved outward by one unit in the last place whenever it was rounded.
+/// The result is then sure to contain the exact result for every number in
+/// the operands, without widening results that floats hold exactly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Smallest magnitude of a product whose rounding error an FMA finds exactly.
+/// Below it the error may be lost to underflow.
+const MIN_EXACT_ERROR: f64 = 1e-290;
+
+/// Rounds a computed lower bound down.
+fn down(x: f64) -> f64 {
+    x.next_down()
+}
+
+/// Rounds a computed upper bound up.
+fn up(x: f64) -> f64 {
+    x.next_up()
+}
+
+/// Bounds on an exact result, given its rounded value and the sign of
+/// `exact - rounded`, or `None` if that is unknown.
+fn bounds(rounded: f64, error: Option<f64>) -> (f64, f64) {
+    match error {
+        _ if rounded.is_nan() => (rounded, rounded),
+        Some(0.0) => (rounded, rounded),
+        Some(error) if error > 0.0 => (rounded, up(rounded)),
+        Some(_) => (down(rounded), rounded),
+        None => (down(rounded), up(rounded)),
+    }
+}
+
+/// Bounds on `a + b`. The rounding error of a sum is exact (TwoSum).
+fn sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    if s.is_infinite() && a.is_finite() && b.is_finite() {
+        return if s > 0.0 {
+            (f64::MAX, s)
+        } else {
+            (s, f64::MIN)
+        };
+    }
+    if !s.is_finite() {
+        return (s, s);
+    }
+    let b_part = s - a;
+    let error = (a - (s - b_part)) + (b - b_part);
+    bounds(s, Some(error))
+}
+
+/// Bounds on `a * b`, with the rounding error found by an FMA.
+fn product(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 || b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let p = a * b;
+    if p.is_infinite() && a.is_finite() && b.is_finite() {
+        return if p > 0.0 {
+            (f64::MAX, p)
+        } else {
+            (p, f64::MIN)
+        };
+    }
+    if !p.is_finite() || p.abs() < MIN_EXACT_ERROR {
+        return bounds(p, None);
+    }
+    bounds(p, Some(a.mul_add(b, -p)))
+}
+
+/// Bounds on `a / b`, with the remainder `a - q * b` found by an FMA.
+fn quotient(a: f64, b: f64) -> (f64, f64) {
+    if a == 0.0 && b != 0.0 {
+        return (0.0, 0.0);
+    }
+    let q = a / b;
+    if !q.is_finite() || q.abs() < MIN_EXACT_ERROR || b.is_infinite() {
+        return bounds(q, None);
+    }
+    // `a / b - q` is `remainder / b`.
+    let remainder = -q.mul_add(b, -a);
+    bounds(q, Some(remainder / b))
+}
+
+impl Interval {
+    pub fn new(low: f64, high: f64) -> Result<Self, String> {
+        if low.is_nan() || high.is_nan() {
+            return Err("An interval cannot have a NaN bound".into());
+        }
+        if low > high {
+            return Err(format!(
+                "An interval needs its lower bound first, found [{low} .. {high}]"
+            ));
+        }
+        Ok(Self { low, high })
+    }
+
+    /// The interval holding only `x`.
+    pub fn point(x: f64) -> Self {
+        Self { low: x, high: x }
+    }
+
+    /// An upper bound on the width.
+    pub fn width(&self) -> f64 {
+        sum(self.high, -self.low).1
+    }
+
+    pub fn mid(&self) -> f64 {
+        self.low / 2.0 + self.high / 2.0
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.low <= x && x <= self.high
+    }
+
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        self.low <= other.low && other.high <= self.high
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Self {
+            low: sum(self.low, other.low).0,
+            high: sum(self.high, other.high).1,
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            low: -self.high,
+            high: -self.low,
+        }
+    }
+
+    /// The smallest and largest of the products of the bounds.
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::hull(&[
+            product(self.low, other.low),
+            product(self.low, other.high),
+            product(self.high, other.low),
+            product(self.high, other.high),
+        ])
+    }
+
+    pub fn div(&self, other: &Self) -> Result<Self, String> {
+        if other.contains(0.0) {
+            return Err(format!(
+                "Division by an interval containing 0, found {other}"
+            ));
+        }
+        Ok(Self::hull(&[
+            quotient(self.low, other.low),
+            quotient(self.low, other.high),
+            quotient(self.high, other.low),
+            quotient(self.high, other.high),
+        ]))
+    }
+
+    /// The smallest interval containing all of the bounds.
+    fn hull(bounds: &[(f64, f64)]) -> Self {
+        Self {
+            low: bounds.iter().map(|b| b.0).fold(f64::INFINITY, f64::min),
+            high: bounds.iter().map(|b| b.1).fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    /// Raises to a natural power, which for even powers is never negative
+    /// even if the interval contains 0.
+    pub fn powi(&self, exponent: u32) -> Self {
+        if exponent == 0 {
+            return Self::point(1.0);
+        }
+        let of_point = |x: f64| {
+            let power = Self::point(x.abs()).pow_nonnegative(exponent);
+            if x < 0.0 && exponent % 2 == 1 {
+                power.neg()
+            } else {
+                power
+            }
+        };
+        if exponent % 2 == 1 {
+            // Odd powers increase, so the bounds map to the bounds.
+            Self {
+                low: of_point(self.low).low,
+                high: of_point(self.high).high,
+            }
+        } else {
+            let smallest = if self.contains(0.0) {
+                0.0
+            } else {
+                self.low.abs().min(self.high.abs())
+            };
+            let largest = self.low.abs().max(self.high.abs());
+            Self {
+                low: of_point(smallest).low,
+                high: of_point(largest).high,
+            }
+        }
+    }
+
+    /// Raises an interval of non-negative numbers to a power by squaring,
+    /// where each product is as tight as the rounding allows.
+    fn pow_nonnegative(&self, exponent: u32) -> Self {
+        let mut result = Self::point(1.0);
+        let mut base = *self;
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            exponent /= 2;
+        }
+        result
+    }
+
+    pub fn sqrt(&self) -> Result<Self, String> {
+        if self.low < 0.0 {
+            return Err(format!(
+                "sqrt() expects an interval of non-negative numbers, found {self}"
+            ));
+        }
+        // `x - s * s` tells which side of the rounded root `s` the exact one is.
+        let root = |x: f64| {
+            let s = x.sqrt();
+            if s == 0.0 || s.is_infinite() {
+                return (s, s);
+            }
+            bounds(s, Some(-s.mul_add(s, -x)))
+        };
+        Ok(Self {
+            low: root(self.low).0,
+            high: root(self.high).1,
+        })
+    }
+
+    pub fn sin(&self) -> Self {
+        self.periodic(f64::sin, FRAC_PI_2, -FRAC_PI_2)
+    }
+
+    pub fn cos(&self) -> Self {
+        self.periodic(f64::cos, 0.0, PI)
+    }
+
+    /// Applies `sin` or `cos`, given where within each period they reach 1 and -1.
+    /// Between those points the function is monotonic, so the result spans
+    /// the values at the bounds and any extremes the interval reaches.
+    fn periodic(&self, f: fn(f64) -> f64, maximum_at: f64, minimum_at: f64) -> Self {
+        let whole = Self {
+            low: -1.0,
+            high: 1.0,
+        };
+        let span = self.high - self.low;
+        if span.is_nan() || span >= TAU {
+            return whole;
+        }
+        // `PI` is rounded, so extremes just outside the interval count as inside,
+        // which can only make the result wider.
+        let slack = 4.0 * f64::EPSILON * self.low.abs().max(self.high.abs()).max(1.0);
+        let reaches = |at: f64| {
+            let k = ((self.low - slack - at) / TAU).ceil();
+            at + k * TAU <= self.high + slack
+        };
+        let (a, b) = (f(self.low), f(self.high));
+        Self {
+            low: if reaches(minimum_at) {
+                -1.0
+            } else {
+                down(a.min(b)).max(-1.0)
+            },
+            high: if reaches(maximum_at) {
+                1.0
+            } else {
+                up(a.max(b)).min(1.0)
+            },
+        }
+    }
+
+    /// Applies a function that increases across the interval.
+    /// Library functions may be off by a unit in the last place,
+    /// so each bound is moved outward by one.
+    pub fn increasing(&self, f: fn(f64) -> f64) -> Self {
+        Self {
+            low: down(f(self.low)),
+            high: up(f(self.high)),
+        }
+    }
+
+    /// Applies a function that decreases across the interval.
+    pub fn decreasing(&self, f: fn(f64) -> f64) -> Self {
+        Self {
+            low: down(f(self.high)),
+            high: up(f(self.low)),
+        }
+    }
+
+    /// Applies a step function like `floor`, which rounds nothing.
+    pub fn steps(&self, f: fn(f64) -> f64) -> Self {
+        Self {
+            low: f(self.low),
+            high: f(self.high),
+        }
+    }
+
+    /// The smallest of each pair of numbers, one from each interval.
+    pub fn min(&self, other: &Self) -> Self {
+        Self {
+            low: self.low.min(other.low),
+            high: self.high.min(other.high),
+        }
+    }
+
+    /// The largest of each pair of numbers, one from each interval.
+    pub fn max(&self, other: &Self) -> Self {
+        Self {
+            low: self.low.max(other.low),
+            high: self.high.max(other.high),
+        }
+    }
+
+    /// Whether every number in `self` is less than every number in `other`,
+    /// or `None` if that depends on which numbers they are.
+    pub fn less(&self, other: &Self) -> Option<bool> {
+        if self.high < other.low {
+            Some(true)
+        } else if self.low >= other.high {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn less_equal(&self, other: &Self) -> Option<bool> {
+        if self.high <= other.low {
+            Some(true)
+        } else if self.low > other.high {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} .. {}]", self.low, self.high)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(low: f64, high: f64) -> Interval {
+        Interval::new(low, high).unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic_contains_exact_result() {
+        let a = interval(0.1, 0.2);
+        let b = interval(0.2, 0.3);
+        assert!(a.add(&b).contains(0.1 + 0.2));
+        assert!(a.add(&b).contains(0.2 + 0.3));
+        assert_eq!(a.sub(&b).high, 0.0);
+        assert_eq!(
+            interval(1.0, 2.0).add(&interval(0.5, 0.5)),
+            interval(1.5, 2.5)
+        );
+        let product = interval(-1.0, 2.0).mul(&interval(3.0, 4.0));
+        assert!(product.contains_interval(&interval(-4.0, 8.0)));
+        assert!(interval(1.0, 2.0).div(&interval(-1.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_powers() {
+        let x = interval(-1.0, 2.0);
+        assert_eq!(x.powi(2), interval(0.0, 4.0));
+        assert_eq!(x.powi(3), interval(-1.0, 8.0));
+        let tenth = interval(0.1, 0.1).powi(2);
+        assert!(tenth.low < tenth.high && tenth.contains(0.1 * 0.1));
+        assert_eq!(x.powi(0), Interval::point(1.0));
+    }
+
+    #[test]
+    fn test_functions() {
+        let around_peak = interval(1.0, 2.0).sin();
+        assert_eq!(around_peak.high, 1.0);
+        assert!(around_peak.contains(1f64.sin()) && around_peak.low < 2f64.sin());
+        assert_eq!(interval(0.0, 7.0).cos(), interval(-1.0, 1.0));
+        let rising = interval(-0.5, 0.5).sin();
+        assert!(rising.contains((-0.5f64).sin()) && rising.contains(0.5f64.sin()));
+        assert!(rising.high < 0.48);
+        assert_eq!(interval(4.0, 9.0).sqrt().unwrap(), interval(2.0, 3.0));
+        let root = interval(2.0, 2.0).sqrt().unwrap();
+        assert!(root.low < root.high && root.contains(2.0f64.sqrt()));
+        assert!(interval(-1.0, 4.0).sqrt().is_err());
+    }
+
+    #[test]
+    fn test_monotonic_functions() {
+        let x = interval(0.0, 1.0);
+        let exp = x.increasing(f64::exp);
+        assert!(exp.low < 1.0 && exp.contains(1f64.exp()));
+        let acos = x.decreasing(f64::acos);
+        assert!(acos.contains(0.0) && acos.contains(FRAC_PI_2));
+        assert_eq!(interval(-0.5, 2.5).steps(f64::floor), interval(-1.0, 2.0));
+        assert_eq!(x.min(&interval(0.5, 2.0)), interval(0.0, 1.0));
+        assert_eq!(x.max(&interval(0.5, 2.0)), interval(0.5, 2.0));
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let a = interval(1.0, 2.0);
+        assert_eq!(a.less(&interval(3.0, 4.0)), Some(true));
+        assert_eq!(a.less(&interval(0.0, 1.0)), Some(false));
+        assert_eq!(a.less(&interval(1.5, 3.0)), None);
+        assert_eq!(a.less_equal(&interval(2.0, 3.0)), Some(true));
+    }
+}
@@ -1,10 +1,29 @@
 pub mod ast;
+pub mod bigint;
 pub mod builtins;
+pub mod complex;
+pub mod dual;
 pub mod interpreter;
+pub mod interval;
+pub mod matrix;
+pub mod memo;
+pub mod number_theory;
+pub mod numeric;
+pub mod ode;
+pub mod operators;
 pub mod parser;
+pub mod polynomial;
+pub mod random;
+pub mod rational;
 pub mod runtime;
 pub mod scanner;
+pub mod special;
+pub mod stats;
+pub mod stream;
+pub mod symbolic;
 pub mod token;
+pub mod typecheck;
+pub mod units;
 
 pub fn execute(input: &str) -> Result<runtime::RuntimeValue, String> {
     execute_env(input, &interpreter::Interpreter::new())
@@ -18,6 +37,25 @@ pub fn execute_env(
     input: &str,
     interpreter: &interpreter::Interpreter,
 ) -> Result<runtime::RuntimeValue, String> {
+    // Operators declared by earlier inputs can be used in this one.
+    let operators = interpreter.operators();
+
+    let tokens = scanner::Scanner::with_operators(input, &operators)
+        .scan()
+        .map_err(|errors| scanner::Scanner::report(&errors))?;
+
+    let mut parser = parser::Parser::with_operators(tokens, operators);
+    let expr = parser
+        .parse()
+        .map_err(|errors| parser::Parser::report(&errors))?;
+    interpreter.set_operators(parser.operators().clone());
+
+    interpreter.interpret(&expr)
+}
+
+/// Infers the type of a program without running it.
+/// Returns the type of the last statement, or the type errors found.
+pub fn check(input: &str) -> Result<typecheck::Type, String> {
     let tokens = scanner::Scanner::new(input)
         .scan()
         .map_err(|errors| scanner::Scanner::report(&errors))?;
@@ -26,7 +64,9 @@ pub fn execute_env(
         .parse()
         .map_err(|errors| parser::Parser::report(&errors))?;
 
-    interpreter.interpret(&expr)
+    typecheck::TypeChecker::new()
+        .check(&expr)
+        .map_err(|errors| typecheck::TypeChecker::report(&errors))
 }
 
 pub fn execute_env_or_panic(
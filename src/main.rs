@@ -1,10 +1,11 @@
 use std::fs;
 use std::io::{self, Write};
 
-use mathfp::{execute_env, interpreter, runtime};
+use mathfp::{check, execute_env, interpreter, runtime};
 
 fn usage() {
     println!("Usage: mathfp [file_name]");
+    println!("       mathfp check <file_name>");
 }
 
 fn run_file(file_name: &str) -> Result<(), String> {
@@ -17,6 +18,22 @@ fn run_file(file_name: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn check_file(file_name: &str) -> Result<(), String> {
+    let contents = fs::read_to_string(file_name)
+        .map_err(|e| format!("Could not read file {file_name}: {e}"))?;
+
+    match check(&contents) {
+        Ok(ty) => println!("{ty}"),
+        Err(e) => {
+            eprintln!("{e}");
+            // Exits with a failure status, so scripts can tell ill-typed programs apart.
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
 fn run_repl() -> Result<(), String> {
     let interpreter = interpreter::Interpreter::new();
 
@@ -52,6 +69,7 @@ fn main() -> Result<(), String> {
     match argv.len() {
         1 => run_repl(),
         2 => run_file(&argv[1]),
+        3 if argv[1] == "check" => check_file(&argv[2]),
         _ => {
             usage();
             Err("Invalid number of arguments.".to_string())
@@ -0,0 +1,577 @@
+use std::fmt::Display;
+
+use crate::complex::Complex;
+
+/// A dense matrix of real numbers, stored row by row.
+/// A vector is a matrix with a single column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+/// An LU decomposition with partial pivoting, `PA = LU`.
+struct Lu {
+    n: usize,
+    /// L below the diagonal, with an implicit unit diagonal, and U above it.
+    factors: Vec<f64>,
+    permutation: Vec<usize>,
+    /// The sign of the permutation, for the determinant.
+    sign: f64,
+    singular: bool,
+}
+
+impl Matrix {
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Result<Self, String> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err("A matrix needs at least one element".into());
+        }
+        if let Some(index) = rows.iter().position(|row| row.len() != cols) {
+            return Err(format!(
+                "Matrix rows must have the same length: row {} has {} elements, expected {cols}",
+                index + 1,
+                rows[index].len()
+            ));
+        }
+        Ok(Self {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        })
+    }
+
+    pub fn vector(values: Vec<f64>) -> Self {
+        Self {
+            rows: values.len(),
+            cols: 1,
+            data: values,
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut data = vec![0.0; n * n];
+        for i in 0..n {
+            data[i * n + i] = 1.0;
+        }
+        Self {
+            rows: n,
+            cols: n,
+            data,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_vector(&self) -> bool {
+        self.cols == 1
+    }
+
+    /// The shape as written in errors, like `2x3`.
+    pub fn shape(&self) -> String {
+        format!("{}x{}", self.rows, self.cols)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col]
+    }
+
+    pub fn row(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn elements(&self) -> &[f64] {
+        &self.data
+    }
+
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self {
+            data: self.data.iter().map(|&x| f(x)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Combines the elements of two matrices of the same shape.
+    pub fn zip_with(
+        &self,
+        other: &Self,
+        verb: &str,
+        f: impl Fn(f64, f64) -> f64,
+    ) -> Result<Self, String> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(format!(
+                "Cannot {verb} matrices of shapes {} and {}",
+                self.shape(),
+                other.shape()
+            ));
+        }
+        Ok(Self {
+            data: self
+                .data
+                .iter()
+                .zip(&other.data)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..self.clone()
+        })
+    }
+
+    pub fn add(&self, other: &Self) -> Result<Self, String> {
+        self.zip_with(other, "add", |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &Self) -> Result<Self, String> {
+        self.zip_with(other, "subtract", |a, b| a - b)
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        self.map(|x| x * factor)
+    }
+
+    pub fn matmul(&self, other: &Self) -> Result<Self, String> {
+        if self.cols != other.rows {
+            return Err(format!(
+                "Cannot multiply a {} matrix by a {} matrix",
+                self.shape(),
+                other.shape()
+            ));
+        }
+        let mut data = vec![0.0; self.rows * other.cols];
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self.get(i, k);
+                for j in 0..other.cols {
+                    data[i * other.cols + j] += a * other.get(k, j);
+                }
+            }
+        }
+        Ok(Self {
+            rows: self.rows,
+            cols: other.cols,
+            data,
+        })
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut data = Vec::with_capacity(self.data.len());
+        for j in 0..self.cols {
+            for i in 0..self.rows {
+                data.push(self.get(i, j));
+            }
+        }
+        Self {
+            rows: self.cols,
+            cols: self.rows,
+            data,
+        }
+    }
+
+    fn expect_square(&self, name: &str) -> Result<usize, String> {
+        if self.rows == self.cols {
+            Ok(self.rows)
+        } else {
+            Err(format!(
+                "{name}() expects a square matrix, found shape {}",
+                self.shape()
+            ))
+        }
+    }
+
+    fn lu(&self) -> Lu {
+        let n = self.rows;
+        let mut factors = self.data.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        let mut singular = false;
+        let scale = self.data.iter().fold(0.0f64, |max, x| max.max(x.abs()));
+        let tolerance = scale * n as f64 * f64::EPSILON;
+
+        for k in 0..n {
+            let pivot = (k..n)
+                .max_by(|&a, &b| {
+                    factors[a * n + k]
+                        .abs()
+                        .total_cmp(&factors[b * n + k].abs())
+                })
+                .unwrap_or(k);
+            if factors[pivot * n + k].abs() <= tolerance {
+                singular = true;
+                continue;
+            }
+            if pivot != k {
+                for j in 0..n {
+                    factors.swap(k * n + j, pivot * n + j);
+                }
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                let multiplier = factors[i * n + k] / factors[k * n + k];
+                factors[i * n + k] = multiplier;
+                for j in k + 1..n {
+                    factors[i * n + j] -= multiplier * factors[k * n + j];
+                }
+            }
+        }
+        Lu {
+            n,
+            factors,
+            permutation,
+            sign,
+            singular,
+        }
+    }
+
+    pub fn det(&self) -> Result<f64, String> {
+        self.expect_square("det")?;
+        let lu = self.lu();
+        if lu.singular {
+            return Ok(0.0);
+        }
+        Ok((0..lu.n).fold(lu.sign, |det, i| det * lu.factors[i * lu.n + i]))
+    }
+
+    /// Solves `self * x = b` for `x`, where `b` may have several columns.
+    pub fn solve(&self, b: &Self) -> Result<Self, String> {
+        let n = self.expect_square("solve")?;
+        if b.rows != n {
+            return Err(format!(
+                "Cannot solve a system with a {} matrix and a right-hand side of shape {}",
+                self.shape(),
+                b.shape()
+            ));
+        }
+        let lu = self.lu();
+        if lu.singular {
+            return Err("The matrix is singular".into());
+        }
+
+        let mut x = vec![0.0; n * b.cols];
+        for col in 0..b.cols {
+            // Forward substitution with L, then back substitution with U.
+            let mut y: Vec<f64> = lu.permutation.iter().map(|&p| b.get(p, col)).collect();
+            for i in 0..n {
+                for j in 0..i {
+                    y[i] -= lu.factors[i * n + j] * y[j];
+                }
+            }
+            for i in (0..n).rev() {
+                for j in i + 1..n {
+                    y[i] -= lu.factors[i * n + j] * y[j];
+                }
+                y[i] /= lu.factors[i * n + i];
+            }
+            for i in 0..n {
+                x[i * b.cols + col] = y[i];
+            }
+        }
+        Ok(Self {
+            rows: n,
+            cols: b.cols,
+            data: x,
+        })
+    }
+
+    pub fn inverse(&self) -> Result<Self, String> {
+        let n = self.expect_square("inverse")?;
+        self.solve(&Self::identity(n))
+    }
+
+    /// The eigenvalues, sorted by their real and then imaginary parts.
+    ///
+    /// The matrix is reduced to Hessenberg form, and then the shifted QR
+    /// algorithm finds the eigenvalues, in complex conjugate pairs for 2x2
+    /// blocks that have no real eigenvalues.
+    pub fn eigenvalues(&self) -> Result<Vec<Complex>, String> {
+        let n = self.expect_square("eigenvalues")?;
+        let mut a = self.hessenberg();
+        let mut values = hessenberg_eigenvalues(&mut a, n)?;
+        values.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        Ok(values)
+    }
+
+    /// Reduces to upper Hessenberg form by elimination with pivoting,
+    /// which keeps the eigenvalues. Rows and columns are 1-based.
+    fn hessenberg(&self) -> Vec<Vec<f64>> {
+        let n = self.rows;
+        let mut a = vec![vec![0.0; n + 1]; n + 1];
+        for i in 0..n {
+            for j in 0..n {
+                a[i + 1][j + 1] = self.get(i, j);
+            }
+        }
+
+        for m in 2..n {
+            let mut x: f64 = 0.0;
+            let mut pivot = m;
+            for (j, row) in a.iter().enumerate().skip(m) {
+                if row[m - 1].abs() > x.abs() {
+                    x = row[m - 1];
+                    pivot = j;
+                }
+            }
+            if pivot != m {
+                a.swap(pivot, m);
+                for row in a.iter_mut() {
+                    row.swap(pivot, m);
+                }
+            }
+            if x != 0.0 {
+                for i in m + 1..=n {
+                    let y = a[i][m - 1] / x;
+                    if y != 0.0 {
+                        let pivot_row = a[m].clone();
+                        for (value, pivot) in a[i].iter_mut().zip(pivot_row).skip(m) {
+                            *value -= y * pivot;
+                        }
+                        for row in a.iter_mut().skip(1) {
+                            row[m] += y * row[i];
+                        }
+                    }
+                }
+            }
+        }
+        for (i, row) in a.iter_mut().enumerate().skip(3) {
+            for value in row.iter_mut().take(i - 1).skip(1) {
+                *value = 0.0;
+            }
+        }
+        a
+    }
+}
+
+/// The QR algorithm with Francis double shifts on a 1-based upper
+/// Hessenberg matrix, which is destroyed.
+fn hessenberg_eigenvalues(a: &mut [Vec<f64>], n: usize) -> Result<Vec<Complex>, String> {
+    let sign = |a: f64, b: f64| if b >= 0.0 { a.abs() } else { -a.abs() };
+    let mut values = vec![Complex::real(0.0); n + 1];
+    let mut norm = 0.0;
+    for (i, row) in a.iter().enumerate().skip(1) {
+        norm += row
+            .iter()
+            .skip(i.saturating_sub(1).max(1))
+            .map(|x| x.abs())
+            .sum::<f64>();
+    }
+
+    let mut nn = n;
+    let mut shift = 0.0;
+    while nn >= 1 {
+        let mut iterations = 0;
+        loop {
+            // Look for a negligible subdiagonal element to split the matrix at.
+            let mut l = 1;
+            for candidate in (2..=nn).rev() {
+                let mut s = a[candidate - 1][candidate - 1].abs() + a[candidate][candidate].abs();
+                if s == 0.0 {
+                    s = norm;
+                }
+                if a[candidate][candidate - 1].abs() + s == s {
+                    a[candidate][candidate - 1] = 0.0;
+                    l = candidate;
+                    break;
+                }
+            }
+
+            let mut x = a[nn][nn];
+            if l == nn {
+                // One root found.
+                values[nn] = Complex::real(x + shift);
+                nn -= 1;
+                break;
+            }
+            let mut y = a[nn - 1][nn - 1];
+            let mut w = a[nn][nn - 1] * a[nn - 1][nn];
+            if l == nn - 1 {
+                // Two roots found, from the trailing 2x2 block.
+                let p = 0.5 * (y - x);
+                let q = p * p + w;
+                let z = q.abs().sqrt();
+                x += shift;
+                if q >= 0.0 {
+                    let z = p + sign(z, p);
+                    values[nn - 1] = Complex::real(x + z);
+                    values[nn] = Complex::real(if z != 0.0 { x - w / z } else { x + z });
+                } else {
+                    values[nn - 1] = Complex::new(x + p, -z);
+                    values[nn] = Complex::new(x + p, z);
+                }
+                nn -= 2;
+                break;
+            }
+
+            if iterations == 60 {
+                return Err("eigenvalues() did not converge".into());
+            }
+            if iterations == 10 || iterations == 20 {
+                // An exceptional shift, to break out of cycles.
+                shift += x;
+                for (i, row) in a.iter_mut().enumerate().take(nn + 1).skip(1) {
+                    row[i] -= x;
+                }
+                let s = a[nn][nn - 1].abs() + a[nn - 1][nn - 2].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+            iterations += 1;
+
+            // Look for two consecutive small subdiagonal elements.
+            let (mut p, mut q, mut r);
+            let mut m = nn - 2;
+            loop {
+                let z = a[m][m];
+                let rr = x - z;
+                let s = y - z;
+                p = (rr * s - w) / a[m + 1][m] + a[m][m + 1];
+                q = a[m + 1][m + 1] - z - rr - s;
+                r = a[m + 2][m + 1];
+                let s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                let u = a[m][m - 1].abs() * (q.abs() + r.abs());
+                let v = p.abs() * (a[m - 1][m - 1].abs() + z.abs() + a[m + 1][m + 1].abs());
+                if u + v == v {
+                    break;
+                }
+                m -= 1;
+            }
+            for i in m + 2..=nn {
+                a[i][i - 2] = 0.0;
+                if i != m + 2 {
+                    a[i][i - 3] = 0.0;
+                }
+            }
+
+            // A double QR step on rows l to nn and columns m to nn.
+            for k in m..nn {
+                if k != m {
+                    p = a[k][k - 1];
+                    q = a[k + 1][k - 1];
+                    r = if k != nn - 1 { a[k + 2][k - 1] } else { 0.0 };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x != 0.0 {
+                        p /= x;
+                        q /= x;
+                        r /= x;
+                    }
+                }
+                let s = sign((p * p + q * q + r * r).sqrt(), p);
+                if s == 0.0 {
+                    continue;
+                }
+                if k == m {
+                    if l != m {
+                        a[k][k - 1] = -a[k][k - 1];
+                    }
+                } else {
+                    a[k][k - 1] = -s * x;
+                }
+                p += s;
+                x = p / s;
+                y = q / s;
+                let z = r / s;
+                q /= p;
+                r /= p;
+                #[allow(clippy::needless_range_loop)]
+                for j in k..=nn {
+                    p = a[k][j] + q * a[k + 1][j];
+                    if k != nn - 1 {
+                        p += r * a[k + 2][j];
+                        a[k + 2][j] -= p * z;
+                    }
+                    a[k + 1][j] -= p * y;
+                    a[k][j] -= p * x;
+                }
+                for row in a.iter_mut().take(nn.min(k + 3) + 1).skip(l) {
+                    p = x * row[k] + y * row[k + 1];
+                    if k != nn - 1 {
+                        p += z * row[k + 2];
+                        row[k + 2] -= p * r;
+                    }
+                    row[k + 1] -= p * q;
+                    row[k] -= p;
+                }
+            }
+        }
+    }
+    values.remove(0);
+    Ok(values)
+}
+
+impl Display for Matrix {
+    /// Vectors are written `[|1, 2, 3|]`, and matrices a row at a time,
+    /// like `[|1, 2; 3, 4|]`. A matrix with one row ends with `;`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[|")?;
+        if self.is_vector() {
+            let elements: Vec<String> = self.data.iter().map(f64::to_string).collect();
+            write!(f, "{}", elements.join(", "))?;
+        } else {
+            let rows: Vec<String> = (0..self.rows)
+                .map(|i| {
+                    let row: Vec<String> = self.row(i).iter().map(f64::to_string).collect();
+                    row.join(", ")
+                })
+                .collect();
+            write!(f, "{}", rows.join("; "))?;
+            if self.rows == 1 {
+                write!(f, ";")?;
+            }
+        }
+        write!(f, "|]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[f64]]) -> Matrix {
+        Matrix::from_rows(rows.iter().map(|row| row.to_vec()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_lu_solve() {
+        let a = matrix(&[&[0.0, 2.0, 1.0], &[1.0, 1.0, 0.0], &[3.0, 0.0, 1.0]]);
+        let x = a.solve(&Matrix::vector(vec![5.0, 3.0, 4.0])).unwrap();
+        for (actual, expected) in x.elements().iter().zip([1.0, 2.0, 1.0]) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+        assert!((a.det().unwrap() - (-5.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_eigenvalues() {
+        let symmetric = matrix(&[&[2.0, 1.0, 0.0], &[1.0, 2.0, 1.0], &[0.0, 1.0, 2.0]]);
+        let expected = [2.0 - 2f64.sqrt(), 2.0, 2.0 + 2f64.sqrt()];
+        let values = symmetric.eigenvalues().unwrap();
+        for (actual, expected) in values.iter().zip(expected) {
+            assert!((actual.re - expected).abs() < 1e-10 && actual.im == 0.0);
+        }
+
+        let rotation = matrix(&[&[0.0, -1.0], &[1.0, 0.0]]);
+        assert_eq!(
+            rotation.eigenvalues().unwrap(),
+            vec![Complex::new(0.0, -1.0), Complex::new(0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Matrix::vector(vec![1.0, 2.5]).to_string(), "[|1, 2.5|]");
+        assert_eq!(Matrix::identity(2).to_string(), "[|1, 0; 0, 1|]");
+        assert_eq!(matrix(&[&[1.0, 2.0]]).to_string(), "[|1, 2;|]");
+    }
+}
@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+use crate::interpreter::Interpreter;
+use crate::rational::Rational;
+use crate::runtime::RuntimeValue;
+
+/// A hashable copy of an argument value.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum MemoKey {
+    Number(u64),
+    Integer(BigInt),
+    Rational(Rational),
+    Complex(u64, u64),
+    String(String),
+    Boolean(bool),
+    List(Vec<MemoKey>),
+    Nil,
+}
+
+impl MemoKey {
+    fn from_value(value: &RuntimeValue) -> Result<Self, String> {
+        match value {
+            RuntimeValue::Number(n) => Ok(Self::Number(Self::float_bits(*n)?)),
+            RuntimeValue::Integer(n) => Ok(Self::Integer(n.clone())),
+            RuntimeValue::Rational(r) => Ok(Self::Rational(r.clone())),
+            RuntimeValue::Complex(c) => Ok(Self::Complex(
+                Self::float_bits(c.re)?,
+                Self::float_bits(c.im)?,
+            )),
+            RuntimeValue::String(s) => Ok(Self::String(s.clone())),
+            RuntimeValue::Boolean(b) => Ok(Self::Boolean(*b)),
+            RuntimeValue::List { elements } => Ok(Self::List(
+                elements
+                    .iter()
+                    .map(Self::from_value)
+                    .collect::<Result<_, _>>()?,
+            )),
+            RuntimeValue::Nil => Ok(Self::Nil),
+            other => Err(format!(
+                "Cannot memoize a call with argument {other}, \
+                 only numbers, strings, booleans, nil and lists of those can be cached"
+            )),
+        }
+    }
+
+    fn float_bits(n: f64) -> Result<u64, String> {
+        if n.is_nan() {
+            Err("Cannot memoize a call with a NaN argument".into())
+        } else if n == 0.0 {
+            // -0.0 and 0.0 compare equal, so they share a key.
+            Ok(0.0f64.to_bits())
+        } else {
+            Ok(n.to_bits())
+        }
+    }
+}
+
+struct Entry {
+    value: RuntimeValue,
+    last_used: u64,
+}
+
+struct Cache {
+    entries: HashMap<MemoKey, Entry>,
+    capacity: Option<usize>,
+    clock: u64,
+}
+
+impl Cache {
+    fn get(&mut self, key: &MemoKey) -> Option<RuntimeValue> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, key: MemoKey, value: RuntimeValue) {
+        if let Some(capacity) = self.capacity
+            && self.entries.len() >= capacity
+            && !self.entries.contains_key(&key)
+        {
+            self.evict_least_recently_used();
+        }
+
+        self.clock += 1;
+        let last_used = self.clock;
+        self.entries.insert(key, Entry { value, last_used });
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+struct MemoState {
+    function: RuntimeValue,
+    cache: RefCell<Cache>,
+}
+
+/// A function whose results are cached by argument value.
+#[derive(Clone)]
+pub struct Memo(Rc<MemoState>);
+
+impl std::fmt::Debug for Memo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Memo({:?})", self.0.function)
+    }
+}
+
+impl Memo {
+    /// Wraps `function`. A `capacity` bounds the cache, evicting the least
+    /// recently used result when it is full.
+    pub fn new(function: RuntimeValue, capacity: Option<usize>) -> Self {
+        Self(Rc::new(MemoState {
+            function,
+            cache: RefCell::new(Cache {
+                entries: HashMap::new(),
+                capacity,
+                clock: 0,
+            }),
+        }))
+    }
+
+    pub fn function(&self) -> &RuntimeValue {
+        &self.0.function
+    }
+
+    pub fn call(&self, arg: RuntimeValue) -> Result<RuntimeValue, String> {
+        // Derivatives taken by `grad` depend on more than the argument's value.
+        if matches!(arg, RuntimeValue::Dual(_)) {
+            return Interpreter::call(&self.0.function, arg);
+        }
+        let key = MemoKey::from_value(&arg)?;
+        if let Some(value) = self.0.cache.borrow_mut().get(&key) {
+            return Ok(value);
+        }
+
+        // The cache must not be borrowed here, as recursive calls use it too.
+        let value = Interpreter::call(&self.0.function, arg)?;
+        self.0.cache.borrow_mut().insert(key, value.clone());
+        Ok(value)
+    }
+
+    pub fn clear(&self) {
+        self.0.cache.borrow_mut().entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.cache.borrow().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> RuntimeValue {
+        RuntimeValue::NativeFunction {
+            name: "identity".into(),
+            function: Ok,
+            reads_formula: false,
+        }
+    }
+
+    #[test]
+    fn test_signed_zero_shares_key() {
+        let memo = Memo::new(identity(), None);
+        memo.call(RuntimeValue::Number(0.0)).unwrap();
+        memo.call(RuntimeValue::Number(-0.0)).unwrap();
+        assert_eq!(memo.len(), 1);
+    }
+
+    #[test]
+    fn test_bounded_evicts_least_recently_used() {
+        let memo = Memo::new(identity(), Some(2));
+        memo.call(RuntimeValue::Number(1.0)).unwrap();
+        memo.call(RuntimeValue::Number(2.0)).unwrap();
+        memo.call(RuntimeValue::Number(1.0)).unwrap(); // 2 is now the oldest
+        memo.call(RuntimeValue::Number(3.0)).unwrap();
+
+        let cache = memo.0.cache.borrow();
+        assert_eq!(cache.entries.len(), 2);
+        assert!(
+            cache
+                .entries
+                .contains_key(&MemoKey::Number(1.0f64.to_bits()))
+        );
+        assert!(
+            !cache
+                .entries
+                .contains_key(&MemoKey::Number(2.0f64.to_bits()))
+        );
+    }
+
+    #[test]
+    fn test_unhashable_argument() {
+        let memo = Memo::new(identity(), None);
+        assert!(memo.call(identity()).is_err());
+        assert!(memo.is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::bigint::BigInt;
+
+/// Bases for which Miller–Rabin is exact for every 64-bit number.
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Primes below this are found by trial division before Pollard's rho.
+const TRIAL_DIVISION_LIMIT: u64 = 1000;
+
+/// The least common multiple, which is never negative.
+pub fn lcm(a: &BigInt, b: &BigInt) -> BigInt {
+    if a.is_zero() || b.is_zero() {
+        return BigInt::zero();
+    }
+    let (quotient, _) = a.div_rem(&a.gcd(b)).expect("the gcd is not zero");
+    (&quotient * b).abs()
+}
+
+pub fn factorial(n: u64) -> BigInt {
+    (2..=n).fold(BigInt::one(), |product, k| {
+        &product * &BigInt::from(k as i64)
+    })
+}
+
+/// The number of ways to choose `k` of `n` things.
+pub fn binomial(n: u64, k: u64) -> BigInt {
+    if k > n {
+        return BigInt::zero();
+    }
+    // Each partial product is itself a binomial coefficient, so each division is exact.
+    let k = k.min(n - k);
+    (0..k).fold(BigInt::one(), |product, i| {
+        let numerator = &product * &BigInt::from((n - i) as i64);
+        numerator
+            .div_rem(&BigInt::from(i as i64 + 1))
+            .expect("i + 1 is not zero")
+            .0
+    })
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Whether `n` is prime, by Miller–Rabin with witnesses that leave no
+/// composite 64-bit number undetected.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let (mut d, mut s) = (n - 1, 0);
+    while d % 2 == 0 {
+        d /= 2;
+        s += 1;
+    }
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// The primes up to and including `limit`, by the sieve of Eratosthenes.
+pub fn primes(limit: usize) -> Vec<u64> {
+    if limit < 2 {
+        return vec![];
+    }
+    let mut composite = vec![false; limit + 1];
+    let mut i = 2;
+    while i * i <= limit {
+        if !composite[i] {
+            for multiple in (i * i..=limit).step_by(i) {
+                composite[multiple] = true;
+            }
+        }
+        i += 1;
+    }
+    (2..=limit)
+        .filter(|&n| !composite[n])
+        .map(|n| n as u64)
+        .collect()
+}
+
+/// The prime factors of a positive number in increasing order,
+/// each repeated as often as it divides the number.
+pub fn factorize(mut n: u64) -> Vec<u64> {
+    let mut factors = vec![];
+    for p in primes(TRIAL_DIVISION_LIMIT as usize) {
+        while n.is_multiple_of(p) {
+            factors.push(p);
+            n /= p;
+        }
+    }
+    // What is left has only large factors, which Pollard's rho splits apart.
+    let mut pending = vec![n];
+    while let Some(m) = pending.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            factors.push(m);
+            continue;
+        }
+        let divisor = pollard_rho(m);
+        pending.push(divisor);
+        pending.push(m / divisor);
+    }
+    factors.sort_unstable();
+    factors
+}
+
+/// A nontrivial divisor of a composite number without small factors,
+/// by Pollard's rho with Brent's cycle detection.
+fn pollard_rho(n: u64) -> u64 {
+    let gcd = |mut a: u64, mut b: u64| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    // Each constant gives a different pseudorandom sequence, so one that
+    // fails to split `n` is followed by the next.
+    for c in 1.. {
+        let step = |x: u64| (mul_mod(x, x, n) + c) % n;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        let mut power = 1;
+        let mut length = 0;
+        while d == 1 {
+            if length == power {
+                x = y;
+                power *= 2;
+                length = 0;
+            }
+            y = step(y);
+            length += 1;
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!("some constant splits every composite number")
+}
+
+/// `base ^ exponent mod modulus`, in `[0, modulus)`, by repeated squaring.
+pub fn mod_pow(base: &BigInt, exponent: &BigInt, modulus: &BigInt) -> BigInt {
+    let reduce = |x: &BigInt| x.div_mod_floor(modulus).expect("the modulus is not zero").1;
+    let two = BigInt::from(2);
+    let mut result = reduce(&BigInt::one());
+    let mut base = reduce(base);
+    let mut exponent = exponent.clone();
+    while !exponent.is_zero() {
+        if !exponent.is_even() {
+            result = reduce(&(&result * &base));
+        }
+        base = reduce(&(&base * &base));
+        exponent = exponent.div_rem(&two).expect("2 is not zero").0;
+    }
+    result
+}
+
+/// The `x` in `[0, modulus)` with `a * x = 1 mod modulus`, by the extended
+/// Euclidean algorithm, or `None` if `a` and `modulus` share a factor.
+pub fn mod_inverse(a: &BigInt, modulus: &BigInt) -> Option<BigInt> {
+    let (mut r0, mut r1) = (modulus.abs(), a.div_mod_floor(&modulus.abs())?.1);
+    let (mut t0, mut t1) = (BigInt::zero(), BigInt::one());
+    while !r1.is_zero() {
+        let (quotient, remainder) = r0.div_rem(&r1)?;
+        (r0, r1) = (r1, remainder);
+        let t = &t0 - &(&quotient * &t1);
+        (t0, t1) = (t1, t);
+    }
+    if !r0.is_one() {
+        return None;
+    }
+    Some(t0.div_mod_floor(&modulus.abs())?.1)
+}
+
+/// Every ordering of the items, in the order of their positions.
+pub fn permutations<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    let mut result = vec![];
+    for (i, first) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(i);
+        for mut tail in permutations(&rest) {
+            tail.insert(0, first.clone());
+            result.push(tail);
+        }
+    }
+    result
+}
+
+/// Every choice of `k` of the items, keeping their order.
+pub fn combinations<T: Clone>(items: &[T], k: usize) -> Vec<Vec<T>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    if k > items.len() {
+        return vec![];
+    }
+    let mut result = vec![];
+    for (i, first) in items.iter().enumerate() {
+        for mut tail in combinations(&items[i + 1..], k - 1) {
+            tail.insert(0, first.clone());
+            result.push(tail);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: i64) -> BigInt {
+        BigInt::from(n)
+    }
+
+    #[test]
+    fn test_counting() {
+        assert_eq!(factorial(0), big(1));
+        assert_eq!(factorial(20), big(2_432_902_008_176_640_000));
+        assert_eq!(factorial(25).to_string(), "15511210043330985984000000");
+        assert_eq!(binomial(5, 2), big(10));
+        assert_eq!(binomial(5, 7), big(0));
+        assert_eq!(
+            binomial(100, 50).to_string(),
+            "100891344545564193334812497256"
+        );
+        assert_eq!(lcm(&big(4), &big(-6)), big(12));
+        assert_eq!(lcm(&big(0), &big(5)), big(0));
+    }
+
+    #[test]
+    fn test_primes() {
+        assert_eq!(primes(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(primes(1).is_empty());
+        assert!(is_prime(2) && is_prime(1_000_000_007) && is_prime(18_446_744_073_709_551_557));
+        // A strong pseudoprime to several small bases.
+        assert!(!is_prime(3_215_031_751));
+        assert!(!is_prime(1) && !is_prime(0) && !is_prime(561));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), Vec::<u64>::new());
+        assert_eq!(factorize(360), vec![2, 2, 2, 3, 3, 5]);
+        assert_eq!(factorize(1_000_000_007), vec![1_000_000_007]);
+        // Two large primes, which trial division would take far too long to split.
+        assert_eq!(
+            factorize(1_000_000_007 * 998_244_353),
+            vec![998_244_353, 1_000_000_007]
+        );
+        assert_eq!(
+            factorize(u64::MAX),
+            vec![3, 5, 17, 257, 641, 65537, 6_700_417]
+        );
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        assert_eq!(mod_pow(&big(4), &big(13), &big(497)), big(445));
+        assert_eq!(mod_pow(&big(-2), &big(3), &big(5)), big(2));
+        assert_eq!(mod_pow(&big(5), &big(0), &big(1)), big(0));
+        assert_eq!(mod_inverse(&big(3), &big(11)), Some(big(4)));
+        assert_eq!(mod_inverse(&big(-3), &big(11)), Some(big(7)));
+        assert_eq!(mod_inverse(&big(4), &big(6)), None);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        assert_eq!(
+            permutations(&[1, 2, 3]),
+            vec![
+                vec![1, 2, 3],
+                vec![1, 3, 2],
+                vec![2, 1, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1]
+            ]
+        );
+        assert_eq!(
+            combinations(&[1, 2, 3, 4], 2),
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![1, 4],
+                vec![2, 3],
+                vec![2, 4],
+                vec![3, 4]
+            ]
+        );
+        assert_eq!(combinations(&[1, 2], 3), Vec::<Vec<i32>>::new());
+        assert_eq!(permutations::<i32>(&[]), vec![vec![]]);
+    }
+}
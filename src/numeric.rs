@@ -0,0 +1,400 @@
+/// The tolerance used when a program does not give one.
+pub const DEFAULT_TOLERANCE: f64 = 1e-10;
+
+/// Most iterations before an iterative method gives up.
+const MAX_ITERATIONS: usize = 500;
+
+/// Deepest bisection of an interval in adaptive quadrature.
+const MAX_DEPTH: usize = 50;
+
+/// Integrates `f` from `a` to `b` with adaptive Simpson's rule, splitting
+/// each interval until the estimated error is below `tolerance`.
+pub fn integrate(
+    name: &str,
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    a: f64,
+    b: f64,
+    tolerance: f64,
+) -> Result<f64, String> {
+    if !a.is_finite() || !b.is_finite() {
+        return Err(format!("{name}() expects finite bounds, found {a} and {b}"));
+    }
+    if a == b {
+        return Ok(0.0);
+    }
+    let (fa, fm, fb) = (f(a)?, f((a + b) / 2.0)?, f(b)?);
+    let whole = simpson(a, b, fa, fm, fb);
+    let result = adaptive_simpson(f, (a, fa), (b, fb), fm, whole, tolerance, MAX_DEPTH)?;
+    result.ok_or_else(|| {
+        format!("{name}() did not converge to within {tolerance}, the integral may be singular")
+    })
+}
+
+fn simpson(a: f64, b: f64, fa: f64, fm: f64, fb: f64) -> f64 {
+    (b - a) / 6.0 * (fa + 4.0 * fm + fb)
+}
+
+/// Returns `None` if an interval could not be refined enough within the depth limit.
+fn adaptive_simpson(
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    (a, fa): (f64, f64),
+    (b, fb): (f64, f64),
+    fm: f64,
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+) -> Result<Option<f64>, String> {
+    let m = (a + b) / 2.0;
+    let (fl, fr) = (f((a + m) / 2.0)?, f((m + b) / 2.0)?);
+    let left = simpson(a, m, fa, fl, fm);
+    let right = simpson(m, b, fm, fr, fb);
+    let error = left + right - whole;
+    if !error.is_finite() {
+        return Ok(None);
+    }
+    if error.abs() <= 15.0 * tolerance {
+        // Richardson extrapolation of the two estimates.
+        return Ok(Some(left + right + error / 15.0));
+    }
+    if depth == 0 {
+        return Ok(None);
+    }
+    let Some(left) = adaptive_simpson(f, (a, fa), (m, fm), fl, left, tolerance / 2.0, depth - 1)?
+    else {
+        return Ok(None);
+    };
+    let Some(right) = adaptive_simpson(f, (m, fm), (b, fb), fr, right, tolerance / 2.0, depth - 1)?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(left + right))
+}
+
+/// Finds a root of `f` between `a` and `b` with Brent's method, which
+/// combines bisection with secant steps and inverse quadratic interpolation.
+pub fn root(
+    name: &str,
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    a: f64,
+    b: f64,
+    tolerance: f64,
+) -> Result<f64, String> {
+    let (mut a, mut b) = (a, b);
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+    if fa.signum() == fb.signum() || fa.is_nan() || fb.is_nan() {
+        return Err(format!(
+            "{name}() expects f({a}) and f({b}) to have opposite signs, found {fa} and {fb}"
+        ));
+    }
+
+    // `b` is the best estimate, `c` the previous one, and the root is between `b` and `c`.
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut e = d;
+    for _ in 0..MAX_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            (c, fc) = (a, fa);
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let limit = 2.0 * f64::EPSILON * b.abs() + tolerance / 2.0;
+        let half = (c - b) / 2.0;
+        if half.abs() <= limit || fb == 0.0 {
+            return Ok(b);
+        }
+
+        if e.abs() >= limit && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (2.0 * half * s, 1.0 - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * half * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            // Interpolate only if the step stays well inside the bracket.
+            if 2.0 * p < (3.0 * half * q - (limit * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = half;
+                e = d;
+            }
+        } else {
+            d = half;
+            e = d;
+        }
+
+        (a, fa) = (b, fb);
+        b += if d.abs() > limit {
+            d
+        } else {
+            limit.copysign(half)
+        };
+        fb = f(b)?;
+    }
+    Err(format!(
+        "{name}() did not converge after {MAX_ITERATIONS} iterations"
+    ))
+}
+
+/// Finds a root of `f` near `x0` with Newton's method, estimating the
+/// derivative with central differences.
+pub fn newton(
+    name: &str,
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    x0: f64,
+    tolerance: f64,
+) -> Result<f64, String> {
+    let mut x = x0;
+    for _ in 0..MAX_ITERATIONS {
+        let fx = f(x)?;
+        if fx == 0.0 {
+            return Ok(x);
+        }
+        let h = f64::EPSILON.cbrt() * x.abs().max(1.0);
+        let slope = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        if slope == 0.0 || !slope.is_finite() {
+            return Err(format!(
+                "{name}() found a zero derivative at {x}, try another starting point"
+            ));
+        }
+        let step = fx / slope;
+        x -= step;
+        if !x.is_finite() {
+            return Err(format!("{name}() diverged from {x0}"));
+        }
+        if step.abs() <= tolerance * x.abs().max(1.0) {
+            return Ok(x);
+        }
+    }
+    Err(format!(
+        "{name}() did not converge after {MAX_ITERATIONS} iterations from {x0}"
+    ))
+}
+
+/// Finds a local minimum of `f` near `x0`, first walking downhill with
+/// growing steps to bracket it, then narrowing with golden-section search.
+pub fn minimize(
+    name: &str,
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    x0: f64,
+    tolerance: f64,
+) -> Result<f64, String> {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut step = 0.1 * x0.abs().max(1.0);
+    let (mut a, mut b) = (x0, x0 + step);
+    let (fa, mut fb) = (f(a)?, f(b)?);
+    if fb > fa {
+        // Walk the other way.
+        (a, b) = (b, a);
+        fb = fa;
+        step = -step;
+    }
+    let mut bracket = None;
+    for _ in 0..MAX_ITERATIONS {
+        step *= 2.0;
+        let c = b + step;
+        if !c.is_finite() {
+            break;
+        }
+        let fc = f(c)?;
+        if fc >= fb {
+            bracket = Some((a, c));
+            break;
+        }
+        (a, b, fb) = (b, c, fc);
+    }
+    let Some((mut low, mut high)) = bracket else {
+        return Err(format!(
+            "{name}() found no minimum near {x0}, the function may be unbounded below"
+        ));
+    };
+    if low > high {
+        (low, high) = (high, low);
+    }
+
+    let mut x1 = high - ratio * (high - low);
+    let mut x2 = low + ratio * (high - low);
+    let (mut f1, mut f2) = (f(x1)?, f(x2)?);
+    for _ in 0..MAX_ITERATIONS {
+        if (high - low).abs() <= tolerance * (x1.abs() + x2.abs()).max(1.0) {
+            return Ok((low + high) / 2.0);
+        }
+        if f1 < f2 {
+            high = x2;
+            (x2, f2) = (x1, f1);
+            x1 = high - ratio * (high - low);
+            f1 = f(x1)?;
+        } else {
+            low = x1;
+            (x1, f1) = (x2, f2);
+            x2 = low + ratio * (high - low);
+            f2 = f(x2)?;
+        }
+    }
+    Err(format!(
+        "{name}() did not converge after {MAX_ITERATIONS} iterations"
+    ))
+}
+
+/// Finds a local minimum of `f` over several variables near `x0` with the
+/// Nelder–Mead simplex method, which needs no derivatives.
+pub fn minimize_simplex(
+    name: &str,
+    f: &mut impl FnMut(&[f64]) -> Result<f64, String>,
+    x0: &[f64],
+    tolerance: f64,
+) -> Result<Vec<f64>, String> {
+    let n = x0.len();
+    if n == 0 {
+        return Err(format!(
+            "{name}() expects a starting point with coordinates"
+        ));
+    }
+
+    // Start from x0 and a step along each axis.
+    let mut simplex = vec![x0.to_vec()];
+    for i in 0..n {
+        let mut vertex = x0.to_vec();
+        vertex[i] += if vertex[i] == 0.0 {
+            0.00025
+        } else {
+            0.05 * vertex[i]
+        };
+        simplex.push(vertex);
+    }
+    let mut values = simplex
+        .iter()
+        .map(|vertex| f(vertex))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let point = |from: &[f64], to: &[f64], t: f64| -> Vec<f64> {
+        from.iter().zip(to).map(|(a, b)| a + t * (b - a)).collect()
+    };
+    for _ in 0..MAX_ITERATIONS * n {
+        let mut order: Vec<usize> = (0..=n).collect();
+        order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+        simplex = order.iter().map(|&i| simplex[i].clone()).collect();
+        values = order.iter().map(|&i| values[i]).collect();
+
+        let spread = (values[n] - values[0]).abs();
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|vertex| vertex.iter().zip(&simplex[0]).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        let scale = simplex[0].iter().fold(1.0, |max: f64, x| max.max(x.abs()));
+        if spread <= tolerance * values[0].abs().max(1.0) && size <= tolerance.sqrt() * scale {
+            return Ok(simplex[0].clone());
+        }
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|i| simplex[..n].iter().map(|vertex| vertex[i]).sum::<f64>() / n as f64)
+            .collect();
+        let reflected = point(&simplex[n], &centroid, 2.0);
+        let reflected_value = f(&reflected)?;
+        if reflected_value < values[0] {
+            let expanded = point(&simplex[n], &centroid, 3.0);
+            let expanded_value = f(&expanded)?;
+            if expanded_value < reflected_value {
+                (simplex[n], values[n]) = (expanded, expanded_value);
+            } else {
+                (simplex[n], values[n]) = (reflected, reflected_value);
+            }
+        } else if reflected_value < values[n - 1] {
+            (simplex[n], values[n]) = (reflected, reflected_value);
+        } else {
+            let contracted = point(&simplex[n], &centroid, 0.5);
+            let contracted_value = f(&contracted)?;
+            if contracted_value < values[n] {
+                (simplex[n], values[n]) = (contracted, contracted_value);
+            } else {
+                // Shrink every vertex towards the best one.
+                for i in 1..=n {
+                    simplex[i] = point(&simplex[0], &simplex[i], 0.5);
+                    values[i] = f(&simplex[i])?;
+                }
+            }
+        }
+    }
+    Err(format!(
+        "{name}() did not converge after {} iterations",
+        MAX_ITERATIONS * n
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-8,
+            "expected {expected}, found {actual}"
+        );
+    }
+
+    #[test]
+    fn test_integrate() {
+        let result = integrate(
+            "integrate",
+            &mut |x| Ok(x.sin()),
+            0.0,
+            std::f64::consts::PI,
+            1e-10,
+        );
+        assert_close(result.unwrap(), 2.0);
+        let result = integrate("integrate", &mut |x| Ok(x.sqrt()), 1.0, 0.0, 1e-10);
+        assert_close(result.unwrap(), -2.0 / 3.0);
+        let result = integrate("integrate", &mut |x| Ok(1.0 / x), -1.0, 1.0, 1e-10);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_root() {
+        let result = root("root", &mut |x| Ok(x * x - 2.0), 0.0, 2.0, 1e-12);
+        assert_close(result.unwrap(), 2f64.sqrt());
+        let result = root("root", &mut |x| Ok(x.cos() - x), 0.0, 1.0, 1e-12);
+        assert_close(result.unwrap(), 0.7390851332151607);
+        assert!(root("root", &mut |x| Ok(x * x + 1.0), -1.0, 1.0, 1e-12).is_err());
+    }
+
+    #[test]
+    fn test_newton() {
+        let result = newton("newton", &mut |x| Ok(x * x * x - 8.0), 1.0, 1e-12);
+        assert_close(result.unwrap(), 2.0);
+        assert!(newton("newton", &mut |_| Ok(1.0), 0.0, 1e-12).is_err());
+    }
+
+    #[test]
+    fn test_minimize() {
+        // Values near a minimum are flat, so its position is only accurate to about sqrt(eps).
+        let result = minimize("minimize", &mut |x| Ok((x - 3.0).powi(2) + 1.0), 0.0, 1e-10);
+        assert!((result.unwrap() - 3.0).abs() < 1e-6);
+        assert!(minimize("minimize", &mut |x| Ok(-x), 0.0, 1e-10).is_err());
+
+        let rosenbrock =
+            &mut |x: &[f64]| Ok((1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2));
+        let result = minimize_simplex("minimize", rosenbrock, &[-1.2, 1.0], 1e-12).unwrap();
+        assert!((result[0] - 1.0).abs() < 1e-4 && (result[1] - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,328 @@
+/// A state of the system, with one number per variable.
+pub type State = Vec<f64>;
+
+/// A condition on `(t, y)` that stops a solution when it becomes true.
+pub type Event<'a> = &'a mut dyn FnMut(f64, &[f64]) -> Result<bool, String>;
+
+/// Most steps before a solver gives up, so stiff systems fail instead of hanging.
+const MAX_STEPS: usize = 100_000;
+
+/// Most bisections when locating the time of an event.
+const MAX_BISECTIONS: usize = 60;
+
+/// The Butcher tableau of the Dormand–Prince method.
+const C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+/// Weights of the fifth-order solution, which is the last row of `A`.
+const B5: [f64; 7] = [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+    0.0,
+];
+/// Weights of the embedded fourth-order solution, to estimate the error.
+const B4: [f64; 7] = [
+    5179.0 / 57600.0,
+    0.0,
+    7571.0 / 16695.0,
+    393.0 / 640.0,
+    -92097.0 / 339200.0,
+    187.0 / 2100.0,
+    1.0 / 40.0,
+];
+
+/// How a solver chooses its steps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// The classic Runge–Kutta method with a fixed step size.
+    Rk4 { step: f64 },
+    /// Dormand–Prince, adapting the step size to keep the estimated
+    /// error of each step within the tolerance.
+    Rk45 { tolerance: f64 },
+}
+
+/// `y + h * sum(weights[i] * k[i])`
+fn combine(y: &[f64], h: f64, weights: &[f64], k: &[State]) -> State {
+    (0..y.len())
+        .map(|i| {
+            y[i] + h * weights
+                .iter()
+                .zip(k)
+                .map(|(weight, k)| weight * k[i])
+                .sum::<f64>()
+        })
+        .collect()
+}
+
+fn rk4_step(
+    f: &mut impl FnMut(f64, &[f64]) -> Result<State, String>,
+    t: f64,
+    y: &[f64],
+    h: f64,
+) -> Result<State, String> {
+    let k1 = f(t, y)?;
+    let k2 = f(
+        t + h / 2.0,
+        &combine(y, h, &[0.5], std::slice::from_ref(&k1)),
+    )?;
+    let k3 = f(
+        t + h / 2.0,
+        &combine(y, h, &[0.5], std::slice::from_ref(&k2)),
+    )?;
+    let k4 = f(t + h, &combine(y, h, &[1.0], std::slice::from_ref(&k3)))?;
+    Ok((0..y.len())
+        .map(|i| y[i] + h * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]) / 6.0)
+        .collect())
+}
+
+/// Takes one Dormand–Prince step, returning the fifth-order solution
+/// and the error relative to the tolerance, which is acceptable below 1.
+fn rk45_step(
+    f: &mut impl FnMut(f64, &[f64]) -> Result<State, String>,
+    t: f64,
+    y: &[f64],
+    h: f64,
+    tolerance: f64,
+) -> Result<(State, f64), String> {
+    let mut k: Vec<State> = Vec::with_capacity(7);
+    for stage in 0..7 {
+        let y_stage = combine(y, h, &A[stage][..stage], &k);
+        k.push(f(t + C[stage] * h, &y_stage)?);
+    }
+    let fifth = combine(y, h, &B5, &k);
+    let fourth = combine(y, h, &B4, &k);
+    let error = fifth
+        .iter()
+        .zip(&fourth)
+        .zip(y)
+        .map(|((a, b), y)| {
+            let scale = tolerance * (1.0 + y.abs().max(a.abs()));
+            ((a - b) / scale).powi(2)
+        })
+        .sum::<f64>()
+        / y.len().max(1) as f64;
+    Ok((fifth, error.sqrt()))
+}
+
+/// Solves `y' = f(t, y)` from `(t0, y0)` to `t1`, returning each step as `(t, y)`.
+///
+/// If `event` is given, the solution stops at the first time it is true,
+/// which is located within the step by bisection.
+pub fn solve(
+    name: &str,
+    f: &mut impl FnMut(f64, &[f64]) -> Result<State, String>,
+    t0: f64,
+    y0: State,
+    t1: f64,
+    method: Method,
+    mut event: Option<Event>,
+) -> Result<Vec<(f64, State)>, String> {
+    if !t0.is_finite() || !t1.is_finite() {
+        return Err(format!(
+            "{name}() expects finite times, found {t0} and {t1}"
+        ));
+    }
+    let mut trajectory = vec![(t0, y0.clone())];
+    if let Some(event) = event.as_mut()
+        && event(t0, &y0)?
+    {
+        return Ok(trajectory);
+    }
+
+    let direction = if t1 >= t0 { 1.0 } else { -1.0 };
+    let span = (t1 - t0).abs();
+    let (mut t, mut y) = (t0, y0);
+    let mut h = match method {
+        Method::Rk4 { step } => step.abs().min(span),
+        Method::Rk45 { .. } => span / 100.0,
+    };
+    let mut steps = 0;
+    while (t1 - t) * direction > 0.0 {
+        steps += 1;
+        if steps > MAX_STEPS {
+            return Err(format!(
+                "{name}() took more than {MAX_STEPS} steps, stopping at t = {t}"
+            ));
+        }
+        // Do not step past the end, but avoid a tiny last step.
+        let remaining = (t1 - t).abs();
+        let last = h * 1.01 >= remaining;
+        let step = if last { remaining } else { h };
+
+        let next = match method {
+            Method::Rk4 { .. } => rk4_step(f, t, &y, step * direction)?,
+            Method::Rk45 { tolerance } => {
+                let (next, error) = rk45_step(f, t, &y, step * direction, tolerance)?;
+                // Grow or shrink the step, within limits, to aim for an error of about 0.9.
+                let factor = if error == 0.0 {
+                    5.0
+                } else {
+                    (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+                };
+                if !error.is_finite() || error > 1.0 {
+                    h = step * factor.min(0.5);
+                    if h <= f64::EPSILON * t.abs().max(1.0) * 16.0 {
+                        return Err(format!(
+                            "{name}() could not reach the tolerance {tolerance}, \
+                             the step size became too small at t = {t}"
+                        ));
+                    }
+                    continue;
+                }
+                h = step * factor;
+                next
+            }
+        };
+        let t_next = if last { t1 } else { t + step * direction };
+        if next.iter().any(|value| !value.is_finite()) {
+            return Err(format!("{name}() diverged at t = {t_next}"));
+        }
+
+        if let Some(event) = event.as_mut()
+            && event(t_next, &next)?
+        {
+            let (t_event, y_event) = locate_event(f, &mut **event, t, &y, step, direction, method)?;
+            trajectory.push((t_event, y_event));
+            return Ok(trajectory);
+        }
+        (t, y) = (t_next, next);
+        trajectory.push((t, y.clone()));
+    }
+    Ok(trajectory)
+}
+
+/// Finds the first time within a step of size `step` from `(t, y)` at which
+/// the event is true, knowing it is false at `t` and true at the end.
+fn locate_event(
+    f: &mut impl FnMut(f64, &[f64]) -> Result<State, String>,
+    event: Event,
+    t: f64,
+    y: &[f64],
+    step: f64,
+    direction: f64,
+    method: Method,
+) -> Result<(f64, State), String> {
+    let mut partial_step = |h: f64| match method {
+        Method::Rk4 { .. } => rk4_step(f, t, y, h * direction),
+        Method::Rk45 { tolerance } => {
+            rk45_step(f, t, y, h * direction, tolerance).map(|(next, _)| next)
+        }
+    };
+    let (mut low, mut high) = (0.0, step);
+    let mut found = partial_step(high)?;
+    for _ in 0..MAX_BISECTIONS {
+        let middle = (low + high) / 2.0;
+        if middle <= low || middle >= high {
+            break;
+        }
+        let state = partial_step(middle)?;
+        if event(t + middle * direction, &state)? {
+            (high, found) = (middle, state);
+        } else {
+            low = middle;
+        }
+    }
+    Ok((t + high * direction, found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decay(_: f64, y: &[f64]) -> Result<State, String> {
+        Ok(vec![-y[0]])
+    }
+
+    #[test]
+    fn test_rk4() {
+        let method = Method::Rk4 { step: 0.01 };
+        let trajectory = solve("solve_ode", &mut decay, 0.0, vec![1.0], 1.0, method, None).unwrap();
+        assert_eq!(trajectory.len(), 101);
+        let (t, y) = trajectory.last().unwrap();
+        assert_eq!(*t, 1.0);
+        assert!((y[0] - (-1f64).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rk45_harmonic_oscillator() {
+        let mut oscillator = |_: f64, y: &[f64]| Ok(vec![y[1], -y[0]]);
+        let method = Method::Rk45 { tolerance: 1e-10 };
+        let end = 2.0 * std::f64::consts::PI;
+        let trajectory = solve(
+            "solve_ode",
+            &mut oscillator,
+            0.0,
+            vec![1.0, 0.0],
+            end,
+            method,
+            None,
+        )
+        .unwrap();
+        let (t, y) = trajectory.last().unwrap();
+        assert_eq!(*t, end);
+        assert!((y[0] - 1.0).abs() < 1e-7 && y[1].abs() < 1e-7);
+        // Far fewer steps than a fixed step size would need for this accuracy.
+        assert!(trajectory.len() < 200);
+    }
+
+    #[test]
+    fn test_backwards_in_time() {
+        let method = Method::Rk45 { tolerance: 1e-10 };
+        let trajectory = solve("solve_ode", &mut decay, 1.0, vec![1.0], 0.0, method, None).unwrap();
+        let (_, y) = trajectory.last().unwrap();
+        assert!((y[0] - 1f64.exp()).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_event() {
+        // A ball dropped from 10 m hits the ground at sqrt(2 * 10 / 9.81) s.
+        let mut falling = |_: f64, y: &[f64]| Ok(vec![y[1], -9.81]);
+        let mut hits_ground = |_: f64, y: &[f64]| Ok(y[0] <= 0.0);
+        let method = Method::Rk45 { tolerance: 1e-10 };
+        let trajectory = solve(
+            "solve_ode",
+            &mut falling,
+            0.0,
+            vec![10.0, 0.0],
+            10.0,
+            method,
+            Some(&mut hits_ground),
+        )
+        .unwrap();
+        let (t, y) = trajectory.last().unwrap();
+        assert!((t - (20.0f64 / 9.81).sqrt()).abs() < 1e-9);
+        assert!(y[0].abs() < 1e-8);
+    }
+}
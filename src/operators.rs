@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use crate::token::TokenType;
+
+/// Characters that user-defined operator symbols are made of.
+const OPERATOR_CHARS: &str = "!#$%&*+./<=>?@\\^|-~:";
+
+/// Symbols with a built-in meaning, which cannot be redeclared.
+const RESERVED_SYMBOLS: &[&str] = &[
+    "+", "-", "*", "/", "%", "^", "!", "!=", "=", "==", "<", "<=", ">", ">=", "|->", "=>", ":=",
+    ":", "..", ".+", ".-", ".*", "./", ".%", ".^", ".<", ".<=", ".>", ".>=", ".==", ".!=",
+];
+
+/// The highest precedence a declared operator can have.
+pub const MAX_PRECEDENCE: u8 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperatorInfo {
+    pub precedence: u8,
+    pub associativity: Associativity,
+}
+
+/// The user-defined infix operators declared so far,
+/// e.g. with `infixl 7 <.> := dot`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OperatorTable {
+    operators: HashMap<String, OperatorInfo>,
+}
+
+impl OperatorTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn declare(&mut self, symbol: String, info: OperatorInfo) {
+        self.operators.insert(symbol, info);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&OperatorInfo> {
+        self.operators.get(symbol)
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &String> {
+        self.operators.keys()
+    }
+}
+
+pub fn is_operator_char(ch: char) -> bool {
+    OPERATOR_CHARS.contains(ch)
+}
+
+/// Checks that `symbol` can be declared as a new operator.
+pub fn validate_symbol(symbol: &str) -> Result<(), String> {
+    if symbol.is_empty() || !symbol.chars().all(is_operator_char) {
+        Err(format!(
+            "Operator '{symbol}' must only contain the characters {OPERATOR_CHARS}"
+        ))
+    } else if RESERVED_SYMBOLS.contains(&symbol) {
+        Err(format!("Cannot redeclare the built-in operator '{symbol}'"))
+    } else if symbol.starts_with("//") {
+        Err(format!(
+            "Operator '{symbol}' cannot start with //, which begins a comment"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// The precedence and associativity of built-in binary operators,
+/// on the same scale as declared operators.
+/// `^` (level 8) is not listed, as it binds tighter than unary operators.
+/// Element-wise operators have the precedence of the operator they apply.
+pub fn builtin_operator(kind: &TokenType) -> Option<OperatorInfo> {
+    let precedence = match kind {
+        TokenType::Dotted(kind) => return builtin_operator(kind),
+        TokenType::Plus | TokenType::Minus => 4,
+        TokenType::EqualEqual | TokenType::BangEqual => 5,
+        TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual => 6,
+        TokenType::Star | TokenType::Slash | TokenType::Percent | TokenType::Div => 7,
+        _ => return None,
+    };
+    Some(OperatorInfo {
+        precedence,
+        associativity: Associativity::Left,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_symbol() {
+        assert!(validate_symbol("<.>").is_ok());
+        assert!(validate_symbol("~=").is_ok());
+        assert!(validate_symbol("==").is_err());
+        assert!(validate_symbol("//").is_err());
+        assert!(validate_symbol("<a>").is_err());
+        // The interval separator, as in [1 .. 2]
+        assert!(validate_symbol("..").is_err());
+        // The type annotation separator, as in x: Number := 1
+        assert!(validate_symbol(":").is_err());
+    }
+}
@@ -1,11 +1,18 @@
 use core::fmt::Arguments;
 
-use crate::ast::{Expr, LiteralValue, MatchArm};
+use crate::ast::{Expr, LiteralValue, MatchArm, TypeAnnotation};
+use crate::operators::{self, Associativity, MAX_PRECEDENCE, OperatorInfo, OperatorTable};
 use crate::token::{Token, TokenType};
+use crate::units::Unit;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Parameters created for `_` placeholders, one list per expression being parsed.
+    placeholder_scopes: Vec<Vec<String>>,
+    /// The number of the last placeholder parameter, named like `_1`.
+    placeholder_count: usize,
+    operators: OperatorTable,
 }
 
 /// Creates a parser message String that quotes the current line and column number.
@@ -17,7 +24,36 @@ macro_rules! parser_fmt {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        // Placeholder parameters are numbered after any names like `_1` in the source,
+        // so they never capture them.
+        let placeholder_count = tokens
+            .iter()
+            .filter_map(|token| match &token.kind {
+                TokenType::Identifier(name) => name.strip_prefix('_')?.parse().ok(),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        Self {
+            tokens,
+            current: 0,
+            placeholder_scopes: vec![],
+            placeholder_count,
+            operators: OperatorTable::new(),
+        }
+    }
+
+    /// Creates a parser that also knows about previously declared operators.
+    pub fn with_operators(tokens: Vec<Token>, operators: OperatorTable) -> Self {
+        Self {
+            operators,
+            ..Self::new(tokens)
+        }
+    }
+
+    /// The operators known to the parser, including those declared while parsing.
+    pub fn operators(&self) -> &OperatorTable {
+        &self.operators
     }
 
     pub fn report(errors: &[String]) -> String {
@@ -117,6 +153,13 @@ impl Parser {
         false
     }
 
+    /// Checks if the current token is one of the given binary operators.
+    /// An operator directly followed by `)` does not take a right operand,
+    /// as it ends an operator section like `(2 ^)`.
+    fn matches_binary_operator(&self, expected_kinds: &[TokenType]) -> bool {
+        self.matches_any(expected_kinds) && self.lookahead_kind() != Some(TokenType::RightParen)
+    }
+
     pub fn program(&mut self) -> Result<Expr, Vec<String>> {
         let mut statements = vec![];
         let mut errors = vec![];
@@ -155,17 +198,55 @@ impl Parser {
         }
     }
 
+    /// Parses an expression, turning it into a function if it contains `_` placeholders.
+    ///
+    /// Each placeholder belongs to the smallest expression that properly contains it,
+    /// so `_ * 2 + 1` is `x |-> x * 2 + 1` and `map(_ + 1, xs)` is `map(x |-> x + 1, xs)`.
+    /// A bare `_` such as a call argument belongs to the enclosing expression instead,
+    /// so `clamp(0, 1, _)` is `x |-> clamp(0, 1, x)`.
+    /// Several placeholders become curried parameters, from left to right.
     fn expression(&mut self) -> Result<Expr, String> {
+        self.placeholder_scopes.push(vec![]);
+        let expr = self.unscoped_expression();
+        let params = self.placeholder_scopes.pop().unwrap_or_default();
+        let expr = expr?;
+
+        if let Expr::Variable(name) = &expr
+            && params.len() == 1
+            && params[0] == *name
+            && let Some(outer) = self.placeholder_scopes.last_mut()
+        {
+            outer.push(name.clone());
+            return Ok(expr);
+        }
+
+        Ok(params
+            .into_iter()
+            .rev()
+            .fold(expr, |body, param| Expr::FunctionDef {
+                param,
+                param_type: None,
+                body: Box::new(body),
+                return_type: None,
+            }))
+    }
+
+    fn unscoped_expression(&mut self) -> Result<Expr, String> {
         match self.current_kind() {
             Some(TokenType::EndStmt) => self.empty_expr(),
             Some(TokenType::If) => self.if_expr(),
             Some(TokenType::Match) => self.match_expr(),
             Some(TokenType::LeftBrace) => self.block_expr(),
+            Some(TokenType::Infix | TokenType::InfixLeft | TokenType::InfixRight) => {
+                self.operator_declaration()
+            }
             Some(TokenType::Eof) | None => Err(parser_fmt!(self, "Expected an expression")),
+            Some(TokenType::LeftParen) if self.is_parameter_list() => self.function_def(),
             Some(_) => match self.lookahead_kind() {
                 Some(TokenType::Equal) => self.assignment(),
                 Some(TokenType::Binding) => self.binding(),
                 Some(TokenType::MapsTo) => self.function_def(),
+                Some(TokenType::Colon) => self.annotated_definition(),
                 _ => self.binary_expr(),
             },
         }
@@ -177,6 +258,10 @@ impl Parser {
     }
 
     fn if_expr(&mut self) -> Result<Expr, String> {
+        let keyword = match self.current() {
+            Some(keyword) => keyword.clone(),
+            None => return Err(parser_fmt!(self, "Expected an if expression")),
+        };
         self.consume(TokenType::If)?;
         let cond_expr = Box::new(self.expression()?);
 
@@ -192,6 +277,7 @@ impl Parser {
         };
 
         Ok(Expr::If {
+            keyword,
             cond_expr,
             then_expr,
             else_expr,
@@ -241,7 +327,85 @@ impl Parser {
         Some(MatchArm { pattern, body })
     }
 
+    /// Parses a declaration like `infixl 7 <.> := dot`.
+    /// The function is bound to the operator symbol, and `a <.> b` in the rest
+    /// of the program calls it as `dot(a, b)`.
+    fn operator_declaration(&mut self) -> Result<Expr, String> {
+        let associativity = match self.current_kind() {
+            Some(TokenType::InfixLeft) => Associativity::Left,
+            Some(TokenType::InfixRight) => Associativity::Right,
+            _ => Associativity::None,
+        };
+        self.advance();
+
+        let precedence = match self.current_kind() {
+            Some(TokenType::Integer(n))
+                if n.to_i64()
+                    .is_some_and(|n| (0..=MAX_PRECEDENCE as i64).contains(&n)) =>
+            {
+                n.to_i64().unwrap_or_default() as u8
+            }
+            _ => {
+                return Err(parser_fmt!(
+                    self,
+                    "Expected an operator precedence from 0 to {MAX_PRECEDENCE}"
+                ));
+            }
+        };
+        self.advance();
+
+        let symbol = match self.current_kind() {
+            Some(TokenType::Operator(symbol)) => symbol,
+            _ => return Err(parser_fmt!(self, "Expected an operator symbol to declare")),
+        };
+        operators::validate_symbol(&symbol).map_err(|e| parser_fmt!(self, "{e}"))?;
+        self.advance();
+
+        self.consume(TokenType::Binding)?;
+        let expr = self.expression()?;
+
+        self.operators.declare(
+            symbol.clone(),
+            OperatorInfo {
+                precedence,
+                associativity,
+            },
+        );
+        Ok(Expr::Binding {
+            name: symbol,
+            annotation: None,
+            expr: Box::new(expr),
+        })
+    }
+
+    fn is_placeholder(&self) -> bool {
+        matches!(self.current_kind(), Some(TokenType::Identifier(name)) if name == "_")
+    }
+
+    /// Creates a parameter name that the source does not use, like `_1`.
+    fn fresh_parameter(&mut self) -> String {
+        self.placeholder_count += 1;
+        format!("_{}", self.placeholder_count)
+    }
+
+    /// Creates the parameter that a `_` placeholder stands for.
+    fn placeholder(&mut self) -> Result<Expr, String> {
+        let param = self.fresh_parameter();
+        match self.placeholder_scopes.last_mut() {
+            Some(scope) => scope.push(param.clone()),
+            None => return Err(parser_fmt!(self, "Unexpected placeholder '_'")),
+        }
+        self.advance();
+        Ok(Expr::Variable(param))
+    }
+
     fn assignment(&mut self) -> Result<Expr, String> {
+        if self.is_placeholder() {
+            return Err(parser_fmt!(
+                self,
+                "Cannot assign a value to the placeholder '_'"
+            ));
+        }
         let name = match self.primary()? {
             Expr::Variable(name) => name,
             _ => {
@@ -251,33 +415,40 @@ impl Parser {
                 ));
             }
         };
-        let expr = match self.current_kind() {
-            Some(TokenType::Equal) => {
-                self.advance();
-                self.expression()?
-            }
-            Some(kind) => {
+        let equal = match self.current() {
+            Some(equal) if equal.kind == TokenType::Equal => equal.clone(),
+            Some(token) => {
                 return Err(parser_fmt!(
                     self,
                     "Expected an assignment expression, found {:?}",
-                    kind
+                    token.kind
                 ));
             }
             None => return Err(parser_fmt!(self, "Expected an expression")),
         };
+        self.advance();
+        let expr = self.expression()?;
         Ok(Expr::Assign {
             name,
+            equal,
             expr: Box::new(expr),
         })
     }
 
     fn binding(&mut self) -> Result<Expr, String> {
+        if self.is_placeholder() {
+            return Err(parser_fmt!(
+                self,
+                "Cannot bind a value to the placeholder '_'"
+            ));
+        }
         let name = match self.primary()? {
             Expr::Variable(name) => name,
             _ => {
                 return Err(parser_fmt!(self, "Expected an identifier to bind a value"));
             }
         };
+        let annotation = self.optional_annotation()?;
         let expr = match self.current_kind() {
             Some(TokenType::Binding) => {
                 self.advance();
@@ -294,11 +465,95 @@ impl Parser {
         };
         Ok(Expr::Binding {
             name,
+            annotation,
             expr: Box::new(expr),
         })
     }
 
+    /// Parses a binding or function that starts with an annotated name,
+    /// like `x: Number := 1` or `x: Number |-> x * 2`.
+    /// Otherwise the name is a function body followed by its return type, as in `x |-> x : Number`.
+    fn annotated_definition(&mut self) -> Result<Expr, String> {
+        let start = self.current;
+        self.advance(); // name
+        self.advance(); // :
+        self.type_annotation()?;
+        let next = self.current_kind();
+        self.current = start;
+
+        match next {
+            Some(TokenType::MapsTo) => self.function_def(),
+            Some(TokenType::Binding) => self.binding(),
+            _ => self.binary_expr(),
+        }
+    }
+
+    /// Checks if the current `(` starts the parameters of a function like `(a, b) |-> a + b`.
+    fn is_parameter_list(&self) -> bool {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.kind {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen if depth == 1 => {
+                    return matches!(
+                        self.tokens.get(i + 1).map(|token| &token.kind),
+                        Some(TokenType::MapsTo)
+                    );
+                }
+                TokenType::RightParen => depth -= 1,
+                TokenType::EndStmt | TokenType::Eof => return false,
+                _ => {}
+            }
+        }
+        false
+    }
+
     fn function_def(&mut self) -> Result<Expr, String> {
+        let params = if self.matches(TokenType::LeftParen) {
+            self.parameter_list()?
+        } else {
+            vec![self.parameter()?]
+        };
+
+        self.consume(TokenType::MapsTo)?;
+        let body = self.function_body()?;
+        let return_type = self.optional_annotation()?;
+
+        // Several parameters are curried, so (a, b) |-> body is a |-> b |-> body.
+        // The return type belongs to the innermost function, which returns the result.
+        let mut params = params.into_iter().rev();
+        let (param, param_type) = params
+            .next()
+            .expect("Functions have at least one parameter");
+        let innermost = Expr::FunctionDef {
+            param,
+            param_type,
+            body: Box::new(body),
+            return_type,
+        };
+        Ok(
+            params.fold(innermost, |body, (param, param_type)| Expr::FunctionDef {
+                param,
+                param_type,
+                body: Box::new(body),
+                return_type: None,
+            }),
+        )
+    }
+
+    fn parameter_list(&mut self) -> Result<Vec<(String, Option<TypeAnnotation>)>, String> {
+        self.consume(TokenType::LeftParen)?;
+        let mut params = vec![self.parameter()?];
+        while self.matches(TokenType::Comma) {
+            self.advance();
+            params.push(self.parameter()?);
+        }
+        self.consume(TokenType::RightParen)?;
+        Ok(params)
+    }
+
+    /// Parses a parameter name with an optional type, like `x` or `x: Number`.
+    fn parameter(&mut self) -> Result<(String, Option<TypeAnnotation>), String> {
         let param = match self.current_kind() {
             Some(TokenType::Identifier(name)) => name,
             _ => {
@@ -309,11 +564,46 @@ impl Parser {
             }
         };
         self.advance();
+        Ok((param, self.optional_annotation()?))
+    }
 
-        self.consume(TokenType::MapsTo)?;
-        let body = Box::new(self.function_body()?);
+    /// Parses a `: Type` annotation, if there is one.
+    fn optional_annotation(&mut self) -> Result<Option<TypeAnnotation>, String> {
+        if self.matches(TokenType::Colon) {
+            self.advance();
+            Ok(Some(self.type_annotation()?))
+        } else {
+            Ok(None)
+        }
+    }
 
-        Ok(Expr::FunctionDef { param, body })
+    /// Parses a type like `Number`, `List` or `List<String>`.
+    fn type_annotation(&mut self) -> Result<TypeAnnotation, String> {
+        let name = match self.current_kind() {
+            Some(TokenType::Identifier(name)) => name,
+            _ => return Err(parser_fmt!(self, "Expected a type name after ':'")),
+        };
+        let annotation = match name.as_str() {
+            "Any" => TypeAnnotation::Any,
+            "Number" => TypeAnnotation::Number,
+            "String" => TypeAnnotation::String,
+            "Boolean" => TypeAnnotation::Boolean,
+            "Function" => TypeAnnotation::Function,
+            "Stream" => TypeAnnotation::Stream,
+            "List" => {
+                self.advance();
+                if !self.matches(TokenType::Less) {
+                    return Ok(TypeAnnotation::List(Box::new(TypeAnnotation::Any)));
+                }
+                self.advance();
+                let element = self.type_annotation()?;
+                self.consume(TokenType::Greater)?;
+                return Ok(TypeAnnotation::List(Box::new(element)));
+            }
+            _ => return Err(parser_fmt!(self, "Unknown type '{name}'")),
+        };
+        self.advance();
+        Ok(annotation)
     }
 
     fn function_body(&mut self) -> Result<Expr, String> {
@@ -327,130 +617,247 @@ impl Parser {
     }
 
     fn binary_expr(&mut self) -> Result<Expr, String> {
-        let mut left = self.equality()?;
+        self.binary_operation(0)
+    }
+
+    /// Returns the precedence and associativity of the current token, if it is a binary
+    /// operator with a right operand. `^` is parsed separately, as it binds tighter
+    /// than unary operators.
+    fn infix_operator(&self) -> Option<OperatorInfo> {
+        // An operator directly followed by `)` ends a section like `(2 *)`.
+        if self.lookahead_kind() == Some(TokenType::RightParen) {
+            return None;
+        }
+        match self.current_kind()? {
+            TokenType::Operator(symbol) => self.operators.get(&symbol).cloned(),
+            kind => operators::builtin_operator(&kind),
+        }
+    }
+
+    /// Parses a chain of binary operators with at least `min_precedence`.
+    fn binary_operation(&mut self, min_precedence: u8) -> Result<Expr, String> {
+        let mut left = self.unary()?;
 
-        while self.matches_any(&[TokenType::Plus, TokenType::Minus]) {
+        while let Some(info) = self.infix_operator()
+            && info.precedence >= min_precedence
+        {
             let op = match self.current() {
                 Some(op) => op.clone(),
-                None => return Err(parser_fmt!(self, "Expected '+' or '-'")),
+                None => return Err(parser_fmt!(self, "Expected a binary operator")),
             };
             self.advance();
-            let right = self.equality()?;
-            left = Expr::Binary {
-                left: Box::new(left),
-                op,
-                right: Box::new(right),
+
+            let right_precedence = match info.associativity {
+                Associativity::Right => info.precedence,
+                Associativity::Left | Associativity::None => info.precedence + 1,
             };
+            let right = self.binary_operation(right_precedence)?;
+            left = Self::make_binary(left, op.clone(), right);
+
+            if info.associativity == Associativity::None
+                && self
+                    .infix_operator()
+                    .is_some_and(|next| next.precedence == info.precedence)
+            {
+                return Err(parser_fmt!(
+                    self,
+                    "Operator '{}' is non-associative, use parentheses to chain it",
+                    op.lexeme
+                ));
+            }
         }
 
         Ok(left)
     }
 
-    fn equality(&mut self) -> Result<Expr, String> {
-        let mut left = self.comparison()?;
-
-        while self.matches_any(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let op = match self.current() {
-                Some(op) => op.clone(),
-                None => return Err(parser_fmt!(self, "Expected '==' or '!='")),
-            };
-            self.advance();
-            let right = self.comparison()?;
-            left = Expr::Binary {
+    fn make_binary(left: Expr, op: Token, right: Expr) -> Expr {
+        match &op.kind {
+            // A user-defined operator calls the function bound to its symbol.
+            TokenType::Operator(symbol) => Expr::FunctionCall {
+                func: Box::new(Expr::FunctionCall {
+                    func: Box::new(Expr::Variable(symbol.clone())),
+                    paren: op.clone(),
+                    arg: Box::new(left),
+                }),
+                paren: op,
+                arg: Box::new(right),
+            },
+            _ => Expr::Binary {
                 left: Box::new(left),
                 op,
                 right: Box::new(right),
-            };
+            },
         }
-
-        Ok(left)
     }
 
-    fn comparison(&mut self) -> Result<Expr, String> {
-        let mut left = self.factor()?;
-
-        while self.matches_any(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.matches_any(&[TokenType::Bang, TokenType::Minus]) {
             let op = match self.current() {
                 Some(op) => op.clone(),
-                None => {
-                    return Err(parser_fmt!(self, "Expected one of '>', '>=', '<', '<='"));
-                }
+                None => return Err(parser_fmt!(self, "Expected '!' or '-'")),
             };
             self.advance();
-            let right = self.factor()?;
-            left = Expr::Binary {
+            let right = Box::new(self.power()?);
+            Ok(Expr::Unary { op, right })
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let left = self.function_call()?;
+
+        // A number directly before a name multiplies it, so 3x^2 is 3 * x^2.
+        if self.is_coefficient(&left)
+            && let Some(name) = self.current().cloned()
+        {
+            let op = Token {
+                kind: TokenType::Star,
+                lexeme: "*".to_string(),
+                ..name
+            };
+            let right = self.power()?;
+            return Ok(Expr::Binary {
                 left: Box::new(left),
                 op,
                 right: Box::new(right),
-            };
+            });
         }
 
-        Ok(left)
-    }
-
-    fn factor(&mut self) -> Result<Expr, String> {
-        let mut left = self.unary()?;
-
-        while self.matches_any(&[TokenType::Star, TokenType::Slash]) {
+        let dotted_caret = TokenType::Dotted(Box::new(TokenType::Caret));
+        if self.matches_binary_operator(&[TokenType::Caret, dotted_caret]) {
             let op = match self.current() {
                 Some(op) => op.clone(),
-                None => return Err(parser_fmt!(self, "Expected '*' or '/'")),
+                None => return Err(parser_fmt!(self, "Expected '^'")),
             };
             self.advance();
+            // Right associative, and binds tighter than a unary minus on its left:
+            // 2 ^ 3 ^ 2 is 2 ^ (3 ^ 2), and -2 ^ 2 is -(2 ^ 2).
             let right = self.unary()?;
-            left = Expr::Binary {
+            return Ok(Expr::Binary {
                 left: Box::new(left),
                 op,
                 right: Box::new(right),
-            };
+            });
         }
 
         Ok(left)
     }
 
-    fn unary(&mut self) -> Result<Expr, String> {
-        if self.matches_any(&[TokenType::Bang, TokenType::Minus]) {
-            let op = match self.current() {
-                Some(op) => op.clone(),
-                None => return Err(parser_fmt!(self, "Expected '!' or '-'")),
-            };
-            self.advance();
-            let right = Box::new(self.function_call()?);
-            Ok(Expr::Unary { op, right })
-        } else {
-            self.function_call()
-        }
-    }
-
     fn function_call(&mut self) -> Result<Expr, String> {
         let mut left = self.primary()?;
 
-        while self.matches(TokenType::LeftParen) {
+        while let Some(paren) = self.current().cloned()
+            && paren.kind == TokenType::LeftParen
+        {
             self.advance();
-
-            let arg = Box::new(self.expression()?);
+            let outer_placeholders = self.placeholder_scopes.last().map_or(0, Vec::len);
+
+            // A call without arguments, f(), passes nil.
+            if self.matches(TokenType::RightParen) {
+                left = Expr::FunctionCall {
+                    func: Box::new(left),
+                    paren: paren.clone(),
+                    arg: Box::new(Expr::Literal(LiteralValue::Nil)),
+                };
+            }
+            // Multiple arguments are curried: f(a, b) is the same as f(a)(b).
+            while !self.matches(TokenType::RightParen) {
+                let arg = Box::new(self.expression()?);
+                left = Expr::FunctionCall {
+                    func: Box::new(left),
+                    paren: paren.clone(),
+                    arg,
+                };
+
+                if self.matches(TokenType::Comma) {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
             self.consume(TokenType::RightParen)?;
 
-            left = Expr::FunctionCall {
-                func: Box::new(left),
-                arg,
-            };
+            // Calling a partial application applies it, so in f(_, 2)(1)
+            // the placeholder belongs to f(_, 2) rather than the whole call.
+            if self.matches(TokenType::LeftParen)
+                && let Some(scope) = self.placeholder_scopes.last_mut()
+                && scope.len() > outer_placeholders
+            {
+                let params: Vec<String> = scope.drain(outer_placeholders..).collect();
+                left = params
+                    .into_iter()
+                    .rev()
+                    .fold(left, |body, param| Expr::FunctionDef {
+                        param,
+                        param_type: None,
+                        body: Box::new(body),
+                        return_type: None,
+                    });
+            }
         }
 
         Ok(left)
     }
 
+    /// Checks if a number literal is directly followed by a name, like `3x`.
+    fn is_coefficient(&self, expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Literal(LiteralValue::Number(_) | LiteralValue::Integer(_))
+        ) && matches!(self.current_kind(), Some(TokenType::Identifier(_)))
+            && self.is_attached()
+    }
+
+    /// Checks if the current token directly follows the previous one, without spaces.
+    fn is_attached(&self) -> bool {
+        match (
+            self.tokens.get(self.current.wrapping_sub(1)),
+            self.current(),
+        ) {
+            (Some(previous), Some(token)) => {
+                previous.line == token.line
+                    && token.column == previous.column + token.lexeme.chars().count()
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses the unit after a number, like `'m/s^2'` in `9.81 'm/s^2'`.
+    fn unit(&mut self) -> Result<Option<Unit>, String> {
+        let Some(TokenType::Unit(text)) = self.current_kind() else {
+            return Ok(None);
+        };
+        let unit = Unit::parse(&text).map_err(|e| parser_fmt!(self, "{e}"))?;
+        self.advance();
+        Ok(Some(unit))
+    }
+
     fn primary(&mut self) -> Result<Expr, String> {
         match self.current_kind() {
             Some(TokenType::Number(value)) => {
                 self.advance();
-                Ok(Expr::Literal(LiteralValue::Number(value)))
+                match self.unit()? {
+                    Some(unit) => Ok(Expr::Literal(LiteralValue::Quantity(value, unit))),
+                    None => Ok(Expr::Literal(LiteralValue::Number(value))),
+                }
+            }
+            Some(TokenType::Integer(value)) => {
+                self.advance();
+                match self.unit()? {
+                    Some(unit) => Ok(Expr::Literal(LiteralValue::Quantity(value.to_f64(), unit))),
+                    None => Ok(Expr::Literal(LiteralValue::Integer(value))),
+                }
+            }
+            Some(TokenType::Rational(value)) => {
+                self.advance();
+                Ok(Expr::Literal(LiteralValue::Rational(value)))
             }
+            Some(TokenType::Imaginary(value)) => {
+                self.advance();
+                Ok(Expr::Literal(LiteralValue::Imaginary(value)))
+            }
+            Some(TokenType::Identifier(name)) if name == "_" => self.placeholder(),
             Some(TokenType::Identifier(name)) => {
                 self.advance();
                 Ok(Expr::Variable(name))
@@ -461,6 +868,8 @@ impl Parser {
             }
             Some(TokenType::LeftParen) => self.grouping(),
             Some(TokenType::LeftSquareBracket) => self.list(),
+            Some(TokenType::LeftMatrixBracket) => self.matrix(),
+            Some(TokenType::Sym) => self.symbolic(),
             Some(kind) => Err(parser_fmt!(
                 self,
                 "Expected a primary expression, found {:?}",
@@ -470,14 +879,29 @@ impl Parser {
         }
     }
 
+    /// Parses `sym(expr)`, whose expression is kept as a formula instead of evaluated.
+    fn symbolic(&mut self) -> Result<Expr, String> {
+        self.consume(TokenType::Sym)?;
+        self.consume(TokenType::LeftParen)?;
+        let expr = self.expression()?;
+        self.consume(TokenType::RightParen)?;
+        Ok(Expr::Symbolic(Box::new(expr)))
+    }
+
     fn grouping(&mut self) -> Result<Expr, String> {
         self.consume(TokenType::LeftParen)?; // opening (
+
+        if let Some(op) = self.section_operator() {
+            return self.right_section(op);
+        }
+
         let expr = self.expression()?;
         match self.current_kind() {
             Some(TokenType::RightParen) => {
                 self.advance(); // closing )
                 Ok(Expr::Grouping(Box::new(expr)))
             }
+            Some(_) if self.section_operator().is_some() => self.left_section(expr),
             Some(kind) => Err(parser_fmt!(
                 self,
                 "Expected ) after parenthesised expression, found {:?}",
@@ -487,11 +911,132 @@ impl Parser {
         }
     }
 
+    /// Returns the current token if it can start or end an operator section.
+    /// A `-` followed by an operand is a unary minus, so `(- 3)` is just -3.
+    fn section_operator(&self) -> Option<Token> {
+        let is_binary_operator = self.matches_any(&[
+            TokenType::Plus,
+            TokenType::Minus,
+            TokenType::Star,
+            TokenType::Slash,
+            TokenType::Percent,
+            TokenType::Div,
+            TokenType::Caret,
+            TokenType::Greater,
+            TokenType::GreaterEqual,
+            TokenType::Less,
+            TokenType::LessEqual,
+            TokenType::BangEqual,
+            TokenType::EqualEqual,
+        ]) || match self.current_kind() {
+            Some(TokenType::Dotted(_)) => true,
+            Some(TokenType::Operator(symbol)) => self.operators.get(&symbol).is_some(),
+            _ => false,
+        };
+        let is_negation =
+            self.matches(TokenType::Minus) && self.lookahead_kind() != Some(TokenType::RightParen);
+
+        if is_binary_operator && !is_negation {
+            self.current().cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Parses `(op)` or `(op right)`, after the opening parenthesis.
+    fn right_section(&mut self, op: Token) -> Result<Expr, String> {
+        self.advance();
+        let right = if self.matches(TokenType::RightParen) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        self.consume(TokenType::RightParen)?;
+
+        Ok(self.make_section(op, None, right))
+    }
+
+    /// Parses the `op)` of `(left op)`.
+    fn left_section(&mut self, left: Expr) -> Result<Expr, String> {
+        let op = match self.current() {
+            Some(op) => op.clone(),
+            None => return Err(parser_fmt!(self, "Expected an operator")),
+        };
+        self.advance();
+        self.consume(TokenType::RightParen)?;
+
+        Ok(self.make_section(op, Some(Box::new(left)), None))
+    }
+
+    fn make_section(
+        &mut self,
+        op: Token,
+        left: Option<Box<Expr>>,
+        right: Option<Box<Expr>>,
+    ) -> Expr {
+        // A user-defined operator is the function bound to its symbol,
+        // so `(<+>)` is that function and `(<+> 1)` is `(y |-> x |-> x <+> y)(1)`,
+        // which evaluates the operand once, like the built-in sections.
+        let TokenType::Operator(symbol) = &op.kind else {
+            return Expr::Section { op, left, right };
+        };
+        let function = Expr::Variable(symbol.clone());
+        match (left, right) {
+            (Some(left), _) => Expr::FunctionCall {
+                func: Box::new(function),
+                paren: op,
+                arg: left,
+            },
+            (_, Some(right)) => {
+                let (operand, param) = (self.fresh_parameter(), self.fresh_parameter());
+                let section = Expr::FunctionDef {
+                    param: param.clone(),
+                    param_type: None,
+                    body: Box::new(Self::make_binary(
+                        Expr::Variable(param),
+                        op.clone(),
+                        Expr::Variable(operand.clone()),
+                    )),
+                    return_type: None,
+                };
+                Expr::FunctionCall {
+                    func: Box::new(Expr::FunctionDef {
+                        param: operand,
+                        param_type: None,
+                        body: Box::new(section),
+                        return_type: None,
+                    }),
+                    paren: op,
+                    arg: right,
+                }
+            }
+            (None, None) => Expr::Grouping(Box::new(function)),
+        }
+    }
+
+    /// Parses a list, or an interval like `[1.9 .. 2.1]`.
     fn list(&mut self) -> Result<Expr, String> {
         self.consume(TokenType::LeftSquareBracket)?; // opening [
 
         let mut elements: Vec<Expr> = vec![];
 
+        if !self.matches(TokenType::RightSquareBracket) {
+            let first = self.expression()?;
+            if self.matches(TokenType::DotDot) {
+                self.advance();
+                let high = self.expression()?;
+                self.consume(TokenType::RightSquareBracket)?; // closing ]
+                return Ok(Expr::Interval {
+                    low: Box::new(first),
+                    high: Box::new(high),
+                });
+            }
+            elements.push(first);
+            if self.matches(TokenType::Comma) {
+                self.advance();
+            }
+        }
+
         while !self.matches(TokenType::RightSquareBracket) {
             elements.push(self.expression()?);
             if self.matches(TokenType::Comma) {
@@ -502,6 +1047,41 @@ impl Parser {
         self.consume(TokenType::RightSquareBracket)?; // closing ]
         Ok(Expr::List { elements })
     }
+
+    /// Parses a matrix with rows separated by `;`, like `[|1, 2; 3, 4|]`,
+    /// or a column vector without any `;`, like `[|1, 2|]`.
+    fn matrix(&mut self) -> Result<Expr, String> {
+        self.consume(TokenType::LeftMatrixBracket)?; // opening [|
+
+        let mut rows: Vec<Vec<Expr>> = vec![vec![]];
+        let mut is_vector = true;
+        while !self.matches(TokenType::RightMatrixBracket) {
+            if self.matches(TokenType::EndStmt) {
+                self.advance();
+                is_vector = false;
+                rows.push(vec![]);
+                continue;
+            }
+            let row = rows.last_mut().expect("There is always a row");
+            row.push(self.expression()?);
+            if self.matches(TokenType::Comma) {
+                self.advance();
+            } else if !self.matches_any(&[TokenType::EndStmt, TokenType::RightMatrixBracket]) {
+                return Err(parser_fmt!(self, "Expected , or ; or |] in a matrix"));
+            }
+        }
+        self.consume(TokenType::RightMatrixBracket)?; // closing |]
+
+        // A trailing ; does not start another row.
+        if rows.len() > 1 && rows.last().is_some_and(Vec::is_empty) {
+            rows.pop();
+        }
+        if is_vector {
+            let elements = rows.pop().unwrap_or_default();
+            rows = elements.into_iter().map(|element| vec![element]).collect();
+        }
+        Ok(Expr::Matrix { rows })
+    }
 }
 
 #[cfg(test)]
@@ -664,7 +1244,276 @@ mod tests {
             Program {
                 statements: vec![FunctionDef {
                     param: "x".into(),
+                    param_type: None,
                     body: Box::new(Literal(LiteralValue::Number(2.0))),
+                    return_type: None,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_function_call_multiple_args() {
+        // f(1, 2) is parsed as f(1)(2)
+        assert_parse(
+            vec![
+                make_token(Identifier("f".into())),
+                make_token(LeftParen),
+                make_token(Number(1.0)),
+                make_token(Comma),
+                make_token(Number(2.0)),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![FunctionCall {
+                    func: Box::new(FunctionCall {
+                        func: Box::new(Variable("f".into())),
+                        paren: make_token(LeftParen),
+                        arg: Box::new(Literal(LiteralValue::Number(1.0))),
+                    }),
+                    paren: make_token(LeftParen),
+                    arg: Box::new(Literal(LiteralValue::Number(2.0))),
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_function_call_no_args() {
+        // f() is parsed as f(nil)
+        assert_parse(
+            vec![
+                make_token(Identifier("f".into())),
+                make_token(LeftParen),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![FunctionCall {
+                    func: Box::new(Variable("f".into())),
+                    paren: make_token(LeftParen),
+                    arg: Box::new(Literal(LiteralValue::Nil)),
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_power_right_associative() {
+        // 2 ^ 3 ^ 2
+        assert_parse(
+            vec![
+                make_token(Number(2.0)),
+                make_token(Caret),
+                make_token(Number(3.0)),
+                make_token(Caret),
+                make_token(Number(2.0)),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Binary {
+                    left: Box::new(Literal(LiteralValue::Number(2.0))),
+                    op: make_token(Caret),
+                    right: Box::new(Binary {
+                        left: Box::new(Literal(LiteralValue::Number(3.0))),
+                        op: make_token(Caret),
+                        right: Box::new(Literal(LiteralValue::Number(2.0))),
+                    }),
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        // (+)
+        assert_parse(
+            vec![
+                make_token(LeftParen),
+                make_token(Plus),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Section {
+                    op: make_token(Plus),
+                    left: None,
+                    right: None,
+                }],
+            },
+        );
+
+        // (< 3)
+        assert_parse(
+            vec![
+                make_token(LeftParen),
+                make_token(Less),
+                make_token(Number(3.0)),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Section {
+                    op: make_token(Less),
+                    left: None,
+                    right: Some(Box::new(Literal(LiteralValue::Number(3.0)))),
+                }],
+            },
+        );
+
+        // (2 ^)
+        assert_parse(
+            vec![
+                make_token(LeftParen),
+                make_token(Number(2.0)),
+                make_token(Caret),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Section {
+                    op: make_token(Caret),
+                    left: Some(Box::new(Literal(LiteralValue::Number(2.0)))),
+                    right: None,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_negation_is_not_a_section() {
+        // (- 3)
+        assert_parse(
+            vec![
+                make_token(LeftParen),
+                make_token(Minus),
+                make_token(Number(3.0)),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Grouping(Box::new(Unary {
+                    op: make_token(Minus),
+                    right: Box::new(Literal(LiteralValue::Number(3.0))),
+                }))],
+            },
+        );
+    }
+
+    #[test]
+    fn test_placeholder_in_binary_expr() {
+        // _ * 2
+        assert_parse(
+            vec![
+                make_token(Identifier("_".into())),
+                make_token(Star),
+                make_token(Number(2.0)),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![FunctionDef {
+                    param: "_1".into(),
+                    param_type: None,
+                    body: Box::new(Binary {
+                        left: Box::new(Variable("_1".into())),
+                        op: make_token(Star),
+                        right: Box::new(Literal(LiteralValue::Number(2.0))),
+                    }),
+                    return_type: None,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_placeholder_call_argument() {
+        // f(_, 1)
+        assert_parse(
+            vec![
+                make_token(Identifier("f".into())),
+                make_token(LeftParen),
+                make_token(Identifier("_".into())),
+                make_token(Comma),
+                make_token(Number(1.0)),
+                make_token(RightParen),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![FunctionDef {
+                    param: "_1".into(),
+                    param_type: None,
+                    body: Box::new(FunctionCall {
+                        func: Box::new(FunctionCall {
+                            func: Box::new(Variable("f".into())),
+                            paren: make_token(LeftParen),
+                            arg: Box::new(Variable("_1".into())),
+                        }),
+                        paren: make_token(LeftParen),
+                        arg: Box::new(Literal(LiteralValue::Number(1.0))),
+                    }),
+                    return_type: None,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_annotated_function_def() {
+        // (x: Number, xs: List<Number>) |-> x : Number
+        assert_parse(
+            vec![
+                make_token(LeftParen),
+                make_token(Identifier("x".into())),
+                make_token(Colon),
+                make_token(Identifier("Number".into())),
+                make_token(Comma),
+                make_token(Identifier("xs".into())),
+                make_token(Colon),
+                make_token(Identifier("List".into())),
+                make_token(Less),
+                make_token(Identifier("Number".into())),
+                make_token(Greater),
+                make_token(RightParen),
+                make_token(MapsTo),
+                make_token(Identifier("x".into())),
+                make_token(Colon),
+                make_token(Identifier("Number".into())),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![FunctionDef {
+                    param: "x".into(),
+                    param_type: Some(TypeAnnotation::Number),
+                    body: Box::new(FunctionDef {
+                        param: "xs".into(),
+                        param_type: Some(TypeAnnotation::List(Box::new(TypeAnnotation::Number))),
+                        body: Box::new(Variable("x".into())),
+                        return_type: Some(TypeAnnotation::Number),
+                    }),
+                    return_type: None,
+                }],
+            },
+        );
+    }
+
+    #[test]
+    fn test_annotated_binding() {
+        // x: Number := 1
+        assert_parse(
+            vec![
+                make_token(Identifier("x".into())),
+                make_token(Colon),
+                make_token(Identifier("Number".into())),
+                make_token(Binding),
+                make_token(Number(1.0)),
+                make_token(Eof),
+            ],
+            Program {
+                statements: vec![Expr::Binding {
+                    name: "x".into(),
+                    annotation: Some(TypeAnnotation::Number),
+                    expr: Box::new(Literal(LiteralValue::Number(1.0))),
                 }],
             },
         );
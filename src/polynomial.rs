@@ -0,0 +1,356 @@
+use std::fmt::Display;
+
+use crate::complex::Complex;
+use crate::matrix::Matrix;
+
+/// Newton steps taken to refine each root found from the companion matrix.
+const POLISH_STEPS: usize = 8;
+
+/// A polynomial in `x` with real coefficients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    /// Lowest degree first, without zero coefficients at the end,
+    /// so the zero polynomial has none.
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial from its coefficients, lowest degree first.
+    pub fn new(mut coefficients: Vec<f64>) -> Self {
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self::new(vec![value])
+    }
+
+    /// The coefficients, lowest degree first.
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// The degree, which is 0 for the zero polynomial as for other constants.
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    fn leading(&self) -> f64 {
+        self.coefficients.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let length = self.coefficients.len().max(other.coefficients.len());
+        let coefficient = |p: &Self, i: usize| p.coefficients.get(i).copied().unwrap_or(0.0);
+        Self::new(
+            (0..length)
+                .map(|i| coefficient(self, i) + coefficient(other, i))
+                .collect(),
+        )
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.scale(-1.0))
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new(self.coefficients.iter().map(|c| c * factor).collect())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self::new(vec![]);
+        }
+        let mut product = vec![0.0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Self::new(product)
+    }
+
+    pub fn pow(&self, exponent: u32) -> Self {
+        (0..exponent).fold(Self::constant(1.0), |power, _| power.mul(self))
+    }
+
+    /// Long division, returning the quotient and the remainder.
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), String> {
+        if divisor.is_zero() {
+            return Err("Division by the zero polynomial".into());
+        }
+        let mut remainder = self.coefficients.clone();
+        let shift = match remainder.len().checked_sub(divisor.coefficients.len()) {
+            Some(shift) => shift,
+            None => return Ok((Self::new(vec![]), self.clone())),
+        };
+        let mut quotient = vec![0.0; shift + 1];
+        for i in (0..=shift).rev() {
+            let factor = remainder[i + divisor.degree()] / divisor.leading();
+            quotient[i] = factor;
+            for (j, c) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] -= factor * c;
+            }
+            // The leading term cancels exactly, whatever the rounding.
+            remainder[i + divisor.degree()] = 0.0;
+        }
+        Ok((Self::new(quotient), Self::new(remainder)))
+    }
+
+    pub fn derivative(&self) -> Self {
+        Self::new(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| c * i as f64)
+                .collect(),
+        )
+    }
+
+    /// The antiderivative that is 0 at 0.
+    pub fn integral(&self) -> Self {
+        let mut coefficients = vec![0.0];
+        coefficients.extend(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .map(|(i, c)| c / (i + 1) as f64),
+        );
+        Self::new(coefficients)
+    }
+
+    /// Evaluates at a complex number with Horner's method.
+    pub fn evaluate(&self, z: Complex) -> Complex {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Complex::real(0.0), |sum, c| sum * z + Complex::real(*c))
+    }
+
+    /// The monic greatest common divisor, found with Euclid's algorithm.
+    /// Remainders that are tiny compared to the polynomials count as zero.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let size = |p: &Self| {
+            p.coefficients
+                .iter()
+                .fold(0.0, |max: f64, c| max.max(c.abs()))
+        };
+        let tolerance = 1e-9 * size(self).max(size(other));
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() && size(&b) > tolerance {
+            let (_, remainder) = a.div_rem(&b).expect("The divisor is not zero");
+            (a, b) = (b, remainder);
+        }
+        if a.is_zero() {
+            a
+        } else {
+            a.scale(1.0 / a.leading())
+        }
+    }
+
+    /// All complex roots, with multiplicity, sorted by their real and
+    /// then imaginary parts. They are the eigenvalues of the companion
+    /// matrix, refined with a few steps of Newton's method.
+    pub fn roots(&self) -> Result<Vec<Complex>, String> {
+        if self.is_zero() {
+            return Err("roots() of the zero polynomial are every number".into());
+        }
+        let n = self.degree();
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let mut rows = vec![vec![0.0; n]; n];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[n - 1] = -self.coefficients[i] / self.leading();
+            if i > 0 {
+                row[i - 1] = 1.0;
+            }
+        }
+        let derivative = self.derivative();
+        let mut roots = Matrix::from_rows(rows)?.eigenvalues()?;
+        for root in roots.iter_mut() {
+            for _ in 0..POLISH_STEPS {
+                let slope = derivative.evaluate(*root);
+                if slope.abs() == 0.0 {
+                    break;
+                }
+                let step = self.evaluate(*root) / slope;
+                if !step.re.is_finite() || !step.im.is_finite() {
+                    break;
+                }
+                *root = *root - step;
+            }
+            // Real roots found as complex ones keep a little noise.
+            if root.im.abs() <= 1e-12 * root.abs().max(1.0) {
+                root.im = 0.0;
+            }
+        }
+        roots.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        Ok(roots)
+    }
+
+    /// The polynomial of at most the given degree that fits the points
+    /// best in the least-squares sense, found by QR decomposition.
+    pub fn fit(xs: &[f64], ys: &[f64], degree: usize) -> Result<Self, String> {
+        if xs.len() != ys.len() {
+            return Err(format!(
+                "polyfit() expects as many x values as y values, found {} and {}",
+                xs.len(),
+                ys.len()
+            ));
+        }
+        let columns = degree + 1;
+        if xs.len() < columns {
+            return Err(format!(
+                "polyfit() needs at least {columns} points for degree {degree}, found {}",
+                xs.len()
+            ));
+        }
+
+        // The Vandermonde matrix, with a column for each power of x.
+        let mut a: Vec<Vec<f64>> = xs
+            .iter()
+            .map(|x| (0..columns).map(|k| x.powi(k as i32)).collect())
+            .collect();
+        let mut b = ys.to_vec();
+
+        // Householder reflections turn `a` upper triangular, and are applied to `b` as well.
+        for k in 0..columns {
+            let norm = a[k..].iter().map(|row| row[k] * row[k]).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if a[k][k] > 0.0 { -norm } else { norm };
+            let mut v: Vec<f64> = a[k..].iter().map(|row| row[k]).collect();
+            v[0] -= alpha;
+            let v_norm = v.iter().map(|x| x * x).sum::<f64>();
+            if v_norm == 0.0 {
+                continue;
+            }
+            for j in k..columns {
+                let dot = v
+                    .iter()
+                    .zip(&a[k..])
+                    .map(|(v, row)| v * row[j])
+                    .sum::<f64>();
+                for (v, row) in v.iter().zip(a[k..].iter_mut()) {
+                    row[j] -= 2.0 * dot / v_norm * v;
+                }
+            }
+            let dot = v.iter().zip(&b[k..]).map(|(v, b)| v * b).sum::<f64>();
+            for (v, b) in v.iter().zip(b[k..].iter_mut()) {
+                *b -= 2.0 * dot / v_norm * v;
+            }
+        }
+
+        // Back substitution.
+        let scale = a[0][0].abs().max(1.0);
+        let mut coefficients = vec![0.0; columns];
+        for k in (0..columns).rev() {
+            if a[k][k].abs() <= 1e-12 * scale {
+                return Err(format!(
+                    "polyfit() needs at least {columns} distinct x values for degree {degree}"
+                ));
+            }
+            let sum = (k + 1..columns)
+                .map(|j| a[k][j] * coefficients[j])
+                .sum::<f64>();
+            coefficients[k] = (b[k] - sum) / a[k][k];
+        }
+        Ok(Self::new(coefficients))
+    }
+}
+
+impl Display for Polynomial {
+    /// Writes the polynomial in standard notation, like `x^2 - 3x + 2`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (power, c) in self.coefficients.iter().enumerate().rev() {
+            if *c == 0.0 {
+                continue;
+            }
+            let sign = if c.is_sign_negative() { "-" } else { "+" };
+            match (first, sign) {
+                (true, "-") => write!(f, "-")?,
+                (true, _) => {}
+                (false, sign) => write!(f, " {sign} ")?,
+            }
+            first = false;
+            let size = c.abs();
+            if size != 1.0 || power == 0 {
+                write!(f, "{size}")?;
+            }
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{power}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `x^2 - 3x + 2`, which is `(x - 1)(x - 2)`.
+    fn quadratic() -> Polynomial {
+        Polynomial::new(vec![2.0, -3.0, 1.0])
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(quadratic().to_string(), "x^2 - 3x + 2");
+        assert_eq!(
+            Polynomial::new(vec![0.0, -1.0, 0.0, 0.5]).to_string(),
+            "0.5x^3 - x"
+        );
+        assert_eq!(Polynomial::new(vec![0.0, 0.0]).to_string(), "0");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let p = quadratic();
+        let q = Polynomial::new(vec![-1.0, 1.0]);
+        assert_eq!(p.mul(&q).to_string(), "x^3 - 4x^2 + 5x - 2");
+        assert_eq!(p.sub(&p), Polynomial::new(vec![]));
+        let (quotient, remainder) = p.add(&Polynomial::constant(1.0)).div_rem(&q).unwrap();
+        assert_eq!(quotient.to_string(), "x - 2");
+        assert_eq!(remainder.to_string(), "1");
+        assert_eq!(p.derivative().to_string(), "2x - 3");
+        assert_eq!(p.derivative().integral().to_string(), "x^2 - 3x");
+        assert_eq!(p.gcd(&q.mul(&q)).to_string(), "x - 1");
+    }
+
+    #[test]
+    fn test_roots() {
+        assert_eq!(
+            quadratic().roots().unwrap(),
+            vec![Complex::real(1.0), Complex::real(2.0)]
+        );
+        let roots = Polynomial::new(vec![1.0, 0.0, 1.0]).roots().unwrap();
+        assert_eq!(roots, vec![Complex::new(0.0, -1.0), Complex::new(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_fit() {
+        let xs = [0.0, 1.0, 2.0, 3.0];
+        let ys: Vec<f64> = xs.iter().map(|x| 2.0 * x * x - x + 1.0).collect();
+        let fitted = Polynomial::fit(&xs, &ys, 2).unwrap();
+        for (actual, expected) in fitted.coefficients().iter().zip([1.0, -1.0, 2.0]) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+        assert!(Polynomial::fit(&[1.0, 1.0], &[2.0, 3.0], 1).is_err());
+    }
+}
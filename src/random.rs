@@ -0,0 +1,257 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A pseudorandom number generator, xoshiro256**, whose sequence is fixed by its seed.
+/// It is fast and statistically sound, but not for cryptography.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+/// The increment of splitmix64, the golden ratio as a 64-bit fraction.
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Below this mean the Poisson sampler multiplies uniforms, one per event.
+const SMALL_MEAN: f64 = 16.0;
+
+/// Below this many trials the binomial sampler counts each one.
+const SMALL_TRIALS: u64 = 32;
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: [0; 4] };
+        rng.seed(seed);
+        rng
+    }
+
+    /// A generator seeded from the clock, different on each run.
+    pub fn from_clock() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    /// Restarts the sequence. The state is filled by splitmix64, which spreads
+    /// similar seeds apart and never leaves it all zero.
+    pub fn seed(&mut self, seed: u64) {
+        let mut x = seed;
+        for word in &mut self.state {
+            x = x.wrapping_add(GOLDEN_GAMMA);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *word = z ^ (z >> 31);
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// A uniform number in `[0, 1)`, from the top 53 bits.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A uniform integer in `[0, n)`, without the bias of taking a remainder.
+    /// Products whose low half falls in the first `2^64 mod n` values are redrawn.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "below() needs a positive bound");
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let product = self.next_u64() as u128 * n as u128;
+            if product as u64 >= threshold {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+
+    /// A uniform integer in `[low, high]`.
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        let span = high.wrapping_sub(low) as u64;
+        let offset = match span.checked_add(1) {
+            Some(n) => self.below(n),
+            None => self.next_u64(),
+        };
+        low.wrapping_add(offset as i64)
+    }
+
+    /// A standard normal number, by Marsaglia's polar method.
+    pub fn normal(&mut self) -> f64 {
+        loop {
+            let u = 2.0 * self.next_f64() - 1.0;
+            let v = 2.0 * self.next_f64() - 1.0;
+            let s = u * u + v * v;
+            if s > 0.0 && s < 1.0 {
+                return u * (-2.0 * s.ln() / s).sqrt();
+            }
+        }
+    }
+
+    /// An exponential number with mean 1.
+    pub fn exponential(&mut self) -> f64 {
+        // `1 - U` is in `(0, 1]`, so its logarithm is finite.
+        -(1.0 - self.next_f64()).ln()
+    }
+
+    /// A gamma number with the given positive shape and scale 1,
+    /// by the method of Marsaglia and Tsang.
+    pub fn gamma(&mut self, shape: f64) -> f64 {
+        if shape < 1.0 {
+            // Boost the shape past 1, then scale back down.
+            let u = 1.0 - self.next_f64();
+            return self.gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.normal();
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = 1.0 - self.next_f64();
+            if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+
+    /// A beta number with the given positive shapes.
+    pub fn beta(&mut self, a: f64, b: f64) -> f64 {
+        let x = self.gamma(a);
+        let y = self.gamma(b);
+        x / (x + y)
+    }
+
+    /// The number of events in a Poisson process with the given mean.
+    pub fn poisson(&mut self, mean: f64) -> u64 {
+        if mean < SMALL_MEAN {
+            // Multiply uniforms until the product drops below `exp(-mean)`.
+            let limit = (-mean).exp();
+            let (mut count, mut product) = (0, self.next_f64());
+            while product > limit {
+                count += 1;
+                product *= self.next_f64();
+            }
+            return count;
+        }
+        // The `m`th event of a unit-rate process arrives at a gamma time.
+        // Whether it comes before `mean` splits the count into smaller problems
+        // (Knuth, TAOCP 3.4.1).
+        let m = (mean * 7.0 / 8.0).floor() as u64;
+        let arrival = self.gamma(m as f64);
+        if arrival < mean {
+            m + self.poisson(mean - arrival)
+        } else {
+            self.binomial(m - 1, mean / arrival)
+        }
+    }
+
+    /// The number of successes in `trials` independent trials with probability `p`.
+    pub fn binomial(&mut self, trials: u64, p: f64) -> u64 {
+        if p <= 0.0 {
+            return 0;
+        }
+        if p >= 1.0 {
+            return trials;
+        }
+        if trials < SMALL_TRIALS {
+            return (0..trials).filter(|_| self.next_f64() < p).count() as u64;
+        }
+        // The `a`th smallest of `trials` uniforms has a beta distribution.
+        // Which side of `p` it falls on splits the count into smaller problems
+        // (Knuth, TAOCP 3.4.1).
+        let a = 1 + trials / 2;
+        let b = trials + 1 - a;
+        let x = self.beta(a as f64, b as f64);
+        if x >= p {
+            self.binomial(a - 1, p / x)
+        } else {
+            a + self.binomial(b - 1, (p - x) / (1.0 - x))
+        }
+    }
+
+    /// Shuffles in place, by Fisher and Yates's method.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_mean(samples: impl Iterator<Item = f64>, n: usize) -> f64 {
+        samples.take(n).sum::<f64>() / n as f64
+    }
+
+    #[test]
+    fn test_seed_fixes_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        a.seed(42);
+        assert_eq!(a.next_u64(), first[0]);
+        assert_ne!(Rng::new(43).next_u64(), first[0]);
+    }
+
+    #[test]
+    fn test_uniform_ranges() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!(rng.below(3) < 3);
+            assert!((-2..=2).contains(&rng.between(-2, 2)));
+        }
+        let mut counts = [0; 6];
+        for _ in 0..60000 {
+            counts[rng.below(6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| (9500..10500).contains(&count)));
+        // The whole range does not overflow.
+        rng.between(i64::MIN, i64::MAX);
+    }
+
+    #[test]
+    fn test_distribution_means() {
+        let mut rng = Rng::new(7);
+        let n = 20000;
+        let normal = sample_mean(std::iter::repeat_with(|| rng.normal()), n);
+        assert!(normal.abs() < 0.05);
+        let exponential = sample_mean(std::iter::repeat_with(|| rng.exponential()), n);
+        assert!((exponential - 1.0).abs() < 0.05);
+        let gamma = sample_mean(std::iter::repeat_with(|| rng.gamma(0.5)), n);
+        assert!((gamma - 0.5).abs() < 0.05);
+        for mean in [3.0, 100.0] {
+            let poisson = sample_mean(std::iter::repeat_with(|| rng.poisson(mean) as f64), n);
+            assert!((poisson / mean - 1.0).abs() < 0.02, "{poisson}");
+        }
+        let binomial = sample_mean(std::iter::repeat_with(|| rng.binomial(1000, 0.3) as f64), n);
+        assert!((binomial - 300.0).abs() < 1.0, "{binomial}");
+    }
+
+    #[test]
+    fn test_shuffle_permutes() {
+        let mut rng = Rng::new(3);
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use crate::bigint::BigInt;
+
+/// Exact powers with more bits than this are refused, rather than
+/// spending minutes computing them.
+const MAX_POWER_BITS: u64 = 1 << 22;
+
+/// An exact fraction of two integers of any size.
+///
+/// Fractions are always in lowest terms with a positive denominator,
+/// so equal values have equal representations.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Result<Self, String> {
+        if denominator.is_zero() {
+            return Err("Division by zero".into());
+        }
+        let divisor = numerator.gcd(&denominator);
+        let divisor = if denominator.is_negative() {
+            -&divisor
+        } else {
+            divisor
+        };
+        let (numerator, _) = numerator.div_rem(&divisor).expect("gcd is not zero");
+        let (denominator, _) = denominator.div_rem(&divisor).expect("gcd is not zero");
+        Ok(Self {
+            numerator,
+            denominator,
+        })
+    }
+
+    pub fn from_integer(value: BigInt) -> Self {
+        Self {
+            numerator: value,
+            denominator: BigInt::one(),
+        }
+    }
+
+    /// Parses a decimal like `12`, `0.1` or `2.50` exactly.
+    pub fn parse_decimal(text: &str) -> Option<Self> {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let numerator = BigInt::parse(&format!("{whole}{fraction}"))?;
+        let denominator = BigInt::from(10).pow(fraction.len() as u32);
+        Self::new(numerator, denominator).ok()
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator.is_one()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let numerator =
+            &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator);
+        Self::new(numerator, &self.denominator * &other.denominator)
+            .expect("Denominators are not zero")
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::new(
+            &self.numerator * &other.numerator,
+            &self.denominator * &other.denominator,
+        )
+        .expect("Denominators are not zero")
+    }
+
+    pub fn div(&self, other: &Self) -> Result<Self, String> {
+        Self::new(
+            &self.numerator * &other.denominator,
+            &self.denominator * &other.numerator,
+        )
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            numerator: -&self.numerator,
+            denominator: self.denominator.clone(),
+        }
+    }
+
+    /// The largest integer not greater than the fraction.
+    pub fn floor(&self) -> BigInt {
+        let (quotient, _) = self
+            .numerator
+            .div_mod_floor(&self.denominator)
+            .expect("Denominators are not zero");
+        quotient
+    }
+
+    /// Raises to an integer power, which may be negative.
+    pub fn pow(&self, exponent: i64) -> Result<Self, String> {
+        let too_large = || format!("Exponent {exponent} is too large for an exact power");
+        let power = u32::try_from(exponent.unsigned_abs()).map_err(|_| too_large())?;
+        let bits = self.numerator.bits().max(self.denominator.bits());
+        if (bits - 1).saturating_mul(power as u64) > MAX_POWER_BITS {
+            return Err(too_large());
+        }
+        let result = Self {
+            numerator: self.numerator.pow(power),
+            denominator: self.denominator.pow(power),
+        };
+        if exponent < 0 {
+            Self::from_integer(BigInt::one()).div(&result)
+        } else {
+            Ok(result)
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let numerator = self.numerator.to_f64();
+        let denominator = self.denominator.to_f64();
+        if numerator.is_finite() && denominator.is_finite() {
+            return numerator / denominator;
+        }
+
+        // Scale both parts down so they fit in a float, keeping 64 bits of precision.
+        let shift = self.numerator.bits().max(self.denominator.bits()) - 64;
+        let scale = BigInt::from(2).pow(shift as u32);
+        let (numerator, _) = self.numerator.div_rem(&scale).expect("scale is not zero");
+        let (denominator, _) = self.denominator.div_rem(&scale).expect("scale is not zero");
+        numerator.to_f64() / denominator.to_f64()
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fraction(numerator: i64, denominator: i64) -> Rational {
+        Rational::new(BigInt::from(numerator), BigInt::from(denominator)).unwrap()
+    }
+
+    #[test]
+    fn test_lowest_terms() {
+        assert_eq!(fraction(6, -8).to_string(), "-3/4");
+        assert_eq!(fraction(10, 5).to_string(), "2");
+        assert!(Rational::new(BigInt::one(), BigInt::zero()).is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(Rational::parse_decimal("0.1"), Some(fraction(1, 10)));
+        assert_eq!(Rational::parse_decimal("2.50"), Some(fraction(5, 2)));
+        assert_eq!(Rational::parse_decimal("7"), Some(fraction(7, 1)));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let third = fraction(1, 3);
+        let sixth = fraction(1, 6);
+        assert_eq!(third.add(&sixth), fraction(1, 2));
+        assert_eq!(third.sub(&sixth), sixth);
+        assert_eq!(third.mul(&sixth), fraction(1, 18));
+        assert_eq!(third.div(&sixth), Ok(fraction(2, 1)));
+        assert_eq!(third.pow(-2), Ok(fraction(9, 1)));
+        assert_eq!(fraction(-7, 2).floor(), BigInt::from(-4));
+        assert!(third < fraction(1, 2));
+    }
+
+    #[test]
+    fn test_to_f64_of_huge_parts() {
+        let huge = BigInt::from(10).pow(400);
+        let value = Rational::new(&huge + &BigInt::one(), &huge * &BigInt::from(3)).unwrap();
+        assert!((value.to_f64() - 1.0 / 3.0).abs() < 1e-15);
+    }
+}
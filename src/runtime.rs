@@ -3,54 +3,299 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use std::rc::Rc;
 
-use crate::ast::Expr;
+use crate::ast::{self, Expr, TypeAnnotation};
+use crate::bigint::BigInt;
 use crate::builtins;
+use crate::complex::Complex;
+use crate::dual::Dual;
+use crate::interval::Interval;
+use crate::matrix::Matrix;
+use crate::memo::Memo;
+use crate::polynomial::Polynomial;
+use crate::random::Rng;
+use crate::rational::Rational;
+use crate::stream::Stream;
+use crate::symbolic::Symbolic;
+use crate::units::Quantity;
+
+/// The signature shared by every native function body.
+pub type NativeFn = dyn Fn(RuntimeValue) -> Result<RuntimeValue, String>;
+
+/// A native function that can capture state, such as the arguments already
+/// passed to a curried builtin.
+#[derive(Clone)]
+pub struct NativeClosure(Rc<NativeFn>);
+
+impl NativeClosure {
+    pub fn call(&self, arg: RuntimeValue) -> Result<RuntimeValue, String> {
+        (self.0)(arg)
+    }
+}
+
+impl std::fmt::Debug for NativeClosure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NativeClosure")
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum RuntimeValue {
     Number(f64),
+    Integer(BigInt),
+    Rational(Rational),
+    Complex(Complex),
+    /// A number carrying its derivative, while `grad` evaluates a function.
+    Dual(Dual),
+    /// An uncertain number within bounds, like `[1.9 .. 2.1]`.
+    Interval(Interval),
+    Quantity(Quantity),
+    Matrix(Matrix),
+    /// A polynomial in `x`, which can be called to evaluate it.
+    Polynomial(Polynomial),
+    Symbolic(Symbolic),
     String(String),
     Boolean(bool),
     Function {
         arg_name: String,
+        param_type: Option<TypeAnnotation>,
         body: Expr,
+        return_type: Option<TypeAnnotation>,
         closure: Rc<RefCell<Environment>>,
     },
     NativeFunction {
         name: String,
         function: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+        /// Whether a call reads a formula in an undefined variable as written,
+        /// like `poly(x^2 - 3x + 2)`, instead of evaluating it.
+        reads_formula: bool,
     },
+    NativeClosure {
+        name: String,
+        function: NativeClosure,
+    },
+    Memoized(Memo),
     List {
         elements: Vec<RuntimeValue>,
     },
+    Stream(Stream),
     Nil,
 }
 
 impl RuntimeValue {
+    /// Creates a native function value from a Rust closure.
+    pub fn native_closure(
+        name: &str,
+        function: impl Fn(RuntimeValue) -> Result<RuntimeValue, String> + 'static,
+    ) -> Self {
+        Self::NativeClosure {
+            name: name.into(),
+            function: NativeClosure(Rc::new(function)),
+        }
+    }
+
+    /// Wraps a quantity, which is a plain number if its units cancel out.
+    pub fn from_quantity(quantity: Quantity) -> Self {
+        if quantity.dimension().is_none() {
+            Self::Number(quantity.magnitude())
+        } else {
+            Self::Quantity(quantity)
+        }
+    }
+
+    /// Wraps an exact fraction, which is an integer if its denominator is 1.
+    pub fn from_rational(rational: Rational) -> Self {
+        if rational.is_integer() {
+            Self::Integer(rational.numerator().clone())
+        } else {
+            Self::Rational(rational)
+        }
+    }
+
+    /// Returns the value as a float, if it is a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            Self::Integer(n) => Some(n.to_f64()),
+            Self::Rational(r) => Some(r.to_f64()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a fraction, if it is an exact number.
+    pub fn as_rational(&self) -> Option<Rational> {
+        match self {
+            Self::Integer(n) => Some(Rational::from_integer(n.clone())),
+            Self::Rational(r) => Some(r.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a complex number, if it is a number.
+    pub fn as_complex(&self) -> Option<Complex> {
+        match self {
+            Self::Complex(c) => Some(*c),
+            _ => self.as_f64().map(Complex::real),
+        }
+    }
+
+    /// Returns the value as a dual number, if it is one or a real number,
+    /// which is a constant.
+    pub fn as_dual(&self) -> Option<Dual> {
+        match self {
+            Self::Dual(d) => Some(*d),
+            _ => self.as_f64().map(Dual::constant),
+        }
+    }
+
+    /// Returns the value as an interval, if it is an interval or a real number.
+    /// Exact numbers that floats cannot hold are widened to contain them.
+    pub fn as_interval(&self) -> Option<Interval> {
+        match self {
+            Self::Interval(i) => Some(*i),
+            Self::Number(n) => Some(Interval::point(*n)),
+            Self::Integer(n) if n.to_i64().is_some_and(|n| n.abs() <= 1 << 53) => {
+                Some(Interval::point(n.to_f64()))
+            }
+            Self::Integer(_) | Self::Rational(_) => {
+                let x = self.as_f64()?;
+                Some(Interval {
+                    low: x.next_down(),
+                    high: x.next_up(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the interval sure to contain a bound written like `0.1` in `[0.1 .. 1]`.
+    /// A float is read as the decimal it prints as, and widened when no float holds it.
+    pub fn as_interval_bound(&self) -> Option<Interval> {
+        match self {
+            Self::Number(n) if !is_exact_decimal(*n) => Some(Interval {
+                low: n.next_down(),
+                high: n.next_up(),
+            }),
+            Self::Interval(_) => None,
+            _ => self.as_interval(),
+        }
+    }
+
+    /// Compares two numbers of any kind, exactly unless one is a float.
+    /// Complex numbers can only be equal or unordered.
+    pub(crate) fn compare_numbers(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if matches!(self, Self::Complex(_)) || matches!(other, Self::Complex(_)) {
+            return (self.as_complex()? == other.as_complex()?)
+                .then_some(std::cmp::Ordering::Equal);
+        }
+        match (self.as_rational(), other.as_rational()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => self.as_f64()?.partial_cmp(&other.as_f64()?),
+        }
+    }
+
+    /// Returns true if the value can be called with an argument.
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            self,
+            Self::Function { .. }
+                | Self::NativeFunction { .. }
+                | Self::NativeClosure { .. }
+                | Self::Memoized(_)
+                | Self::Polynomial(_)
+        )
+    }
+
+    /// Returns true if the value satisfies a type annotation.
+    pub fn has_type(&self, annotation: &TypeAnnotation) -> bool {
+        match (annotation, self) {
+            (TypeAnnotation::Any, _) => true,
+            (
+                TypeAnnotation::Number,
+                Self::Number(_)
+                | Self::Integer(_)
+                | Self::Rational(_)
+                | Self::Complex(_)
+                | Self::Dual(_)
+                | Self::Interval(_),
+            ) => true,
+            (TypeAnnotation::String, Self::String(_)) => true,
+            (TypeAnnotation::Boolean, Self::Boolean(_)) => true,
+            (TypeAnnotation::Function, _) => self.is_callable(),
+            (TypeAnnotation::Stream, Self::Stream(_)) => true,
+            (TypeAnnotation::List(element), Self::List { elements }) => {
+                elements.iter().all(|value| value.has_type(element))
+            }
+            _ => false,
+        }
+    }
+
     /// Converts a RuntimeValue to a bool.
     pub fn is_truthy(&self) -> bool {
         match self {
             Self::Number(n) => *n != 0.0,
+            Self::Integer(n) => !n.is_zero(),
+            Self::Rational(r) => !r.is_zero(),
+            Self::Complex(c) => c.re != 0.0 || c.im != 0.0,
+            Self::Dual(d) => d.value != 0.0,
+            Self::Interval(i) => i.low != 0.0 || i.high != 0.0,
+            Self::Quantity(q) => q.magnitude() != 0.0,
+            Self::Matrix(_) => true,
+            Self::Polynomial(p) => !p.is_zero(),
+            Self::Symbolic(_) => true,
             Self::String(msg) => !msg.is_empty(),
             Self::Boolean(cond) => *cond,
             Self::Function { .. } => true,
             Self::NativeFunction { .. } => true,
+            Self::NativeClosure { .. } => true,
+            Self::Memoized(_) => true,
             Self::List { elements } => !elements.is_empty(),
+            // Streams are not forced just to check for emptiness.
+            Self::Stream(_) => true,
             Self::Nil => false,
         }
     }
 }
 
+/// Whether a float holds exactly the decimal it prints as, like `0.5` but not `0.1`.
+fn is_exact_decimal(x: f64) -> bool {
+    // Infinities and NaN print as words, and are exact.
+    let Some(decimal) = Rational::parse_decimal(&x.abs().to_string()) else {
+        return true;
+    };
+    // It is exact when its denominator and the factors of 2 in its numerator
+    // are the exponent of a float, and the rest fits in the mantissa.
+    let numerator = decimal.numerator();
+    let denominator = decimal.denominator();
+    let twos = numerator.gcd(&BigInt::from(2).pow(numerator.bits() as u32));
+    let (odd, _) = numerator.div_rem(&twos).expect("gcd is not zero");
+    BigInt::from(2).pow(denominator.bits() as u32 - 1) == *denominator
+        && odd.bits() <= f64::MANTISSA_DIGITS as u64
+}
+
 impl PartialEq for RuntimeValue {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::Number(a), Self::Number(b)) => a == b,
+            // Numbers of different kinds are equal if their values are.
+            (
+                Self::Number(_) | Self::Integer(_) | Self::Rational(_) | Self::Complex(_),
+                Self::Number(_) | Self::Integer(_) | Self::Rational(_) | Self::Complex(_),
+            ) => self.compare_numbers(other) == Some(std::cmp::Ordering::Equal),
+            (Self::Dual(a), Self::Dual(b)) => a == b,
+            (Self::Interval(a), Self::Interval(b)) => a == b,
+            (Self::Quantity(a), Self::Quantity(b)) => a == b,
+            (Self::Matrix(a), Self::Matrix(b)) => a == b,
+            (Self::Polynomial(a), Self::Polynomial(b)) => a == b,
+            (Self::Symbolic(a), Self::Symbolic(b)) => a == b,
             (Self::String(a), Self::String(b)) => a == b,
             (Self::Boolean(a), Self::Boolean(b)) => a == b,
             // Functions cannot be compared.
             (Self::Function { .. }, Self::Function { .. }) => false,
             (Self::NativeFunction { .. }, Self::NativeFunction { .. }) => false,
+            (Self::NativeClosure { .. }, Self::NativeClosure { .. }) => false,
+            (Self::Memoized(_), Self::Memoized(_)) => false,
             (Self::List { elements: a }, Self::List { elements: b }) => a == b,
+            // Streams may be infinite, so they cannot be compared.
+            (Self::Stream(_), Self::Stream(_)) => false,
             (Self::Nil, Self::Nil) => true,
             // Two different types are never equal.
             _ => false,
@@ -61,13 +306,21 @@ impl PartialEq for RuntimeValue {
 impl PartialOrd for RuntimeValue {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
-            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
+            (
+                Self::Number(_) | Self::Integer(_) | Self::Rational(_) | Self::Complex(_),
+                Self::Number(_) | Self::Integer(_) | Self::Rational(_) | Self::Complex(_),
+            ) => self.compare_numbers(other),
+            // Quantities of different dimensions cannot be compared.
+            (Self::Quantity(a), Self::Quantity(b)) => a.partial_cmp(b),
             (Self::String(a), Self::String(b)) => a.partial_cmp(b),
             (Self::Boolean(a), Self::Boolean(b)) => a.partial_cmp(b),
             // Functions cannot be compared.
             (Self::Function { .. }, Self::Function { .. }) => None,
             (Self::NativeFunction { .. }, Self::NativeFunction { .. }) => None,
+            (Self::NativeClosure { .. }, Self::NativeClosure { .. }) => None,
+            (Self::Memoized(_), Self::Memoized(_)) => None,
             (Self::List { .. }, Self::List { .. }) => None,
+            (Self::Stream(_), Self::Stream(_)) => None,
             // Allow nil checking.
             (Self::Nil, Self::Nil) => Some(std::cmp::Ordering::Equal),
             // Two different types cannot be compared.
@@ -80,6 +333,15 @@ impl Display for RuntimeValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Number(n) => write!(f, "{n}"),
+            Self::Integer(n) => write!(f, "{n}"),
+            Self::Complex(c) => write!(f, "{c}"),
+            Self::Rational(r) => write!(f, "{r}"),
+            Self::Dual(d) => write!(f, "{d}"),
+            Self::Interval(i) => write!(f, "{i}"),
+            Self::Quantity(q) => write!(f, "{q}"),
+            Self::Matrix(m) => write!(f, "{m}"),
+            Self::Polynomial(p) => write!(f, "{p}"),
+            Self::Symbolic(s) => write!(f, "{s}"),
             Self::String(msg) => write!(f, "\"{msg}\""),
             Self::Boolean(cond) => {
                 if *cond {
@@ -90,12 +352,18 @@ impl Display for RuntimeValue {
             }
             Self::Function {
                 arg_name,
-                body: _,
-                closure: _,
-            } => write!(f, "<function in {arg_name}>"),
-            Self::NativeFunction { name, function: _ } => {
+                param_type,
+                body,
+                return_type,
+                ..
+            } => ast::write_function(f, arg_name, param_type.as_ref(), body, return_type.as_ref()),
+            Self::NativeFunction { name, .. } => {
+                write!(f, "<native function {name}>")
+            }
+            Self::NativeClosure { name, function: _ } => {
                 write!(f, "<native function {name}>")
             }
+            Self::Memoized(memo) => write!(f, "<memoized {}>", memo.function()),
             Self::List { elements } => {
                 write!(f, "[")?;
                 for i in 0..elements.len() {
@@ -106,6 +374,7 @@ impl Display for RuntimeValue {
                 }
                 write!(f, "]")
             }
+            Self::Stream(_) => write!(f, "<stream>"),
             Self::Nil => write!(f, "nil"),
         }
     }
@@ -115,6 +384,8 @@ impl Display for RuntimeValue {
 struct Binding {
     value: RuntimeValue,
     is_constant: bool,
+    /// The type that values assigned to the binding must have.
+    annotation: Option<TypeAnnotation>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -138,19 +409,187 @@ impl Environment {
         env.bind_const(String::from("nil"), RuntimeValue::Nil);
         env.bind_const(String::from("true"), RuntimeValue::Boolean(true));
         env.bind_const(String::from("false"), RuntimeValue::Boolean(false));
+        env.bind_const(
+            String::from("pi"),
+            RuntimeValue::Number(std::f64::consts::PI),
+        );
+        env.bind_const(String::from("e"), RuntimeValue::Number(std::f64::consts::E));
+        env.bind_const(
+            String::from("tau"),
+            RuntimeValue::Number(std::f64::consts::TAU),
+        );
+        env.bind_const(String::from("inf"), RuntimeValue::Number(f64::INFINITY));
+        env.bind_const(String::from("nan"), RuntimeValue::Number(f64::NAN));
 
         env.bind_native_fn("sin", builtins::sin);
         env.bind_native_fn("cos", builtins::cos);
         env.bind_native_fn("sqrt", builtins::sqrt);
+        env.bind_native_fn("exp", builtins::exp);
+        env.bind_native_fn("ln", builtins::ln);
+        env.bind_native_fn("abs", builtins::abs);
+        env.bind_native_fn("tan", builtins::tan);
+        env.bind_native_fn("asin", builtins::asin);
+        env.bind_native_fn("acos", builtins::acos);
+        env.bind_native_fn("atan", builtins::atan);
+        env.bind_native_fn("atan2", builtins::atan2);
+        env.bind_native_fn("sinh", builtins::sinh);
+        env.bind_native_fn("cosh", builtins::cosh);
+        env.bind_native_fn("tanh", builtins::tanh);
+        env.bind_native_fn("asinh", builtins::asinh);
+        env.bind_native_fn("acosh", builtins::acosh);
+        env.bind_native_fn("atanh", builtins::atanh);
+        env.bind_native_fn("log10", builtins::log10);
+        env.bind_native_fn("log2", builtins::log2);
+        env.bind_native_fn("log1p", builtins::log1p);
+        env.bind_native_fn("expm1", builtins::expm1);
+        env.bind_native_fn("exp2", builtins::exp2);
+        env.bind_native_fn("cbrt", builtins::cbrt);
+        env.bind_native_fn("hypot", builtins::hypot);
+        env.bind_native_fn("floor", builtins::floor);
+        env.bind_native_fn("ceil", builtins::ceil);
+        env.bind_native_fn("round", builtins::round);
+        env.bind_native_fn("trunc", builtins::trunc);
+        env.bind_native_fn("fract", builtins::fract);
+        env.bind_native_fn("sign", builtins::sign);
+        env.bind_native_fn("min", builtins::min);
+        env.bind_native_fn("max", builtins::max);
+        env.bind_native_fn("arg", builtins::arg);
+        env.bind_native_fn("conj", builtins::conj);
+        env.bind_native_fn("re", builtins::re);
+        env.bind_native_fn("im", builtins::im);
         env.bind_native_fn("clock", builtins::clock);
         env.bind_native_fn("bool", builtins::bool);
         env.bind_native_fn("str", builtins::str);
         env.bind_native_fn("print", builtins::print);
         env.bind_native_fn("println", builtins::println);
 
+        env.bind_native_fn("iterate", builtins::iterate);
+        env.bind_native_fn("repeat", builtins::repeat);
+        env.bind_native_fn("cycle", builtins::cycle);
+        env.bind_native_fn("unfold", builtins::unfold);
+        env.bind_native_fn("map", builtins::map);
+        env.bind_native_fn("filter", builtins::filter);
+        env.bind_native_fn("take_while", builtins::take_while);
+        env.bind_native_fn("take", builtins::take);
+
+        env.bind_native_fn("len", builtins::len);
+        env.bind_native_fn("head", builtins::head);
+        env.bind_native_fn("tail", builtins::tail);
+        env.bind_native_fn("foldl", builtins::foldl);
+        env.bind_native_fn("foldr", builtins::foldr);
+        env.bind_native_fn("zip", builtins::zip);
+        env.bind_native_fn("enumerate", builtins::enumerate);
+        env.bind_native_fn("reverse", builtins::reverse);
+        env.bind_native_fn("sort", builtins::sort);
+        env.bind_native_fn("sort_by", builtins::sort_by);
+        env.bind_native_fn("any", builtins::any);
+        env.bind_native_fn("all", builtins::all);
+        env.bind_native_fn("find", builtins::find);
+        env.bind_native_fn("flatten", builtins::flatten);
+        env.bind_native_fn("range", builtins::range);
+
+        env.bind_native_fn("memo", builtins::memo);
+        env.bind_native_fn("memo_bounded", builtins::memo_bounded);
+        env.bind_native_fn("memo_clear", builtins::memo_clear);
+        env.bind_native_fn("memo_size", builtins::memo_size);
+
+        env.bind_native_fn("to", builtins::to);
+        env.bind_native_fn("diff", builtins::diff);
+        env.bind_native_fn("grad", builtins::grad);
+        env.bind_native_fn("jacobian", builtins::jacobian);
+
+        env.bind_native_fn("matrix", builtins::matrix);
+        env.bind_native_fn("shape", builtins::shape);
+        env.bind_native_fn("identity", builtins::identity);
+        env.bind_native_fn("transpose", builtins::transpose);
+        env.bind_native_fn("det", builtins::det);
+        env.bind_native_fn("inverse", builtins::inverse);
+        env.bind_native_fn("solve", builtins::solve);
+        env.bind_native_fn("eigenvalues", builtins::eigenvalues);
+
+        env.bind_native_fn("interval", builtins::interval);
+        env.bind_native_fn("width", builtins::width);
+        env.bind_native_fn("mid", builtins::mid);
+        env.bind_native_fn("contains", builtins::contains);
+
+        env.bind_formula_fn("poly", builtins::poly);
+        env.bind_native_fn("coeffs", builtins::coeffs);
+        env.bind_native_fn("degree", builtins::degree);
+        env.bind_native_fn("integral", builtins::integral);
+        env.bind_native_fn("roots", builtins::roots);
+        env.bind_native_fn("real_roots", builtins::real_roots);
+        env.bind_native_fn("polygcd", builtins::polygcd);
+        env.bind_native_fn("polyfit", builtins::polyfit);
+
+        env.bind_native_fn("mean", builtins::mean);
+        env.bind_native_fn("median", builtins::median);
+        env.bind_native_fn("mode", builtins::mode);
+        env.bind_native_fn("variance", builtins::variance);
+        env.bind_native_fn("pvariance", builtins::pvariance);
+        env.bind_native_fn("stddev", builtins::stddev);
+        env.bind_native_fn("pstddev", builtins::pstddev);
+        env.bind_native_fn("quantile", builtins::quantile);
+        env.bind_native_fn("covariance", builtins::covariance);
+        env.bind_native_fn("pcovariance", builtins::pcovariance);
+        env.bind_native_fn("correlation", builtins::correlation);
+        env.bind_native_fn("histogram", builtins::histogram);
+        env.bind_native_fn("linear_regression", builtins::linear_regression);
+
+        env.bind_native_fn("gcd", builtins::gcd);
+        env.bind_native_fn("lcm", builtins::lcm);
+        env.bind_native_fn("factorial", builtins::factorial);
+        env.bind_native_fn("binomial", builtins::binomial);
+        env.bind_native_fn("is_prime", builtins::is_prime);
+        env.bind_native_fn("primes", builtins::primes);
+        env.bind_native_fn("factorize", builtins::factorize);
+        env.bind_native_fn("mod_pow", builtins::mod_pow);
+        env.bind_native_fn("mod_inverse", builtins::mod_inverse);
+        env.bind_native_fn("permutations", builtins::permutations);
+        env.bind_native_fn("combinations", builtins::combinations);
+        env.bind_native_fn("gamma", builtins::gamma);
+        env.bind_native_fn("beta", builtins::beta);
+
+        env.bind_native_fn("uniform_pdf", builtins::uniform_pdf);
+        env.bind_native_fn("uniform_cdf", builtins::uniform_cdf);
+        env.bind_native_fn("normal_pdf", builtins::normal_pdf);
+        env.bind_native_fn("normal_cdf", builtins::normal_cdf);
+        env.bind_native_fn("exponential_pdf", builtins::exponential_pdf);
+        env.bind_native_fn("exponential_cdf", builtins::exponential_cdf);
+        env.bind_native_fn("poisson_pmf", builtins::poisson_pmf);
+        env.bind_native_fn("poisson_cdf", builtins::poisson_cdf);
+        env.bind_native_fn("binomial_pmf", builtins::binomial_pmf);
+        env.bind_native_fn("binomial_cdf", builtins::binomial_cdf);
+
+        env.bind_native_fn("simplify", builtins::simplify);
+        env.bind_native_fn("expand", builtins::expand);
+        env.bind_native_fn("factor", builtins::factor);
+        env.bind_native_fn("substitute", builtins::substitute);
+        env.bind_native_fn("eval", builtins::eval);
+
+        env.bind_native_fn("integrate", builtins::integrate);
+        env.bind_native_fn("integrate_tol", builtins::integrate_tol);
+        env.bind_native_fn("root", builtins::root);
+        env.bind_native_fn("root_tol", builtins::root_tol);
+        env.bind_native_fn("newton", builtins::newton);
+        env.bind_native_fn("newton_tol", builtins::newton_tol);
+        env.bind_native_fn("minimize", builtins::minimize);
+        env.bind_native_fn("minimize_tol", builtins::minimize_tol);
+
+        env.bind_native_fn("solve_ode", builtins::solve_ode);
+        env.bind_native_fn("solve_ode_tol", builtins::solve_ode_tol);
+        env.bind_native_fn("solve_ode_rk4", builtins::solve_ode_rk4);
+        env.bind_native_fn("solve_ode_until", builtins::solve_ode_until);
+
         env
     }
 
+    /// Binds the builtins that draw random numbers from `rng`.
+    pub fn bind_random(&mut self, rng: &Rc<RefCell<Rng>>) {
+        for (name, value) in builtins::random_builtins(rng) {
+            self.bind_const(name.into(), value);
+        }
+    }
+
     fn bind_native_fn(
         &mut self,
         name: &str,
@@ -159,10 +598,31 @@ impl Environment {
         let value = RuntimeValue::NativeFunction {
             name: name.into(),
             function,
+            reads_formula: false,
         };
         self.bind_const(name.into(), value);
     }
 
+    /// Binds a native function whose argument may be a formula in an undefined
+    /// variable, which it receives unevaluated as a `Symbolic`.
+    fn bind_formula_fn(
+        &mut self,
+        name: &str,
+        function: fn(RuntimeValue) -> Result<RuntimeValue, String>,
+    ) {
+        let value = RuntimeValue::NativeFunction {
+            name: name.into(),
+            function,
+            reads_formula: true,
+        };
+        self.bind_const(name.into(), value);
+    }
+
+    /// The names bound directly in this scope.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.bindings.keys()
+    }
+
     pub fn with_parent(parent: Rc<RefCell<Environment>>) -> Environment {
         Environment {
             bindings: HashMap::new(),
@@ -176,11 +636,23 @@ impl Environment {
             Binding {
                 value,
                 is_constant: true,
+                annotation: None,
             },
         );
     }
 
     pub fn bind(&mut self, name: String, value: RuntimeValue) -> Result<(), String> {
+        self.bind_annotated(name, value, None)
+    }
+
+    /// Binds a variable whose later assignments must match `annotation`.
+    /// The initial value is checked by the caller, which knows if it is a parameter.
+    pub fn bind_annotated(
+        &mut self,
+        name: String,
+        value: RuntimeValue,
+        annotation: Option<TypeAnnotation>,
+    ) -> Result<(), String> {
         if self.bindings.contains_key(&name) {
             return Err(format!("Cannot redeclare variable '{name}'"));
         }
@@ -189,6 +661,7 @@ impl Environment {
             Binding {
                 value,
                 is_constant: false,
+                annotation,
             },
         );
         Ok(())
@@ -198,12 +671,20 @@ impl Environment {
         if let Some(binding) = self.bindings.get(&name) {
             if binding.is_constant {
                 Err(format!("Cannot modify constant variable '{name}'"))
+            } else if let Some(annotation) = &binding.annotation
+                && !value.has_type(annotation)
+            {
+                Err(format!(
+                    "Cannot assign {value} to '{name}', which expects {annotation}"
+                ))
             } else {
+                let annotation = binding.annotation.clone();
                 self.bindings.insert(
                     name,
                     Binding {
                         value,
                         is_constant: false,
+                        annotation,
                     },
                 );
                 Ok(())
@@ -241,6 +722,20 @@ mod tests {
         assert_eq!(env.resolve("nil"), Some(RuntimeValue::Nil));
     }
 
+    #[test]
+    fn test_random_builtins_are_listed() {
+        let rng = Rc::new(RefCell::new(Rng::new(0)));
+        let mut env = Environment::new();
+        env.bind_random(&rng);
+        for name in builtins::RANDOM_BUILTINS {
+            assert!(env.resolve(name).is_some(), "{name} should be bound");
+        }
+        assert_eq!(
+            builtins::random_builtins(&rng).len(),
+            builtins::RANDOM_BUILTINS.len()
+        );
+    }
+
     #[test]
     fn test_binding_and_resolving() {
         let mut env = Environment::new();
@@ -308,6 +803,7 @@ mod tests {
             RuntimeValue::NativeFunction {
                 name: "sqrt".into(),
                 function: |val| Ok(val),
+                reads_formula: false,
             }
         }
 
@@ -322,7 +818,9 @@ mod tests {
         fn make_fn() -> RuntimeValue {
             RuntimeValue::Function {
                 arg_name: "x".into(),
+                param_type: None,
                 body: Expr::Block { statements: vec![] },
+                return_type: None,
                 closure: Rc::new(RefCell::new(Environment::new())),
             }
         }
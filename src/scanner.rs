@@ -1,11 +1,42 @@
+use crate::bigint::BigInt;
+use crate::operators::{self, OperatorTable};
+use crate::rational::Rational;
 use crate::token::{Token, TokenType};
 
+/// Built-in symbols made of operator characters, which take priority over
+/// user-defined operators that are not longer than them.
+const BUILTIN_SYMBOLS: &[&str] = &[
+    "|->", "|]", ":=", "=>", "==", "!=", "<=", ">=", ".+", ".-", ".*", "./", ".%", ".^", ".<",
+    ".<=", ".>", ".>=", ".==", ".!=",
+];
+
+/// The operators that have an element-wise version prefixed with a dot, like `.*`.
+/// Longer symbols come first, so `.<=` is not scanned as `.<` followed by `=`.
+const DOTTED_OPERATORS: &[(&str, TokenType)] = &[
+    ("<=", TokenType::LessEqual),
+    (">=", TokenType::GreaterEqual),
+    ("==", TokenType::EqualEqual),
+    ("!=", TokenType::BangEqual),
+    ("+", TokenType::Plus),
+    ("-", TokenType::Minus),
+    ("*", TokenType::Star),
+    ("/", TokenType::Slash),
+    ("%", TokenType::Percent),
+    ("^", TokenType::Caret),
+    ("<", TokenType::Less),
+    (">", TokenType::Greater),
+];
+
 pub struct Scanner {
     source: String,
     start: usize,
     current: usize,
     line: usize,
     column: usize,
+    /// Symbols of user-defined operators.
+    operators: Vec<String>,
+    /// Set after an `infix` keyword, until the operator symbol is scanned.
+    declaring_operator: bool,
 }
 
 impl Scanner {
@@ -16,9 +47,18 @@ impl Scanner {
             current: 0,
             line: 1,
             column: 0,
+            operators: vec![],
+            declaring_operator: false,
         }
     }
 
+    /// Creates a scanner that also recognises previously declared operators.
+    pub fn with_operators(source: &str, operators: &OperatorTable) -> Self {
+        let mut scanner = Self::new(source);
+        scanner.operators = operators.symbols().cloned().collect();
+        scanner
+    }
+
     pub fn report(errors: &[String]) -> String {
         format!("Scanner errors:\n{}", errors.join("\n"))
     }
@@ -78,6 +118,16 @@ impl Scanner {
                 None => return self.advance_and_make_token(TokenType::Eof, ""),
             };
 
+            if self.declaring_operator && !ch.is_whitespace() && !ch.is_ascii_digit() {
+                self.declaring_operator = false;
+                if operators::is_operator_char(ch) {
+                    return self.operator_symbol();
+                }
+            }
+            if let Some(symbol) = self.match_operator() {
+                return self.advance_and_make_token(TokenType::Operator(symbol.clone()), &symbol);
+            }
+
             match ch {
                 '+' => return self.advance_and_make_token(TokenType::Plus, "+"),
                 '-' => return self.advance_and_make_token(TokenType::Minus, "-"),
@@ -94,11 +144,19 @@ impl Scanner {
                         return self.advance_and_make_token(TokenType::Slash, "/");
                     }
                 }
+                '%' => return self.advance_and_make_token(TokenType::Percent, "%"),
+                '^' => return self.advance_and_make_token(TokenType::Caret, "^"),
                 '(' => return self.advance_and_make_token(TokenType::LeftParen, "("),
                 ')' => return self.advance_and_make_token(TokenType::RightParen, ")"),
                 '{' => return self.advance_and_make_token(TokenType::LeftBrace, "{"),
                 '}' => return self.advance_and_make_token(TokenType::RightBrace, "}"),
-                '[' => return self.advance_and_make_token(TokenType::LeftSquareBracket, "["),
+                '[' => {
+                    if self.match_char('|') {
+                        return self.advance_and_make_token(TokenType::LeftMatrixBracket, "[|");
+                    } else {
+                        return self.advance_and_make_token(TokenType::LeftSquareBracket, "[");
+                    }
+                }
                 ']' => return self.advance_and_make_token(TokenType::RightSquareBracket, "]"),
                 ',' => return self.advance_and_make_token(TokenType::Comma, ","),
                 '!' => {
@@ -131,7 +189,13 @@ impl Scanner {
                         return self.advance_and_make_token(TokenType::Greater, ">");
                     }
                 }
-                '|' => return self.maps_to(),
+                '|' => {
+                    if self.match_char(']') {
+                        return self.advance_and_make_token(TokenType::RightMatrixBracket, "|]");
+                    } else {
+                        return self.maps_to();
+                    }
+                }
                 ':' => return self.binding(),
                 ';' => {
                     return self.advance_and_make_token(TokenType::EndStmt, &ch.to_string());
@@ -142,6 +206,11 @@ impl Scanner {
                     continue;
                 }
                 '"' => return self.string(),
+                '\'' => return self.unit(),
+                '.' if self.match_char('.') => {
+                    return self.advance_and_make_token(TokenType::DotDot, "..");
+                }
+                '.' if let Some(token) = self.dotted_operator() => return token,
                 _ if ch.is_alphabetic() || ch == '_' => return self.identifier(),
                 _ if ch.is_ascii_digit() || ch == '.' => return self.number(),
                 _ => return self.unexpected(ch),
@@ -190,22 +259,123 @@ impl Scanner {
     fn number(&mut self) -> Result<Token, String> {
         while self.current().is_some() {
             let ch = self.current().unwrap();
-            if ch.is_numeric() || ch == '.' {
+            // A `..` after a number ends it, as in `[1..2]`.
+            if ch.is_numeric() || (ch == '.' && !self.match_char('.')) {
                 self.advance();
             } else {
                 break;
             }
         }
 
+        // An exponent makes a float, like `1e-3` or `6.02E23`.
+        let has_exponent = match self.exponent_length() {
+            Some(0) => {
+                self.advance();
+                let lexeme = &self.source[self.start..self.current];
+                return self
+                    .make_error(&format!("Expected digits after the exponent in '{lexeme}'"));
+            }
+            Some(length) => {
+                self.advance_by(length);
+                true
+            }
+            None => false,
+        };
+
+        if !has_exponent && let Some(length) = self.rational_suffix() {
+            return self.rational(length);
+        }
+
+        // An `i` suffix makes an imaginary number, like `4i`.
+        let is_imaginary = self.current() == Some('i')
+            && !self
+                .source
+                .chars()
+                .nth(self.current + 1)
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '_');
+        let digits_end = self.current;
+        if is_imaginary {
+            self.advance();
+        }
+
         let lexeme = &self.source[self.start..self.current];
-        let value = match lexeme.parse::<f64>() {
+        let digits = &self.source[self.start..digits_end];
+        if !is_imaginary
+            && !has_exponent
+            && let Some(value) = BigInt::parse(digits)
+        {
+            return self.make_token(TokenType::Integer(value), lexeme);
+        }
+        let value = match digits.parse::<f64>() {
             Ok(float) => float,
             Err(e) => {
                 return self
                     .make_error(&format!("Failed to parse '{}' as a number: {}", lexeme, e));
             }
         };
-        self.make_token(TokenType::Number(value), lexeme)
+        if is_imaginary {
+            self.make_token(TokenType::Imaginary(value), lexeme)
+        } else {
+            self.make_token(TokenType::Number(value), lexeme)
+        }
+    }
+
+    /// Returns the length of an exponent like `e-3` after the number just scanned,
+    /// or `Some(0)` for an `e` that lacks its digits, like in `2e`.
+    /// An `e` that starts a name, like in `2exp(1)`, is not an exponent.
+    fn exponent_length(&self) -> Option<usize> {
+        let peek = |offset: usize| self.source.chars().nth(self.current + offset);
+        if !matches!(peek(0), Some('e' | 'E')) {
+            return None;
+        }
+        let sign = usize::from(matches!(peek(1), Some('+' | '-')));
+        let mut length = 1 + sign;
+        while peek(length).is_some_and(|ch| ch.is_ascii_digit()) {
+            length += 1;
+        }
+        if length > 1 + sign {
+            Some(length)
+        } else if sign == 0 && peek(1).is_some_and(|ch| ch.is_alphanumeric() || ch == '_') {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    /// Returns the length of the rest of a rational literal like `3r` or
+    /// `1/3r`, if the number just scanned starts one.
+    fn rational_suffix(&self) -> Option<usize> {
+        let peek = |offset: usize| self.source.chars().nth(self.current + offset);
+        let mut length = 0;
+        if peek(0) == Some('/') {
+            length = 1;
+            while peek(length).is_some_and(|ch| ch.is_ascii_digit() || ch == '.') {
+                length += 1;
+            }
+            if length == 1 {
+                return None;
+            }
+        }
+        let ends_word = !peek(length + 1).is_some_and(|ch| ch.is_alphanumeric() || ch == '_');
+        (peek(length) == Some('r') && ends_word).then_some(length + 1)
+    }
+
+    fn rational(&mut self, length: usize) -> Result<Token, String> {
+        self.advance_by(length);
+        let lexeme = &self.source[self.start..self.current];
+        let digits = &lexeme[..lexeme.len() - 1];
+        let (numerator, denominator) = digits.split_once('/').unwrap_or((digits, "1"));
+        let value = match (
+            Rational::parse_decimal(numerator),
+            Rational::parse_decimal(denominator),
+        ) {
+            (Some(numerator), Some(denominator)) => numerator.div(&denominator),
+            _ => Err(format!("Failed to parse '{}' as a rational", lexeme)),
+        };
+        match value {
+            Ok(value) => self.make_token(TokenType::Rational(value), lexeme),
+            Err(message) => self.make_error(&message),
+        }
     }
 
     fn identifier(&mut self) -> Result<Token, String> {
@@ -224,10 +394,66 @@ impl Scanner {
             "then" => self.make_token(TokenType::Then, lexeme),
             "else" => self.make_token(TokenType::Else, lexeme),
             "match" => self.make_token(TokenType::Match, lexeme),
+            "div" => self.make_token(TokenType::Div, lexeme),
+            "sym" => self.make_token(TokenType::Sym, lexeme),
+            "infix" | "infixl" | "infixr" => {
+                self.declaring_operator = true;
+                let kind = match lexeme {
+                    "infixl" => TokenType::InfixLeft,
+                    "infixr" => TokenType::InfixRight,
+                    _ => TokenType::Infix,
+                };
+                self.make_token(kind, lexeme)
+            }
             _ => self.make_token(TokenType::Identifier(lexeme.to_string()), lexeme),
         }
     }
 
+    /// Scans the symbol of an operator declaration, and recognises it from now on.
+    fn operator_symbol(&mut self) -> Result<Token, String> {
+        while let Some(ch) = self.current()
+            && operators::is_operator_char(ch)
+        {
+            self.advance();
+        }
+
+        let lexeme = self.source[self.start..self.current].to_string();
+        // Invalid symbols are reported by the parser.
+        if operators::validate_symbol(&lexeme).is_ok() && !self.operators.contains(&lexeme) {
+            self.operators.push(lexeme.clone());
+        }
+        self.make_token(TokenType::Operator(lexeme.clone()), &lexeme)
+    }
+
+    /// Returns the longest user-defined operator at the current position,
+    /// unless a built-in symbol there is at least as long.
+    fn match_operator(&self) -> Option<String> {
+        let rest = self.source.get(self.current..)?;
+        let longest = self
+            .operators
+            .iter()
+            .filter(|symbol| rest.starts_with(symbol.as_str()))
+            .max_by_key(|symbol| symbol.len())?;
+        let builtin_len = BUILTIN_SYMBOLS
+            .iter()
+            .filter(|symbol| rest.starts_with(*symbol))
+            .map(|symbol| symbol.len())
+            .max()
+            .unwrap_or(0);
+
+        (longest.len() > builtin_len).then(|| longest.clone())
+    }
+
+    /// Scans an element-wise operator like `.*`, if one starts here.
+    fn dotted_operator(&mut self) -> Option<Result<Token, String>> {
+        let rest = self.source.get(self.current + 1..)?;
+        let (symbol, kind) = DOTTED_OPERATORS
+            .iter()
+            .find(|(symbol, _)| rest.starts_with(symbol))?;
+        let kind = TokenType::Dotted(Box::new(kind.clone()));
+        Some(self.advance_and_make_token(kind, &format!(".{symbol}")))
+    }
+
     fn maps_to(&mut self) -> Result<Token, String> {
         // symbol |->
         let lexeme = match self.source.get(self.start..self.current + 3) {
@@ -250,23 +476,12 @@ impl Scanner {
     }
 
     fn binding(&mut self) -> Result<Token, String> {
-        // symbol :=
-        let lexeme = match self.source.get(self.start..self.current + 2) {
-            Some(slice) => slice,
-            None => {
-                self.advance();
-                return self.make_error("Expected a |-> (MapsTo) symbol, reached EOF");
-            }
-        };
-        match lexeme {
-            ":=" => {
-                self.advance_by(2);
-                self.make_token(TokenType::Binding, ":=")
-            }
-            _ => {
-                self.advance();
-                self.make_error("Expected a := (Binding) symbol")
-            }
+        // symbol := or a type annotation's :
+        if self.match_char('=') {
+            self.advance_by(2);
+            self.make_token(TokenType::Binding, ":=")
+        } else {
+            self.advance_and_make_token(TokenType::Colon, ":")
         }
     }
 
@@ -291,6 +506,22 @@ impl Scanner {
         let lexeme = &self.source[str_start..str_end];
         self.make_token(TokenType::String(lexeme.to_string()), lexeme)
     }
+
+    /// Scans a unit in single quotes, like `'km/h'`.
+    fn unit(&mut self) -> Result<Token, String> {
+        self.advance(); // skip the opening '
+        while let Some(ch) = self.current() {
+            self.advance();
+            if ch == '\'' {
+                let lexeme = &self.source[self.start + 1..self.current - 1];
+                return self.make_token(TokenType::Unit(lexeme.to_string()), lexeme);
+            }
+            if ch == '\n' {
+                break;
+            }
+        }
+        self.make_error("Unterminated unit, expected a closing '")
+    }
 }
 
 #[cfg(test)]
@@ -337,7 +568,7 @@ mod tests {
                 make_token(Binding),
                 make_token(Identifier("x".to_string())),
                 make_token(MapsTo),
-                make_token(Number(2.0)),
+                make_token(Integer(2.into())),
                 make_token(Star),
                 make_token(Identifier("x".to_string())),
                 make_token(EndStmt),
@@ -346,6 +577,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_number_suffixes() {
+        let fraction = |numerator: i64, denominator: i64| {
+            crate::rational::Rational::new(numerator.into(), denominator.into()).unwrap()
+        };
+        assert_scan(
+            "1/3r 0.25r 2r 1/x 4 rad 4i 1.5i 2in",
+            vec![
+                make_token(TokenType::Rational(fraction(1, 3))),
+                make_token(TokenType::Rational(fraction(1, 4))),
+                make_token(TokenType::Rational(fraction(2, 1))),
+                make_token(Integer(1.into())),
+                make_token(Slash),
+                make_token(Identifier("x".to_string())),
+                make_token(Integer(4.into())),
+                make_token(Identifier("rad".to_string())),
+                make_token(Imaginary(4.0)),
+                make_token(Imaginary(1.5)),
+                make_token(Integer(2.into())),
+                make_token(Identifier("in".to_string())),
+                make_token(Eof),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_matrix_brackets() {
+        assert_scan(
+            "[|1; 2|] [x] |->",
+            vec![
+                make_token(LeftMatrixBracket),
+                make_token(Integer(1.into())),
+                make_token(EndStmt),
+                make_token(Integer(2.into())),
+                make_token(RightMatrixBracket),
+                make_token(LeftSquareBracket),
+                make_token(Identifier("x".to_string())),
+                make_token(RightSquareBracket),
+                make_token(MapsTo),
+                make_token(Eof),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_interval_dots() {
+        assert_scan(
+            "[1.5..2 .. x]",
+            vec![
+                make_token(LeftSquareBracket),
+                make_token(Number(1.5)),
+                make_token(DotDot),
+                make_token(Integer(2.into())),
+                make_token(DotDot),
+                make_token(Identifier("x".to_string())),
+                make_token(RightSquareBracket),
+                make_token(Eof),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_dotted_operators() {
+        let dotted = |kind: TokenType| Dotted(Box::new(kind));
+        assert_scan(
+            "xs.*2 .5 .<= ys",
+            vec![
+                make_token(Identifier("xs".to_string())),
+                make_token(dotted(Star)),
+                make_token(Integer(2.into())),
+                make_token(Number(0.5)),
+                make_token(dotted(LessEqual)),
+                make_token(Identifier("ys".to_string())),
+                make_token(Eof),
+            ],
+        );
+    }
+
     #[test]
     fn test_multiple_stmt() {
         assert_scan(
@@ -381,6 +690,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_remainder_symbol() {
+        assert_scan(
+            "7 % 2",
+            vec![
+                make_token(Integer(7.into())),
+                make_token(Percent),
+                make_token(Integer(2.into())),
+                make_token(Eof),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_power_symbol() {
+        assert_scan(
+            "2^x ^ 3",
+            vec![
+                make_token(Integer(2.into())),
+                make_token(Caret),
+                make_token(Identifier("x".into())),
+                make_token(Caret),
+                make_token(Integer(3.into())),
+                make_token(Eof),
+            ],
+        );
+    }
+
     #[test]
     fn test_multi_char_symbols() {
         assert_scan(
@@ -394,7 +731,7 @@ mod tests {
         assert_scan(
             "123 45.67 .5 -0.5",
             vec![
-                make_token(Number(123.0)),
+                make_token(Integer(123.into())),
                 make_token(Number(45.67)),
                 make_token(Number(0.5)),
                 make_token(Minus),
@@ -404,14 +741,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_exponents() {
+        assert_scan(
+            "1e-3 1E+20 6.02e23 2exp",
+            vec![
+                make_token(Number(0.001)),
+                make_token(Number(1e20)),
+                make_token(Number(6.02e23)),
+                make_token(Integer(2.into())),
+                make_token(Identifier("exp".into())),
+                make_token(Eof),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected digits after the exponent in '2e'")]
+    fn test_missing_exponent_digits() {
+        Scanner::new("2e").scan().unwrap();
+    }
+
     #[test]
     fn test_keywords_and_identifiers() {
         assert_scan(
-            "if then else iffy then_else",
+            "if then else div sym iffy then_else",
             vec![
                 make_token(If),
                 make_token(Then),
                 make_token(Else),
+                make_token(Div),
+                make_token(Sym),
                 make_token(Identifier("iffy".to_string())),
                 make_token(Identifier("then_else".to_string())),
                 make_token(Eof),
@@ -432,7 +792,7 @@ mod tests {
                 make_token(LeftParen),
                 make_token(Identifier("n".to_string())),
                 make_token(Greater),
-                make_token(Number(0.0)),
+                make_token(Integer(0.into())),
                 make_token(RightParen),
                 make_token(Then),
                 make_token(Identifier("x".to_string())),
@@ -464,7 +824,7 @@ mod tests {
             vec![
                 make_token(Identifier("x".to_string())),
                 make_token(Binding),
-                make_token(Number(10.0)),
+                make_token(Integer(10.into())),
                 make_token(EndStmt),
                 make_token(Eof),
             ],
@@ -477,9 +837,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Failed to parse '..' as a number")]
+    #[should_panic(expected = "Failed to parse '1.2.3' as a number")]
     fn test_invalid_float_literal() {
-        let source = "..";
+        let source = "1.2.3";
         Scanner::new(source).scan().unwrap();
     }
 
@@ -491,9 +851,17 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Expected a := (Binding) symbol")]
-    fn test_invalid_binding() {
-        let source = ": x";
-        Scanner::new(source).scan().unwrap();
+    fn test_colon_and_binding() {
+        assert_scan(
+            "x: Number := 1",
+            vec![
+                make_token(Identifier("x".into())),
+                make_token(Colon),
+                make_token(Identifier("Number".into())),
+                make_token(Binding),
+                make_token(Integer(1.into())),
+                make_token(Eof),
+            ],
+        );
     }
 }
@@ -0,0 +1,296 @@
+/// A sum with Neumaier's compensation, which keeps the rounding error
+/// of each addition and adds it back at the end.
+fn sum(xs: impl IntoIterator<Item = f64>) -> f64 {
+    let (mut total, mut compensation) = (0.0, 0.0);
+    for x in xs {
+        let t = total + x;
+        if f64::abs(total) >= x.abs() {
+            compensation += (total - t) + x;
+        } else {
+            compensation += (x - t) + total;
+        }
+        total = t;
+    }
+    total + compensation
+}
+
+fn expect_non_empty(name: &str, xs: &[f64]) -> Result<(), String> {
+    if xs.is_empty() {
+        Err(format!("{name}() expects a non-empty list of numbers"))
+    } else {
+        Ok(())
+    }
+}
+
+fn expect_same_length(name: &str, xs: &[f64], ys: &[f64]) -> Result<(), String> {
+    if xs.len() == ys.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name}() expects lists of the same length, found {} and {}",
+            xs.len(),
+            ys.len()
+        ))
+    }
+}
+
+/// The numbers in increasing order.
+fn sorted(xs: &[f64]) -> Vec<f64> {
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+pub fn mean(name: &str, xs: &[f64]) -> Result<f64, String> {
+    expect_non_empty(name, xs)?;
+    Ok(sum(xs.iter().copied()) / xs.len() as f64)
+}
+
+pub fn median(name: &str, xs: &[f64]) -> Result<f64, String> {
+    quantile(name, xs, 0.5)
+}
+
+/// The most common number, or the smallest of the most common numbers.
+pub fn mode(name: &str, xs: &[f64]) -> Result<f64, String> {
+    expect_non_empty(name, xs)?;
+    let sorted = sorted(xs);
+    let (mut best, mut best_count) = (sorted[0], 0);
+    for run in sorted.chunk_by(|a, b| a == b) {
+        if run.len() > best_count {
+            (best, best_count) = (run[0], run.len());
+        }
+    }
+    Ok(best)
+}
+
+/// The sum of products of deviations from the means, found in two passes,
+/// which avoids the cancellation of the one-pass formula `Σxy - n x̄ ȳ`.
+/// The second pass corrects for the rounding of the means.
+fn co_moment(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len() as f64;
+    let (x_mean, y_mean) = (sum(xs.iter().copied()) / n, sum(ys.iter().copied()) / n);
+    let products = sum(xs.iter().zip(ys).map(|(x, y)| (x - x_mean) * (y - y_mean)));
+    let x_error = sum(xs.iter().map(|x| x - x_mean));
+    let y_error = sum(ys.iter().map(|y| y - y_mean));
+    products - x_error * y_error / n
+}
+
+/// The number to divide a sum of squares by: `n - 1` for a sample,
+/// or `n` for a whole population.
+fn degrees_of_freedom(name: &str, n: usize, sample: bool) -> Result<f64, String> {
+    match (n, sample) {
+        (0, _) => Err(format!("{name}() expects a non-empty list of numbers")),
+        (1, true) => Err(format!(
+            "{name}() expects at least 2 numbers for a sample, found 1"
+        )),
+        (n, true) => Ok((n - 1) as f64),
+        (n, false) => Ok(n as f64),
+    }
+}
+
+/// The variance of a sample, or of a whole population.
+pub fn variance(name: &str, xs: &[f64], sample: bool) -> Result<f64, String> {
+    let divisor = degrees_of_freedom(name, xs.len(), sample)?;
+    // Rounding could make a sum of squares slightly negative.
+    Ok(co_moment(xs, xs).max(0.0) / divisor)
+}
+
+/// The derivatives of the variance in each number.
+pub fn variance_gradient(xs: &[f64], sample: bool) -> Vec<f64> {
+    let n = xs.len() as f64;
+    let divisor = if sample { n - 1.0 } else { n };
+    let mean = sum(xs.iter().copied()) / n;
+    xs.iter().map(|x| 2.0 * (x - mean) / divisor).collect()
+}
+
+pub fn covariance(name: &str, xs: &[f64], ys: &[f64], sample: bool) -> Result<f64, String> {
+    expect_same_length(name, xs, ys)?;
+    let divisor = degrees_of_freedom(name, xs.len(), sample)?;
+    Ok(co_moment(xs, ys) / divisor)
+}
+
+/// Pearson's correlation coefficient.
+pub fn correlation(name: &str, xs: &[f64], ys: &[f64]) -> Result<f64, String> {
+    expect_same_length(name, xs, ys)?;
+    if xs.len() < 2 {
+        return Err(format!(
+            "{name}() expects at least 2 pairs of numbers, found {}",
+            xs.len()
+        ));
+    }
+    let (sxx, syy) = (co_moment(xs, xs), co_moment(ys, ys));
+    if sxx <= 0.0 || syy <= 0.0 {
+        return Err(format!(
+            "{name}() is undefined when all the numbers in a list are equal"
+        ));
+    }
+    Ok((co_moment(xs, ys) / (sxx * syy).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// The number below which a fraction `q` of the numbers lie, interpolating
+/// linearly between the numbers on either side.
+pub fn quantile(name: &str, xs: &[f64], q: f64) -> Result<f64, String> {
+    expect_non_empty(name, xs)?;
+    if !(0.0..=1.0).contains(&q) {
+        return Err(format!(
+            "{name}() expects a fraction between 0 and 1, found {q}"
+        ));
+    }
+    // NaN has no place in the order, so like the mean, the result is NaN.
+    if xs.iter().any(|x| x.is_nan()) {
+        return Ok(f64::NAN);
+    }
+    let sorted = sorted(xs);
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let fraction = position - below as f64;
+    match sorted.get(below + 1) {
+        Some(above) if fraction > 0.0 => Ok(sorted[below] + fraction * (above - sorted[below])),
+        _ => Ok(sorted[below]),
+    }
+}
+
+/// The weight of each number in `quantile(xs, q)`, which is a weighted sum of
+/// at most two of them, and its derivative in `q`.
+pub fn quantile_weights(xs: &[f64], q: f64) -> (Vec<f64>, f64) {
+    let mut order: Vec<usize> = (0..xs.len()).collect();
+    order.sort_by(|&i, &j| xs[i].total_cmp(&xs[j]));
+    let position = q * (xs.len() - 1) as f64;
+    let below = order[position.floor() as usize];
+    let fraction = position.fract();
+    let mut weights = vec![0.0; xs.len()];
+    weights[below] = 1.0;
+    match order.get(position.floor() as usize + 1) {
+        Some(&above) => {
+            weights[below] -= fraction;
+            weights[above] += fraction;
+            (weights, (xs[above] - xs[below]) * (xs.len() - 1) as f64)
+        }
+        None => (weights, 0.0),
+    }
+}
+
+/// How many numbers fall into each of `bins` equal bins from the smallest
+/// number to the largest, which counts in the last bin.
+pub fn histogram(name: &str, xs: &[f64], bins: usize) -> Result<Vec<usize>, String> {
+    expect_non_empty(name, xs)?;
+    if bins == 0 {
+        return Err(format!("{name}() expects at least 1 bin"));
+    }
+    let (low, high) = xs
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), x| {
+            (low.min(*x), high.max(*x))
+        });
+    if !low.is_finite() || !high.is_finite() {
+        return Err(format!("{name}() expects finite numbers"));
+    }
+    let mut counts = vec![0; bins];
+    let width = (high - low) / bins as f64;
+    for x in xs {
+        let bin = if width > 0.0 {
+            (((x - low) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+    Ok(counts)
+}
+
+/// The least-squares line `y = slope * x + intercept`, and the fraction
+/// of the variance of `ys` it explains, `r²`.
+pub fn linear_regression(name: &str, xs: &[f64], ys: &[f64]) -> Result<(f64, f64, f64), String> {
+    expect_same_length(name, xs, ys)?;
+    let sxx = if xs.len() < 2 { 0.0 } else { co_moment(xs, xs) };
+    if sxx <= 0.0 {
+        return Err(format!("{name}() expects at least 2 distinct x values"));
+    }
+    let (sxy, syy) = (co_moment(xs, ys), co_moment(ys, ys));
+    let slope = sxy / sxx;
+    let n = xs.len() as f64;
+    let intercept = sum(ys.iter().copied()) / n - slope * sum(xs.iter().copied()) / n;
+    // A horizontal line through equal ys fits them perfectly.
+    let r_squared = if syy <= 0.0 {
+        1.0
+    } else {
+        (sxy / sxx * (sxy / syy)).clamp(0.0, 1.0)
+    };
+    Ok((slope, intercept, r_squared))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_averages() {
+        let xs = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0];
+        assert_eq!(mean("mean", &xs).unwrap(), 3.875);
+        assert_eq!(median("median", &xs).unwrap(), 3.5);
+        assert_eq!(mode("mode", &xs).unwrap(), 1.0);
+        assert_eq!(quantile("quantile", &xs, 0.0).unwrap(), 1.0);
+        assert_eq!(quantile("quantile", &xs, 1.0).unwrap(), 9.0);
+        assert_eq!(quantile("quantile", &[1.0, 2.0], 0.25).unwrap(), 1.25);
+        assert!(mean("mean", &[]).is_err());
+    }
+
+    #[test]
+    fn test_variance_is_stable() {
+        // A large offset ruins the one-pass formula, but not the variance.
+        let xs: Vec<f64> = [4.0, 7.0, 13.0, 16.0].iter().map(|x| 1e9 + x).collect();
+        assert_eq!(variance("variance", &xs, true).unwrap(), 30.0);
+        assert_eq!(variance("variance", &xs, false).unwrap(), 22.5);
+        assert!(variance("variance", &[1.0], true).is_err());
+        assert_eq!(variance("variance", &[1.0], false).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_gradients() {
+        assert_eq!(variance_gradient(&[1.0, 3.0], true), [-2.0, 2.0]);
+        assert_eq!(variance_gradient(&[1.0, 3.0], false), [-1.0, 1.0]);
+        // The quantile lies a quarter of the way from 1 to 3, so 3 has a quarter of the weight.
+        assert_eq!(
+            quantile_weights(&[3.0, 5.0, 1.0], 0.125),
+            (vec![0.25, 0.0, 0.75], 4.0)
+        );
+        assert_eq!(
+            quantile_weights(&[3.0, 5.0, 1.0], 1.0),
+            (vec![0.0, 1.0, 0.0], 0.0)
+        );
+    }
+
+    #[test]
+    fn test_sum_is_compensated() {
+        assert_eq!(sum([1e16, 1.0, -1e16]), 1.0);
+        assert_eq!(mean("mean", &[0.1; 10]).unwrap(), 0.1);
+    }
+
+    #[test]
+    fn test_relationships() {
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        let ys = [2.0, 4.0, 5.0, 9.0];
+        assert_eq!(
+            covariance("covariance", &xs, &ys, true).unwrap(),
+            11.0 / 3.0
+        );
+        let r = correlation("correlation", &xs, &ys).unwrap();
+        assert!((r - 11.0 / 130f64.sqrt()).abs() < 1e-12);
+        let (slope, intercept, r_squared) =
+            linear_regression("linear_regression", &xs, &ys).unwrap();
+        assert_eq!((slope, intercept), (2.2, -0.5));
+        assert!((r_squared - r * r).abs() < 1e-12);
+        assert!(correlation("correlation", &xs, &[1.0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_histogram() {
+        let xs = [0.0, 0.5, 1.0, 1.5, 2.0, 2.0];
+        assert_eq!(histogram("histogram", &xs, 2).unwrap(), vec![2, 4]);
+        assert_eq!(histogram("histogram", &xs, 4).unwrap(), vec![1, 1, 1, 3]);
+        assert_eq!(
+            histogram("histogram", &[3.0, 3.0], 3).unwrap(),
+            vec![2, 0, 0]
+        );
+    }
+}
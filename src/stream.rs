@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::interpreter::Interpreter;
+use crate::runtime::RuntimeValue;
+
+/// The first element of a stream together with the rest of the stream,
+/// or `None` if the stream is empty.
+pub type StreamNode = Option<(RuntimeValue, Stream)>;
+
+type Thunk = Rc<dyn Fn() -> Result<StreamNode, String>>;
+
+enum Cell {
+    Pending(Thunk),
+    Forced(StreamNode),
+}
+
+/// A lazy, possibly infinite sequence of values.
+///
+/// Each cell is computed at most once, the first time it is forced.
+#[derive(Clone)]
+pub struct Stream(Rc<RefCell<Cell>>);
+
+impl std::fmt::Debug for Stream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stream")
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        // Unlink forced cells one at a time, so that dropping a long
+        // stream does not recurse once per element.
+        let mut next = self.take_unique_tail();
+        while let Some(mut stream) = next {
+            next = stream.take_unique_tail();
+        }
+    }
+}
+
+impl Stream {
+    /// Creates a stream whose first cell is computed by `thunk` when forced.
+    pub fn lazy(thunk: impl Fn() -> Result<StreamNode, String> + 'static) -> Self {
+        Self(Rc::new(RefCell::new(Cell::Pending(Rc::new(thunk)))))
+    }
+
+    pub fn empty() -> Self {
+        Self(Rc::new(RefCell::new(Cell::Forced(None))))
+    }
+
+    pub fn cons(head: RuntimeValue, tail: Stream) -> Self {
+        Self(Rc::new(RefCell::new(Cell::Forced(Some((head, tail))))))
+    }
+
+    pub fn from_values(values: Vec<RuntimeValue>) -> Self {
+        values
+            .into_iter()
+            .rev()
+            .fold(Self::empty(), |tail, head| Self::cons(head, tail))
+    }
+
+    /// Computes the first cell of the stream, if it has not been computed yet.
+    pub fn force(&self) -> Result<StreamNode, String> {
+        // Release the borrow before running the thunk, which may force other
+        // cells of this stream.
+        let thunk = match &*self.0.borrow() {
+            Cell::Forced(node) => return Ok(node.clone()),
+            Cell::Pending(thunk) => Rc::clone(thunk),
+        };
+
+        let node = thunk()?;
+        *self.0.borrow_mut() = Cell::Forced(node.clone());
+        Ok(node)
+    }
+
+    fn take_unique_tail(&mut self) -> Option<Stream> {
+        if Rc::strong_count(&self.0) != 1 {
+            return None;
+        }
+        match &mut *self.0.borrow_mut() {
+            Cell::Forced(Some((_, tail))) => Some(std::mem::replace(tail, Self::empty())),
+            _ => None,
+        }
+    }
+
+    /// Forces at most `count` elements into a vector.
+    pub fn take(&self, count: usize) -> Result<Vec<RuntimeValue>, String> {
+        let mut values = vec![];
+        let mut current = self.clone();
+        while values.len() < count {
+            match current.force()? {
+                Some((head, tail)) => {
+                    values.push(head);
+                    current = tail;
+                }
+                None => break,
+            }
+        }
+        Ok(values)
+    }
+
+    /// The elements in order, forcing each one as it is reached.
+    /// The iterator ends after the first error.
+    pub fn iter(&self) -> impl Iterator<Item = Result<RuntimeValue, String>> + use<> {
+        let mut current = Some(self.clone());
+        std::iter::from_fn(move || match current.take()?.force() {
+            Ok(Some((head, tail))) => {
+                current = Some(tail);
+                Some(Ok(head))
+            }
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        })
+    }
+
+    /// `x0, f(x0), f(f(x0)), ...`
+    pub fn iterate(function: RuntimeValue, initial: RuntimeValue) -> Self {
+        let previous = initial.clone();
+        let next = Self::lazy(move || {
+            let value = Interpreter::call(&function, previous.clone())?;
+            Self::iterate(function.clone(), value).force()
+        });
+        Self::cons(initial, next)
+    }
+
+    /// `x, x, x, ...`
+    pub fn repeat(value: RuntimeValue) -> Self {
+        Self::lazy(move || Ok(Some((value.clone(), Self::repeat(value.clone())))))
+    }
+
+    /// Repeats the given values forever. The values must not be empty.
+    pub fn cycle(values: Rc<Vec<RuntimeValue>>, index: usize) -> Self {
+        Self::lazy(move || {
+            let next = (index + 1) % values.len();
+            Ok(Some((
+                values[index].clone(),
+                Self::cycle(Rc::clone(&values), next),
+            )))
+        })
+    }
+
+    /// Calls `function` on the seed, which returns either `nil` to end the
+    /// stream, or a `[value, next_seed]` pair.
+    pub fn unfold(function: RuntimeValue, seed: RuntimeValue) -> Self {
+        Self::lazy(move || match Interpreter::call(&function, seed.clone())? {
+            RuntimeValue::Nil => Ok(None),
+            RuntimeValue::List { elements } if elements.len() == 2 => {
+                let mut pair = elements.into_iter();
+                let value = pair.next().unwrap_or(RuntimeValue::Nil);
+                let next_seed = pair.next().unwrap_or(RuntimeValue::Nil);
+                Ok(Some((value, Self::unfold(function.clone(), next_seed))))
+            }
+            other => Err(format!(
+                "unfold() expects the function to return nil or a [value, seed] pair, found {other}"
+            )),
+        })
+    }
+
+    pub fn map(&self, function: RuntimeValue) -> Self {
+        let source = self.clone();
+        Self::lazy(move || match source.force()? {
+            Some((head, tail)) => {
+                let value = Interpreter::call(&function, head)?;
+                Ok(Some((value, tail.map(function.clone()))))
+            }
+            None => Ok(None),
+        })
+    }
+
+    /// Keeps the elements satisfying `predicate`.
+    /// Forcing a filtered stream without any further matches never returns.
+    pub fn filter(&self, predicate: RuntimeValue) -> Self {
+        let source = self.clone();
+        Self::lazy(move || {
+            let mut current = source.clone();
+            while let Some((head, tail)) = current.force()? {
+                if Interpreter::call(&predicate, head.clone())?.is_truthy() {
+                    return Ok(Some((head, tail.filter(predicate.clone()))));
+                }
+                current = tail;
+            }
+            Ok(None)
+        })
+    }
+
+    pub fn take_while(&self, predicate: RuntimeValue) -> Self {
+        let source = self.clone();
+        Self::lazy(move || match source.force()? {
+            Some((head, tail)) if Interpreter::call(&predicate, head.clone())?.is_truthy() => {
+                Ok(Some((head, tail.take_while(predicate.clone()))))
+            }
+            _ => Ok(None),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_from_finite_stream() {
+        let stream = Stream::from_values(vec![RuntimeValue::Number(1.0)]);
+        assert_eq!(stream.take(5), Ok(vec![RuntimeValue::Number(1.0)]));
+        assert_eq!(stream.take(0), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_iter_stops_early() {
+        let stream = Stream::repeat(RuntimeValue::Nil);
+        assert_eq!(stream.iter().take(3).count(), 3);
+        let stream = Stream::from_values(vec![RuntimeValue::Nil; 2]);
+        assert_eq!(stream.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_cells_forced_once() {
+        let count = Rc::new(RefCell::new(0));
+        let counter = Rc::clone(&count);
+        let stream = Stream::lazy(move || {
+            *counter.borrow_mut() += 1;
+            Ok(Some((RuntimeValue::Nil, Stream::empty())))
+        });
+
+        assert_eq!(*count.borrow(), 0);
+        stream.force().unwrap();
+        stream.force().unwrap();
+        assert_eq!(*count.borrow(), 1);
+    }
+
+    #[test]
+    fn test_drop_long_stream() {
+        let values = vec![RuntimeValue::Nil; 1_000_000];
+        let stream = Stream::from_values(values);
+        drop(stream);
+    }
+}
@@ -0,0 +1,1280 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::rc::Rc;
+
+use crate::ast::{Expr, LiteralValue, MatchArm};
+use crate::bigint::BigInt;
+use crate::interpreter::Interpreter;
+use crate::rational::Rational;
+use crate::runtime::{Environment, RuntimeValue};
+use crate::token::{Token, TokenType};
+
+/// Differentiates a user-defined function with respect to its parameter,
+/// returning the simplified derivative as a new function.
+///
+/// Calls to other user-defined functions use their derivatives, which are
+/// bound in the new function's scope with a prime, like `g'`.
+pub fn differentiate_function(function: &RuntimeValue) -> Result<RuntimeValue, String> {
+    differentiate_named(function, &mut vec![])
+}
+
+/// `active` holds the names of the functions being differentiated,
+/// to reject recursive functions instead of looping forever.
+fn differentiate_named(
+    function: &RuntimeValue,
+    active: &mut Vec<String>,
+) -> Result<RuntimeValue, String> {
+    let RuntimeValue::Function {
+        arg_name,
+        param_type,
+        body,
+        return_type,
+        closure,
+    } = function
+    else {
+        return match function {
+            RuntimeValue::Memoized(memo) => differentiate_named(memo.function(), active),
+            _ => Err(format!(
+                "diff() expects a user-defined function, found {function}"
+            )),
+        };
+    };
+
+    let mut differentiator = Differentiator {
+        var: arg_name,
+        closure,
+        derivatives: vec![],
+        active,
+    };
+    let derivative = simplify(&differentiator.derivative(body)?);
+
+    let closure = if differentiator.derivatives.is_empty() {
+        Rc::clone(closure)
+    } else {
+        let mut scope = Environment::with_parent(Rc::clone(closure));
+        for (name, value) in differentiator.derivatives {
+            scope.bind(name, value)?;
+        }
+        Rc::new(RefCell::new(scope))
+    };
+    Ok(RuntimeValue::Function {
+        arg_name: arg_name.clone(),
+        param_type: param_type.clone(),
+        body: derivative,
+        return_type: return_type.clone(),
+        closure,
+    })
+}
+
+struct Differentiator<'a> {
+    /// The variable to differentiate with respect to.
+    var: &'a str,
+    /// The scope of the function, used to look up the functions it calls.
+    closure: &'a Rc<RefCell<Environment>>,
+    /// Derivatives of the user-defined functions that are called.
+    derivatives: Vec<(String, RuntimeValue)>,
+    active: &'a mut Vec<String>,
+}
+
+impl Differentiator<'_> {
+    fn derivative(&mut self, expr: &Expr) -> Result<Expr, String> {
+        Ok(match expr {
+            Expr::Literal(_) => integer(0),
+            Expr::Variable(name) if name == self.var => integer(1),
+            Expr::Variable(_) => integer(0),
+            Expr::Grouping(expr) => self.derivative(expr)?,
+            Expr::Unary { op, right } if op.kind == TokenType::Minus => {
+                neg(self.derivative(right)?)
+            }
+            Expr::Binary { left, op, right } => {
+                let (u, v) = (left.as_ref().clone(), right.as_ref().clone());
+                let du = self.derivative(left)?;
+                let dv = self.derivative(right)?;
+                match op.kind {
+                    TokenType::Plus => add(du, dv),
+                    TokenType::Minus => sub(du, dv),
+                    TokenType::Star => add(mul(du, v), mul(u, dv)),
+                    TokenType::Slash => {
+                        div(sub(mul(du, v.clone()), mul(u, dv)), pow(v, integer(2)))
+                    }
+                    TokenType::Caret => Self::power_rule(u, du, v, dv),
+                    _ => return Err(cannot_differentiate(expr)),
+                }
+            }
+            Expr::FunctionCall { func, arg, .. } => {
+                let du = self.derivative(arg)?;
+                let outer = self.call_derivative(func, arg.as_ref().clone())?;
+                mul(du, outer)
+            }
+            Expr::Block { statements } if statements.len() == 1 => {
+                self.derivative(&statements[0])?
+            }
+            // Each branch is differentiated separately.
+            Expr::If {
+                keyword,
+                cond_expr,
+                then_expr,
+                else_expr,
+            } => Expr::If {
+                keyword: keyword.clone(),
+                cond_expr: cond_expr.clone(),
+                then_expr: Box::new(self.derivative(then_expr)?),
+                else_expr: Box::new(self.derivative(else_expr)?),
+            },
+            Expr::Match { arms } => Expr::Match {
+                arms: arms
+                    .iter()
+                    .map(|arm| {
+                        Ok(MatchArm {
+                            pattern: arm.pattern.clone(),
+                            body: Box::new(self.derivative(&arm.body)?),
+                        })
+                    })
+                    .collect::<Result<_, String>>()?,
+            },
+            // A function of further parameters, as in `(x, y) |-> x * y`,
+            // has the partial derivative with respect to the first one.
+            Expr::FunctionDef {
+                param,
+                param_type,
+                body,
+                return_type,
+            } => Expr::FunctionDef {
+                param: param.clone(),
+                param_type: param_type.clone(),
+                body: Box::new(if param == self.var {
+                    integer(0)
+                } else {
+                    self.derivative(body)?
+                }),
+                return_type: return_type.clone(),
+            },
+            Expr::List { elements } => Expr::List {
+                elements: elements
+                    .iter()
+                    .map(|element| self.derivative(element))
+                    .collect::<Result<_, _>>()?,
+            },
+            _ => return Err(cannot_differentiate(expr)),
+        })
+    }
+
+    fn power_rule(u: Expr, du: Expr, v: Expr, dv: Expr) -> Expr {
+        if is_zero(&dv) {
+            // d(u^n) = n * u^(n - 1) * du
+            let exponent = sub(v.clone(), integer(1));
+            mul(du, mul(v, pow(u, exponent)))
+        } else if is_zero(&du) {
+            // d(a^v) = a^v * ln(a) * dv
+            mul(dv, mul(pow(u.clone(), v), call("ln", u)))
+        } else {
+            // d(u^v) = u^v * (dv * ln(u) + v * du / u)
+            let rate = add(
+                mul(dv, call("ln", u.clone())),
+                div(mul(v.clone(), du), u.clone()),
+            );
+            mul(pow(u, v), rate)
+        }
+    }
+
+    /// The derivative of `func` at `arg`, for the chain rule.
+    fn call_derivative(&mut self, func: &Expr, arg: Expr) -> Result<Expr, String> {
+        let Expr::Variable(name) = func else {
+            return Err(cannot_differentiate(func));
+        };
+        let value = self.closure.borrow().resolve(name);
+        match value {
+            Some(RuntimeValue::NativeFunction { name: native, .. }) => match native.as_str() {
+                "sin" => Ok(call("cos", arg)),
+                "cos" => Ok(neg(call("sin", arg))),
+                "exp" => Ok(call("exp", arg)),
+                "ln" => Ok(div(integer(1), arg)),
+                "sqrt" => Ok(div(integer(1), mul(integer(2), call("sqrt", arg)))),
+                "abs" => Ok(div(arg.clone(), call("abs", arg))),
+                "tan" => Ok(div(integer(1), pow(call("cos", arg), integer(2)))),
+                "asin" => Ok(div(
+                    integer(1),
+                    call("sqrt", sub(integer(1), pow(arg, integer(2)))),
+                )),
+                "acos" => Ok(neg(div(
+                    integer(1),
+                    call("sqrt", sub(integer(1), pow(arg, integer(2)))),
+                ))),
+                "atan" => Ok(div(integer(1), add(integer(1), pow(arg, integer(2))))),
+                "sinh" => Ok(call("cosh", arg)),
+                "cosh" => Ok(call("sinh", arg)),
+                "tanh" => Ok(div(integer(1), pow(call("cosh", arg), integer(2)))),
+                "asinh" => Ok(div(
+                    integer(1),
+                    call("sqrt", add(pow(arg, integer(2)), integer(1))),
+                )),
+                "acosh" => Ok(div(
+                    integer(1),
+                    call("sqrt", sub(pow(arg, integer(2)), integer(1))),
+                )),
+                "atanh" => Ok(div(integer(1), sub(integer(1), pow(arg, integer(2))))),
+                "log10" => Ok(div(integer(1), mul(arg, call("ln", integer(10))))),
+                "log2" => Ok(div(integer(1), mul(arg, call("ln", integer(2))))),
+                "log1p" => Ok(div(integer(1), add(integer(1), arg))),
+                "expm1" => Ok(call("exp", arg)),
+                "exp2" => Ok(mul(call("exp2", arg), call("ln", integer(2)))),
+                "cbrt" => Ok(div(
+                    integer(1),
+                    mul(integer(3), pow(call("cbrt", arg), integer(2))),
+                )),
+                _ => Err(format!("diff() does not know the derivative of {native}")),
+            },
+            Some(function @ (RuntimeValue::Function { .. } | RuntimeValue::Memoized(_))) => {
+                let prime = format!("{name}'");
+                if !self.derivatives.iter().any(|(bound, _)| *bound == prime) {
+                    if self.active.contains(name) {
+                        return Err(format!(
+                            "diff() cannot differentiate the recursive function {name}"
+                        ));
+                    }
+                    self.active.push(name.clone());
+                    let derivative = differentiate_named(&function, self.active);
+                    self.active.pop();
+                    self.derivatives.push((prime.clone(), derivative?));
+                }
+                Ok(Expr::FunctionCall {
+                    func: Box::new(Expr::Variable(prime)),
+                    paren: token(TokenType::LeftParen, "("),
+                    arg: Box::new(arg),
+                })
+            }
+            _ => Err(format!("diff() cannot differentiate calls to {name}")),
+        }
+    }
+}
+
+fn cannot_differentiate(expr: &Expr) -> String {
+    format!("diff() cannot differentiate {expr}")
+}
+
+/// Simplifies an expression to a sum of products, collecting like terms and
+/// evaluating arithmetic on constants, so `x ^ 2 + 2 * x * x` is `3 * x ^ 2`.
+pub fn simplify(expr: &Expr) -> Expr {
+    normalize(expr, false)
+}
+
+/// Simplifies an expression and multiplies out products and integer powers of sums.
+/// Powers above `MAX_EXPANDED_POWER` are an error, as their expansion grows too large.
+pub fn expand(expr: &Expr) -> Result<Expr, String> {
+    let expanded = normalize(expr, true);
+    match unexpanded_power(&expanded) {
+        Some(power) => Err(format!(
+            "expand() multiplies out powers of sums up to {MAX_EXPANDED_POWER}, found {power}"
+        )),
+        None => Ok(expanded),
+    }
+}
+
+/// Finds a power of a sum that was too large to expand.
+fn unexpanded_power(expr: &Expr) -> Option<Expr> {
+    if let Expr::Binary { left, op, right } = expr
+        && op.kind == TokenType::Caret
+        && matches!(
+            left.as_ref(),
+            Expr::Binary { op, .. } if matches!(op.kind, TokenType::Plus | TokenType::Minus)
+        )
+        && let Some(RuntimeValue::Integer(n)) = constant(right)
+        && n.to_i64().is_none_or(|n| n > MAX_EXPANDED_POWER)
+    {
+        return Some(expr.clone());
+    }
+    let mut found = None;
+    map_children(expr, |child| {
+        if found.is_none() {
+            found = unexpanded_power(child);
+        }
+        child.clone()
+    });
+    found
+}
+
+/// Replaces a variable with an expression, except where a function parameter shadows it.
+pub fn substitute(expr: &Expr, name: &str, value: &Expr) -> Expr {
+    match expr {
+        Expr::Variable(variable) if variable == name => value.clone(),
+        Expr::FunctionDef { param, .. } if param == name => expr.clone(),
+        _ => map_children(expr, |child| substitute(child, name, value)),
+    }
+}
+
+fn normalize(expr: &Expr, expand: bool) -> Expr {
+    match expr {
+        Expr::Binary { op, .. } if !is_arithmetic(&op.kind) => {
+            map_children(expr, |child| normalize(child, expand))
+        }
+        Expr::Unary { op, .. } if op.kind != TokenType::Minus => {
+            map_children(expr, |child| normalize(child, expand))
+        }
+        Expr::Grouping(_)
+        | Expr::Literal(_)
+        | Expr::Variable(_)
+        | Expr::Unary { .. }
+        | Expr::Binary { .. }
+        | Expr::FunctionCall { .. } => build_sum(terms(expr, expand)),
+        _ => map_children(expr, |child| normalize(child, expand)),
+    }
+}
+
+fn is_arithmetic(kind: &TokenType) -> bool {
+    matches!(
+        kind,
+        TokenType::Plus | TokenType::Minus | TokenType::Star | TokenType::Slash | TokenType::Caret
+    )
+}
+
+/// Rebuilds an expression with `f` applied to each of its direct subexpressions.
+fn map_children(expr: &Expr, mut f: impl FnMut(&Expr) -> Expr) -> Expr {
+    let mut boxed = |expr: &Expr| Box::new(f(expr));
+    match expr {
+        Expr::Program { statements } => Expr::Program {
+            statements: statements
+                .iter()
+                .map(|statement| *boxed(statement))
+                .collect(),
+        },
+        Expr::Block { statements } => Expr::Block {
+            statements: statements
+                .iter()
+                .map(|statement| *boxed(statement))
+                .collect(),
+        },
+        Expr::Binary { left, op, right } => Expr::Binary {
+            left: boxed(left),
+            op: op.clone(),
+            right: boxed(right),
+        },
+        Expr::Unary { op, right } => Expr::Unary {
+            op: op.clone(),
+            right: boxed(right),
+        },
+        Expr::Grouping(expr) => Expr::Grouping(boxed(expr)),
+        Expr::Section { op, left, right } => Expr::Section {
+            op: op.clone(),
+            left: left.as_deref().map(&mut boxed),
+            right: right.as_deref().map(&mut boxed),
+        },
+        Expr::Assign { name, equal, expr } => Expr::Assign {
+            name: name.clone(),
+            equal: equal.clone(),
+            expr: boxed(expr),
+        },
+        Expr::Binding {
+            name,
+            annotation,
+            expr,
+        } => Expr::Binding {
+            name: name.clone(),
+            annotation: annotation.clone(),
+            expr: boxed(expr),
+        },
+        Expr::FunctionDef {
+            param,
+            param_type,
+            body,
+            return_type,
+        } => Expr::FunctionDef {
+            param: param.clone(),
+            param_type: param_type.clone(),
+            body: boxed(body),
+            return_type: return_type.clone(),
+        },
+        Expr::FunctionCall { func, paren, arg } => Expr::FunctionCall {
+            func: boxed(func),
+            paren: paren.clone(),
+            arg: boxed(arg),
+        },
+        Expr::If {
+            keyword,
+            cond_expr,
+            then_expr,
+            else_expr,
+        } => Expr::If {
+            keyword: keyword.clone(),
+            cond_expr: boxed(cond_expr),
+            then_expr: boxed(then_expr),
+            else_expr: boxed(else_expr),
+        },
+        Expr::Match { arms } => Expr::Match {
+            arms: arms
+                .iter()
+                .map(|arm| MatchArm {
+                    pattern: boxed(&arm.pattern),
+                    body: boxed(&arm.body),
+                })
+                .collect(),
+        },
+        Expr::List { elements } => Expr::List {
+            elements: elements.iter().map(|element| *boxed(element)).collect(),
+        },
+        Expr::Matrix { rows } => Expr::Matrix {
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|element| *boxed(element)).collect())
+                .collect(),
+        },
+        Expr::Interval { low, high } => Expr::Interval {
+            low: boxed(low),
+            high: boxed(high),
+        },
+        // A nested formula is already simplified, and has its own names.
+        Expr::Symbolic(_) | Expr::Variable(_) | Expr::Literal(_) | Expr::Empty => expr.clone(),
+    }
+}
+
+/// A constant times a product of powers, like `3 * x ^ 2 * sin(y)`.
+#[derive(Clone)]
+struct Term {
+    /// A real number.
+    coefficient: RuntimeValue,
+    /// Each base with its exponent, in a fixed order.
+    factors: Vec<(Expr, Expr)>,
+}
+
+impl Term {
+    fn constant(coefficient: RuntimeValue) -> Self {
+        Self {
+            coefficient,
+            factors: vec![],
+        }
+    }
+
+    fn power(base: Expr, exponent: Expr) -> Self {
+        if is_zero(&exponent) {
+            return Self::constant(RuntimeValue::Integer(BigInt::one()));
+        }
+        Self {
+            coefficient: RuntimeValue::Integer(BigInt::one()),
+            factors: vec![(base, exponent)],
+        }
+    }
+
+    fn atom(expr: Expr) -> Self {
+        Self::power(expr, integer(1))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficient == RuntimeValue::Integer(BigInt::zero())
+    }
+
+    /// Identifies terms that only differ in their coefficient.
+    fn key(&self) -> String {
+        self.factors
+            .iter()
+            .map(|(base, exponent)| format!("({base})^({exponent})"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The sum of the constant exponents, to order terms by.
+    fn degree(&self) -> f64 {
+        self.factors
+            .iter()
+            .filter_map(|(_, exponent)| constant(exponent)?.as_f64())
+            .sum()
+    }
+
+    fn negate(&self) -> Self {
+        let coefficient = arithmetic(
+            &RuntimeValue::Integer(BigInt::zero()),
+            TokenType::Minus,
+            &self.coefficient,
+        );
+        Self {
+            coefficient: coefficient.expect("Real numbers can be negated"),
+            factors: self.factors.clone(),
+        }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let coefficient = arithmetic(&self.coefficient, TokenType::Star, &other.coefficient);
+        let mut factors = self.factors.clone();
+        for (base, exponent) in &other.factors {
+            match factors.iter().position(|(other, _)| same(other, base)) {
+                Some(i) => {
+                    let sum = simplify(&add(factors[i].1.clone(), exponent.clone()));
+                    if is_zero(&sum) {
+                        factors.remove(i);
+                    } else {
+                        factors[i].1 = sum;
+                    }
+                }
+                None => factors.push((base.clone(), exponent.clone())),
+            }
+        }
+        // Variables come first, in alphabetical order, then other factors.
+        factors.sort_by_key(|(base, _)| (!matches!(base, Expr::Variable(_)), base.to_string()));
+        Self {
+            coefficient: coefficient.expect("Products of real numbers are real"),
+            factors,
+        }
+    }
+
+    /// Raises the term to an integer power, unless the coefficient is 0 and the power negative.
+    fn pow(&self, exponent: &Expr) -> Option<Self> {
+        let power = constant(exponent)?;
+        Some(Self {
+            coefficient: arithmetic(&self.coefficient, TokenType::Caret, &power)?,
+            // A power of 0 leaves no factor, so x ^ 0 is 1.
+            factors: self
+                .factors
+                .iter()
+                .map(|(base, inner)| {
+                    (
+                        base.clone(),
+                        simplify(&mul(inner.clone(), exponent.clone())),
+                    )
+                })
+                .filter(|(_, exponent)| !is_zero(exponent))
+                .collect(),
+        })
+    }
+}
+
+/// The terms of a sum, with like terms collected.
+fn terms(expr: &Expr, expand: bool) -> Vec<Term> {
+    match expr {
+        Expr::Grouping(expr) => terms(expr, expand),
+        Expr::Unary { op, right } if op.kind == TokenType::Minus => {
+            terms(right, expand).iter().map(Term::negate).collect()
+        }
+        Expr::Binary { left, op, right } if op.kind == TokenType::Caret => {
+            power_terms(terms(left, expand), normalize(right, expand), expand)
+        }
+        Expr::Binary { left, op, right } if is_arithmetic(&op.kind) => {
+            let (left, right) = (terms(left, expand), terms(right, expand));
+            match op.kind {
+                TokenType::Plus => collect([left, right].concat()),
+                TokenType::Minus => {
+                    collect([left, right.iter().map(Term::negate).collect()].concat())
+                }
+                TokenType::Star => product_terms(&left, &right, expand),
+                _ => quotient_terms(left, right, expand),
+            }
+        }
+        _ => match constant(expr) {
+            Some(value) => collect(vec![Term::constant(value)]),
+            None => vec![Term::atom(map_children(expr, |child| {
+                normalize(child, expand)
+            }))],
+        },
+    }
+}
+
+/// Combines terms that only differ in their coefficient, and drops zero terms.
+fn collect(terms: Vec<Term>) -> Vec<Term> {
+    let mut collected: Vec<Term> = vec![];
+    for term in terms {
+        let key = term.key();
+        match collected.iter_mut().find(|other| other.key() == key) {
+            Some(other) => {
+                let sum = arithmetic(&other.coefficient, TokenType::Plus, &term.coefficient);
+                other.coefficient = sum.expect("Sums of real numbers are real");
+            }
+            None => collected.push(term),
+        }
+    }
+    collected.retain(|term| !term.is_zero());
+    collected
+}
+
+/// A sum as a single term, which is a factor of other terms.
+fn as_term(terms: &[Term]) -> Term {
+    match terms {
+        [] => Term::constant(RuntimeValue::Integer(BigInt::zero())),
+        [term] => term.clone(),
+        _ => Term::atom(build_sum(terms.to_vec())),
+    }
+}
+
+fn product_terms(left: &[Term], right: &[Term], expand: bool) -> Vec<Term> {
+    if expand || (left.len() <= 1 && right.len() <= 1) {
+        let products = left
+            .iter()
+            .flat_map(|a| right.iter().map(move |b| a.mul(b)))
+            .collect();
+        collect(products)
+    } else {
+        vec![as_term(left).mul(&as_term(right))]
+    }
+}
+
+fn quotient_terms(left: Vec<Term>, right: Vec<Term>, expand: bool) -> Vec<Term> {
+    let inverse = match right.as_slice() {
+        [term] => term.pow(&integer(-1)),
+        [] => None,
+        _ => Some(Term::power(build_sum(right.clone()), integer(-1))),
+    };
+    match inverse {
+        Some(inverse) => product_terms(&left, &[inverse], expand),
+        // Dividing by zero is left for the program to report.
+        None => vec![Term::atom(binary(
+            build_sum(left),
+            token(TokenType::Slash, "/"),
+            build_sum(right),
+        ))],
+    }
+}
+
+/// Most integer powers of a sum that are multiplied out by `expand`.
+const MAX_EXPANDED_POWER: i64 = 32;
+
+fn power_terms(base: Vec<Term>, exponent: Expr, expand: bool) -> Vec<Term> {
+    if let [term] = base.as_slice()
+        && term.factors.is_empty()
+        && let Some(power) = constant(&exponent)
+        && let Some(value) = arithmetic(&term.coefficient, TokenType::Caret, &power)
+    {
+        return collect(vec![Term::constant(value)]);
+    }
+    if let Some(RuntimeValue::Integer(n)) = constant(&exponent) {
+        let n = n.to_i64().unwrap_or(i64::MAX);
+        match base.as_slice() {
+            [term] => {
+                if let Some(power) = term.pow(&exponent) {
+                    return vec![power];
+                }
+            }
+            [] if n > 0 => return vec![],
+            _ if expand && (2..=MAX_EXPANDED_POWER).contains(&n) => {
+                let mut result = base.clone();
+                for _ in 1..n {
+                    result = product_terms(&result, &base, true);
+                }
+                return result;
+            }
+            _ => {}
+        }
+    }
+    vec![Term::power(build_sum(base), exponent)]
+}
+
+/// Writes terms as a sum, with the highest powers first.
+fn build_sum(mut terms: Vec<Term>) -> Expr {
+    terms.sort_by(|a, b| {
+        b.degree()
+            .partial_cmp(&a.degree())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.key().cmp(&b.key()))
+    });
+    let mut terms = terms.into_iter();
+    let Some(first) = terms.next() else {
+        return integer(0);
+    };
+    terms.fold(build_term(&first), |sum, term| {
+        if term.coefficient < RuntimeValue::Integer(BigInt::zero()) {
+            binary(
+                sum,
+                token(TokenType::Minus, "-"),
+                build_term(&term.negate()),
+            )
+        } else {
+            binary(sum, token(TokenType::Plus, "+"), build_term(&term))
+        }
+    })
+}
+
+/// Writes a term as a product, with factors that have negative powers
+/// and the denominator of the coefficient after a `/`.
+fn build_term(term: &Term) -> Expr {
+    let coefficient = || literal(term.coefficient.clone()).expect("Coefficients are real");
+    if term.factors.is_empty() {
+        return coefficient();
+    }
+
+    let mut numerator = vec![];
+    let mut denominator = vec![];
+    for (base, exponent) in &term.factors {
+        if is_negative(exponent) {
+            denominator.push(raw_power(base, &neg(exponent.clone())));
+        } else {
+            numerator.push(raw_power(base, exponent));
+        }
+    }
+    let (top, bottom) = match &term.coefficient {
+        RuntimeValue::Rational(r) => (
+            integer_literal(r.numerator().clone()),
+            integer_literal(r.denominator().clone()),
+        ),
+        _ => (coefficient(), integer(1)),
+    };
+    if !is_one(&bottom) {
+        denominator.insert(0, bottom);
+    }
+
+    let negative = is_one(&neg(top.clone())) && !numerator.is_empty();
+    if numerator.is_empty() || !(is_one(&top) || negative) {
+        numerator.insert(0, top);
+    }
+    let product = product_of(numerator).expect("The term has factors or a coefficient");
+    let numerator = if negative {
+        Expr::Unary {
+            op: token(TokenType::Minus, "-"),
+            right: Box::new(product),
+        }
+    } else {
+        product
+    };
+    match product_of(denominator) {
+        Some(denominator) => binary(numerator, token(TokenType::Slash, "/"), denominator),
+        None => numerator,
+    }
+}
+
+fn raw_power(base: &Expr, exponent: &Expr) -> Expr {
+    if is_one(exponent) {
+        base.clone()
+    } else {
+        binary(base.clone(), token(TokenType::Caret, "^"), exponent.clone())
+    }
+}
+
+fn product_of(factors: Vec<Expr>) -> Option<Expr> {
+    factors
+        .into_iter()
+        .reduce(|product, factor| binary(product, token(TokenType::Star, "*"), factor))
+}
+
+// Constructors that simplify as they build, so derivatives stay readable.
+
+fn add(left: Expr, right: Expr) -> Expr {
+    if let Some(sum) = fold(&left, TokenType::Plus, &right) {
+        return sum;
+    }
+    match (left, right) {
+        (left, right) if is_zero(&left) => right,
+        (left, right) if is_zero(&right) => left,
+        (left, Expr::Unary { op, right }) if op.kind == TokenType::Minus => sub(left, *right),
+        (left, right) if is_negative(&right) => sub(left, neg(right)),
+        (left, right) if same(&left, &right) => mul(integer(2), left),
+        (left, right) => binary(left, token(TokenType::Plus, "+"), right),
+    }
+}
+
+fn sub(left: Expr, right: Expr) -> Expr {
+    if let Some(difference) = fold(&left, TokenType::Minus, &right) {
+        return difference;
+    }
+    match (left, right) {
+        (left, right) if is_zero(&right) => left,
+        (left, right) if is_zero(&left) => neg(right),
+        (left, Expr::Unary { op, right }) if op.kind == TokenType::Minus => add(left, *right),
+        (left, right) if is_negative(&right) => add(left, neg(right)),
+        (left, right) if same(&left, &right) => integer(0),
+        (left, right) => binary(left, token(TokenType::Minus, "-"), right),
+    }
+}
+
+fn mul(left: Expr, right: Expr) -> Expr {
+    if let Some(product) = fold(&left, TokenType::Star, &right) {
+        return product;
+    }
+    match (left, right) {
+        (left, right) if is_zero(&left) || is_zero(&right) => integer(0),
+        (left, right) if is_one(&left) => right,
+        (left, right) if is_one(&right) => left,
+        (Expr::Unary { op, right: left }, right) if op.kind == TokenType::Minus => {
+            neg(mul(*left, right))
+        }
+        (left, Expr::Unary { op, right }) if op.kind == TokenType::Minus => neg(mul(left, *right)),
+        (left, right) if is_negative(&left) && is_one(&neg(left.clone())) => neg(right),
+        // Multiplying by 1 / u divides by u.
+        (
+            left,
+            Expr::Binary {
+                left: one,
+                op,
+                right,
+            },
+        ) if op.kind == TokenType::Slash && is_one(&one) => div(left, *right),
+        (
+            Expr::Binary {
+                left: one,
+                op,
+                right: denominator,
+            },
+            right,
+        ) if op.kind == TokenType::Slash && is_one(&one) => div(right, *denominator),
+        // Constants go first, and are combined: 2 * (3 * x) is 6 * x.
+        (left, right) if constant(&right).is_some() => mul(right, left),
+        (
+            left,
+            Expr::Binary {
+                left: inner,
+                op,
+                right,
+            },
+        ) if op.kind == TokenType::Star
+            && constant(&left).is_some()
+            && constant(&inner).is_some() =>
+        {
+            mul(mul(left, *inner), *right)
+        }
+        (left, right) if same(&left, &right) => pow(left, integer(2)),
+        (left, right) => binary(left, token(TokenType::Star, "*"), right),
+    }
+}
+
+fn div(left: Expr, right: Expr) -> Expr {
+    if let Some(quotient) = fold(&left, TokenType::Slash, &right) {
+        return quotient;
+    }
+    match (left, right) {
+        (left, right) if is_one(&right) => left,
+        (left, right) if is_zero(&left) && !is_zero(&right) => integer(0),
+        (Expr::Unary { op, right: left }, right) if op.kind == TokenType::Minus => {
+            neg(div(*left, right))
+        }
+        (left, right) if same(&left, &right) => integer(1),
+        (left, right) => binary(left, token(TokenType::Slash, "/"), right),
+    }
+}
+
+fn pow(base: Expr, exponent: Expr) -> Expr {
+    if let Some(power) = fold(&base, TokenType::Caret, &exponent) {
+        return power;
+    }
+    match (base, exponent) {
+        (_, exponent) if is_zero(&exponent) => integer(1),
+        (base, exponent) if is_one(&exponent) => base,
+        (base, _) if is_one(&base) => integer(1),
+        // (u ^ a) ^ n is u ^ (a * n) for an integer n.
+        (
+            Expr::Binary {
+                left: base,
+                op,
+                right: inner,
+            },
+            exponent,
+        ) if op.kind == TokenType::Caret
+            && matches!(constant(&exponent), Some(RuntimeValue::Integer(_))) =>
+        {
+            pow(*base, mul(*inner, exponent))
+        }
+        (base, exponent) => binary(base, token(TokenType::Caret, "^"), exponent),
+    }
+}
+
+fn neg(expr: Expr) -> Expr {
+    if let Some(value) = constant(&expr)
+        && let Ok(negated) = Interpreter::apply_binary(
+            RuntimeValue::Integer(BigInt::zero()),
+            &token(TokenType::Minus, "-"),
+            value,
+        )
+        && let Some(literal) = literal(negated)
+    {
+        return literal;
+    }
+    match expr {
+        Expr::Unary { op, right } if op.kind == TokenType::Minus => *right,
+        Expr::Binary { left, op, right } if op.kind == TokenType::Minus => sub(*right, *left),
+        Expr::Binary { left, op, right }
+            if op.kind == TokenType::Star && constant(&left).is_some() =>
+        {
+            mul(neg(*left), *right)
+        }
+        expr => Expr::Unary {
+            op: token(TokenType::Minus, "-"),
+            right: Box::new(expr),
+        },
+    }
+}
+
+fn binary(left: Expr, op: Token, right: Expr) -> Expr {
+    Expr::Binary {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::FunctionCall {
+        func: Box::new(Expr::Variable(name.into())),
+        paren: token(TokenType::LeftParen, "("),
+        arg: Box::new(arg),
+    }
+}
+
+fn integer(value: i64) -> Expr {
+    integer_literal(value.into())
+}
+
+fn integer_literal(value: BigInt) -> Expr {
+    Expr::Literal(LiteralValue::Integer(value))
+}
+
+/// A token for an operator that does not appear in the source.
+fn token(kind: TokenType, lexeme: &str) -> Token {
+    Token {
+        kind,
+        lexeme: lexeme.into(),
+        line: 0,
+        column: 0,
+    }
+}
+
+/// The value of a numeric literal.
+fn constant(expr: &Expr) -> Option<RuntimeValue> {
+    match expr {
+        Expr::Literal(LiteralValue::Number(n)) => Some(RuntimeValue::Number(*n)),
+        Expr::Literal(LiteralValue::Integer(n)) => Some(RuntimeValue::Integer(n.clone())),
+        Expr::Literal(LiteralValue::Rational(r)) => Some(RuntimeValue::Rational(r.clone())),
+        _ => None,
+    }
+}
+
+fn literal(value: RuntimeValue) -> Option<Expr> {
+    match value {
+        RuntimeValue::Number(n) => Some(Expr::Literal(LiteralValue::Number(n))),
+        RuntimeValue::Integer(n) => Some(Expr::Literal(LiteralValue::Integer(n))),
+        RuntimeValue::Rational(r) => Some(Expr::Literal(LiteralValue::Rational(r))),
+        _ => None,
+    }
+}
+
+/// Evaluates an operator on two constants. Results that are not real,
+/// or errors like a division by zero, are left for the program to report.
+fn fold(left: &Expr, kind: TokenType, right: &Expr) -> Option<Expr> {
+    literal(arithmetic(&constant(left)?, kind, &constant(right)?)?)
+}
+
+/// Applies an arithmetic operator to real numbers, if the result is real.
+fn arithmetic(left: &RuntimeValue, kind: TokenType, right: &RuntimeValue) -> Option<RuntimeValue> {
+    let lexeme = match kind {
+        TokenType::Plus => "+",
+        TokenType::Minus => "-",
+        TokenType::Star => "*",
+        TokenType::Slash => "/",
+        _ => "^",
+    };
+    let result =
+        Interpreter::apply_binary(left.clone(), &token(kind, lexeme), right.clone()).ok()?;
+    literal(result.clone()).map(|_| result)
+}
+
+fn is_zero(expr: &Expr) -> bool {
+    constant(expr).is_some_and(|value| value == RuntimeValue::Integer(BigInt::zero()))
+}
+
+fn is_one(expr: &Expr) -> bool {
+    constant(expr).is_some_and(|value| value == RuntimeValue::Integer(BigInt::one()))
+}
+
+fn is_negative(expr: &Expr) -> bool {
+    constant(expr).is_some_and(|value| value < RuntimeValue::Integer(BigInt::zero()))
+}
+
+/// Checks if two expressions are written the same, ignoring token positions.
+fn same(left: &Expr, right: &Expr) -> bool {
+    left.to_string() == right.to_string()
+}
+
+/// A formula kept as an expression, with the scope it was written in,
+/// where its names are looked up when it is evaluated.
+#[derive(Clone, Debug)]
+pub struct Symbolic {
+    expr: Expr,
+    scope: Rc<RefCell<Environment>>,
+}
+
+impl Symbolic {
+    /// Creates a formula in its simplified form.
+    pub fn new(expr: &Expr, scope: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            expr: simplify(expr),
+            scope,
+        }
+    }
+
+    /// Creates a formula that is kept in the given form, like a factored polynomial.
+    pub fn unsimplified(expr: Expr, scope: Rc<RefCell<Environment>>) -> Self {
+        Self { expr, scope }
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn scope(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.scope)
+    }
+
+    /// Creates a formula in the same scope.
+    pub fn with_expr(&self, expr: &Expr) -> Self {
+        Self::new(expr, self.scope())
+    }
+
+    /// Evaluates the formula with the values its names have in its scope.
+    pub fn eval(&self) -> Result<RuntimeValue, String> {
+        Interpreter::execute(&self.expr, self.scope())
+    }
+}
+
+impl PartialEq for Symbolic {
+    /// Formulas are equal if their simplified forms are the same.
+    fn eq(&self, other: &Self) -> bool {
+        same(&self.expr, &other.expr)
+    }
+}
+
+impl Display for Symbolic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+/// Converts a number or a formula to an expression that can be part of a formula.
+pub fn to_expr(value: &RuntimeValue) -> Option<Expr> {
+    match value {
+        RuntimeValue::Symbolic(symbolic) => Some(symbolic.expr().clone()),
+        RuntimeValue::Complex(c) => Some(add(
+            literal(RuntimeValue::Number(c.re))?,
+            Expr::Literal(LiteralValue::Imaginary(c.im)),
+        )),
+        _ => literal(value.clone()),
+    }
+}
+
+/// Largest coefficient whose divisors are tried as rational roots by `factor`.
+const MAX_ROOT_SEARCH: i64 = 1_000_000_000_000;
+
+/// Factors a polynomial in one variable over the rationals, like
+/// `x ^ 3 - x` into `x * (x - 1) * (x + 1)`. Factors without rational
+/// roots are left as they are.
+pub fn factor(expr: &Expr) -> Result<Expr, String> {
+    let not_polynomial = || {
+        format!(
+            "factor() expects a polynomial in one variable with exact coefficients, found {expr}"
+        )
+    };
+    let (variable, coefficients) = polynomial(expr).ok_or_else(not_polynomial)?;
+    let coefficients = coefficients
+        .iter()
+        .map(RuntimeValue::as_rational)
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(not_polynomial)?;
+    let Some(variable) = variable else {
+        return expand(expr);
+    };
+
+    // Pull out the lowest power of the variable.
+    let lowest = coefficients
+        .iter()
+        .position(|c| !c.is_zero())
+        .expect("A polynomial with a variable has a nonzero coefficient");
+    let coefficients = &coefficients[lowest..];
+
+    // Split the polynomial into a constant and a primitive polynomial.
+    let constant = content(coefficients);
+    let mut remaining: Vec<Rational> = coefficients
+        .iter()
+        .map(|c| c.div(&constant).expect("The content is not zero"))
+        .collect();
+
+    let mut roots = vec![];
+    for (numerator, denominator) in root_candidates(&remaining) {
+        let root =
+            Rational::new(numerator.into(), denominator.into()).expect("Divisors are not zero");
+        let mut multiplicity = 0;
+        while remaining.len() > 1 && evaluate(&remaining, &root).is_zero() {
+            remaining = divide_by_root(&remaining, &root);
+            multiplicity += 1;
+        }
+        if multiplicity > 0 {
+            roots.push((numerator, denominator, multiplicity));
+        }
+    }
+
+    let x = Expr::Variable(variable);
+    let mut factors = vec![];
+    if lowest > 0 {
+        factors.push(raw_power(&x, &integer(lowest as i64)));
+    }
+    for (numerator, denominator, multiplicity) in roots {
+        let linear = build_sum(vec![
+            Term {
+                coefficient: RuntimeValue::Integer(denominator.into()),
+                factors: vec![(x.clone(), integer(1))],
+            },
+            Term::constant(RuntimeValue::Integer((-numerator).into())),
+        ]);
+        factors.push(raw_power(&linear, &integer(multiplicity)));
+    }
+    // Dividing out a root r/s leaves s as the content of the rest.
+    let divisor = content(&remaining);
+    let rest: Vec<Rational> = remaining
+        .iter()
+        .map(|c| c.div(&divisor).expect("The content is not zero"))
+        .collect();
+    if rest.len() > 1 {
+        let terms = rest
+            .iter()
+            .enumerate()
+            .map(|(degree, c)| {
+                Term::constant(RuntimeValue::from_rational(c.clone()))
+                    .mul(&Term::power(x.clone(), integer(degree as i64)))
+            })
+            .collect();
+        factors.push(build_sum(collect(terms)));
+    }
+
+    if factors.is_empty() {
+        return Ok(literal(RuntimeValue::from_rational(constant)).expect("Rationals are real"));
+    }
+    // The constant leads the product, which nests to the left like `2 * (x - 1) * (x + 1)`.
+    if (-constant.numerator()).is_one() {
+        factors[0] = Expr::Unary {
+            op: token(TokenType::Minus, "-"),
+            right: Box::new(factors[0].clone()),
+        };
+    } else if !constant.numerator().is_one() {
+        factors.insert(0, integer_literal(constant.numerator().clone()));
+    }
+    let product = product_of(factors).expect("There is at least one factor");
+    if constant.is_integer() {
+        Ok(product)
+    } else {
+        Ok(binary(
+            product,
+            token(TokenType::Slash, "/"),
+            integer_literal(constant.denominator().clone()),
+        ))
+    }
+}
+
+/// Highest degree of a polynomial read from a formula, to refuse ones too large to store.
+const MAX_DEGREE: i64 = 100_000;
+
+/// The variable and the coefficients, lowest degree first, of a formula
+/// that is a polynomial in one variable with real coefficients.
+pub fn polynomial(expr: &Expr) -> Option<(Option<String>, Vec<RuntimeValue>)> {
+    let mut variable: Option<String> = None;
+    let mut coefficients: Vec<RuntimeValue> = vec![];
+    for term in terms(expr, true) {
+        let degree = match term.factors.as_slice() {
+            [] => 0,
+            [(Expr::Variable(name), exponent)] => {
+                if variable.get_or_insert_with(|| name.clone()) != name {
+                    return None;
+                }
+                match constant(exponent)? {
+                    RuntimeValue::Integer(n) => match n.to_i64()? {
+                        n if (0..=MAX_DEGREE).contains(&n) => n as usize,
+                        _ => return None,
+                    },
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        if coefficients.len() <= degree {
+            coefficients.resize(degree + 1, RuntimeValue::Integer(BigInt::zero()));
+        }
+        coefficients[degree] = term.coefficient;
+    }
+    Some((variable, coefficients))
+}
+
+/// Candidates `r/s` for rational roots, where `r` divides the constant coefficient
+/// and `s` the leading one, smallest first and positive before negative.
+fn root_candidates(coefficients: &[Rational]) -> Vec<(i64, i64)> {
+    let small = |c: &Rational| {
+        c.numerator()
+            .to_i64()
+            .filter(|n| n.abs() <= MAX_ROOT_SEARCH)
+    };
+    let (Some(first), Some(last)) = (
+        coefficients.first().and_then(small),
+        coefficients.last().and_then(small),
+    ) else {
+        return vec![];
+    };
+    let mut candidates = vec![];
+    for numerator in divisors(first) {
+        for denominator in divisors(last) {
+            if gcd(numerator, denominator) == 1 {
+                candidates.push((numerator, denominator));
+                candidates.push((-numerator, denominator));
+            }
+        }
+    }
+    candidates.sort_by(|(a, b), (c, d)| {
+        let size = (a.abs() as i128 * *d as i128).cmp(&(c.abs() as i128 * *b as i128));
+        size.then_with(|| c.cmp(a))
+    });
+    candidates
+}
+
+fn divisors(n: i64) -> Vec<i64> {
+    let n = n.abs();
+    let mut divisors = vec![];
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            divisors.push(d);
+            if d != n / d {
+                divisors.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    divisors
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Evaluates a polynomial with Horner's method.
+fn evaluate(coefficients: &[Rational], x: &Rational) -> Rational {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Rational::from_integer(BigInt::zero()), |sum, c| {
+            sum.mul(x).add(c)
+        })
+}
+
+/// Divides a polynomial by `x - root`, which must be a factor, with synthetic division.
+fn divide_by_root(coefficients: &[Rational], root: &Rational) -> Vec<Rational> {
+    let mut quotient = vec![Rational::from_integer(BigInt::zero()); coefficients.len() - 1];
+    let mut carry = Rational::from_integer(BigInt::zero());
+    for degree in (1..coefficients.len()).rev() {
+        carry = carry.mul(root).add(&coefficients[degree]);
+        quotient[degree - 1] = carry.clone();
+    }
+    quotient
+}
+
+/// The number that divides a polynomial into one with coprime integer
+/// coefficients and a positive leading coefficient.
+fn content(coefficients: &[Rational]) -> Rational {
+    let multiple = coefficients.iter().fold(BigInt::one(), |multiple, c| {
+        let divisor = multiple.gcd(c.denominator());
+        let (quotient, _) = (&multiple * c.denominator())
+            .div_rem(&divisor)
+            .expect("Denominators are not zero");
+        quotient
+    });
+    let gcd = coefficients.iter().fold(BigInt::zero(), |gcd, c| {
+        let (scaled, _) = (c.numerator() * &multiple)
+            .div_rem(c.denominator())
+            .expect("Denominators are not zero");
+        gcd.gcd(&scaled)
+    });
+    let leading = coefficients
+        .last()
+        .expect("The polynomial has coefficients");
+    let gcd = if leading.numerator().is_negative() {
+        -&gcd
+    } else {
+        gcd
+    };
+    Rational::new(gcd, multiple).expect("The multiple is not zero")
+}
@@ -1,3 +1,6 @@
+use crate::bigint::BigInt;
+use crate::rational::Rational;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Token {
     pub kind: TokenType,
@@ -13,12 +16,16 @@ pub enum TokenType {
     Minus,
     Star,
     Slash,
+    Percent,
+    Caret,
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
     LeftSquareBracket,
     RightSquareBracket,
+    LeftMatrixBracket,
+    RightMatrixBracket,
     Comma,
 
     // One or two character tokens
@@ -30,22 +37,38 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    DotDot,
+
+    // An element-wise operator, like `.*` for Star
+    Dotted(Box<TokenType>),
 
     // Data tokens
     Identifier(String),
     Number(f64),
+    Integer(BigInt),
+    Rational(Rational),
+    Imaginary(f64),
     String(String),
+    /// A unit in single quotes, like `'m/s^2'`.
+    Unit(String),
+    Operator(String),
 
     // Keywords
     If,
     Then,
     Else,
     Match,
+    Div,
+    Sym,
+    Infix,
+    InfixLeft,
+    InfixRight,
 
     // Special symbols
     MapsTo,
     FatArrow,
     Binding,
+    Colon,
     EndStmt,
 
     // Last token
@@ -0,0 +1,1305 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::ast::{Expr, LiteralValue, MatchArm, TypeAnnotation};
+use crate::builtins;
+use crate::runtime::Environment;
+use crate::symbolic;
+use crate::token::{Token, TokenType};
+
+/// A static type, as inferred by the `TypeChecker`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Number,
+    String,
+    Boolean,
+    List(Box<Type>),
+    Stream(Box<Type>),
+    /// A list or a stream, which the sequence builtins like `head` accept.
+    Sequence(Box<Type>),
+    Function(Box<Type>, Box<Type>),
+    /// A type that is not known yet.
+    Var(usize),
+    /// A type parameter of a polymorphic type, such as `a` in `a -> a`.
+    Generic(usize),
+    /// A type that is only known at runtime, which is compatible with every type.
+    /// Heterogeneous lists and some builtins have this type.
+    Dynamic,
+    /// The parameter of a builtin like `poly`, which reads a formula in an
+    /// undefined variable as written. Otherwise it is like `Dynamic`.
+    Formula,
+}
+
+fn fun(param: Type, result: Type) -> Type {
+    Type::Function(Box::new(param), Box::new(result))
+}
+
+fn list(element: Type) -> Type {
+    Type::List(Box::new(element))
+}
+
+fn stream(element: Type) -> Type {
+    Type::Stream(Box::new(element))
+}
+
+fn sequence(element: Type) -> Type {
+    Type::Sequence(Box::new(element))
+}
+
+/// Names type parameters `a`, `b`, ..., `z`, `a1`, ...
+fn generic_name(index: usize) -> String {
+    let letter = (b'a' + (index % 26) as u8) as char;
+    match index / 26 {
+        0 => letter.to_string(),
+        n => format!("{letter}{n}"),
+    }
+}
+
+impl Type {
+    fn fmt_with(&self, f: &mut std::fmt::Formatter<'_>, in_param: bool) -> std::fmt::Result {
+        match self {
+            Self::Number => write!(f, "Number"),
+            Self::String => write!(f, "String"),
+            Self::Boolean => write!(f, "Boolean"),
+            Self::List(element) => write!(f, "List<{element}>"),
+            Self::Stream(element) => write!(f, "Stream<{element}>"),
+            Self::Sequence(element) => write!(f, "Sequence<{element}>"),
+            Self::Function(param, result) => {
+                if in_param {
+                    write!(f, "(")?;
+                }
+                param.fmt_with(f, true)?;
+                write!(f, " -> ")?;
+                result.fmt_with(f, false)?;
+                if in_param {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Self::Var(id) => write!(f, "t{id}"),
+            Self::Generic(index) => write!(f, "{}", generic_name(*index)),
+            Self::Dynamic => write!(f, "Dynamic"),
+            Self::Formula => write!(f, "Formula"),
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, false)
+    }
+}
+
+/// A group of types that an operator is defined for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Class {
+    /// Types supporting `+`.
+    Add,
+    /// Types supporting `<`, `<=`, `>` and `>=`.
+    Ord,
+    /// Types the math builtins like `sin` apply to:
+    /// numbers, and lists of them nested to any depth.
+    Math,
+    /// Types `len` applies to: lists and strings.
+    Length,
+}
+
+impl Class {
+    fn accepts(&self, ty: &Type) -> bool {
+        match self {
+            // A sequence may be a list.
+            Self::Add => matches!(
+                ty,
+                Type::Number | Type::String | Type::List(_) | Type::Sequence(_)
+            ),
+            Self::Ord => matches!(ty, Type::Number | Type::String | Type::Boolean),
+            Self::Math => matches!(ty, Type::Number),
+            Self::Length => matches!(ty, Type::String | Type::List(_) | Type::Sequence(_)),
+        }
+    }
+
+    /// Whether the constraint on a list applies to its elements instead.
+    fn applies_to_elements(&self) -> bool {
+        matches!(self, Self::Math)
+    }
+
+    fn unsupported(&self, ty: &str) -> String {
+        match self {
+            Self::Add => format!("Unsupported operand type for '+': {ty}"),
+            Self::Ord => format!("Unsupported operand type for '<': {ty}"),
+            Self::Math => format!("Expected a number or a list of numbers, found {ty}"),
+            Self::Length => format!("len() expects a list or string, found {ty}"),
+        }
+    }
+}
+
+/// A possibly polymorphic type, with constraints on its type parameters.
+#[derive(Clone, Debug)]
+struct Scheme {
+    generics: usize,
+    constraints: Vec<(usize, Class)>,
+    ty: Type,
+}
+
+impl Scheme {
+    fn mono(ty: Type) -> Self {
+        Self {
+            generics: 0,
+            constraints: vec![],
+            ty,
+        }
+    }
+
+    fn poly(generics: usize, ty: Type) -> Self {
+        Self {
+            generics,
+            constraints: vec![],
+            ty,
+        }
+    }
+
+    /// The type of a math builtin like `sin`, which maps over lists.
+    fn math() -> Self {
+        Self {
+            generics: 1,
+            constraints: vec![(0, Class::Math)],
+            ty: fun(Type::Generic(0), Type::Generic(0)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum VarState {
+    Unbound { level: usize },
+    Bound(Type),
+}
+
+type Location = Option<(usize, usize)>;
+
+#[derive(Clone, Debug)]
+struct Constraint {
+    ty: Type,
+    class: Class,
+    location: Location,
+}
+
+/// `(Number -> Number) -> Number -> Number -> Number`, like `integrate`.
+fn interval_method() -> Type {
+    fun(
+        fun(Type::Number, Type::Number),
+        fun(Type::Number, fun(Type::Number, Type::Number)),
+    )
+}
+
+/// `(Number -> Number) -> Number -> Number`, like `newton`.
+fn point_method() -> Type {
+    fun(
+        fun(Type::Number, Type::Number),
+        fun(Type::Number, Type::Number),
+    )
+}
+
+/// `List<Number> -> List<Number> -> result`, like `covariance`.
+fn paired_statistic(result: Type) -> Type {
+    fun(list(Type::Number), fun(list(Type::Number), result))
+}
+
+/// The type of a distribution's pdf or cdf, taking `parameters` numbers and then `x`.
+fn distribution(parameters: usize) -> Type {
+    (0..=parameters).fold(Type::Number, |result, _| fun(Type::Number, result))
+}
+
+/// Signatures of the native functions.
+/// Globals not listed here have the `Dynamic` type.
+fn builtin_signatures() -> Vec<(&'static str, Scheme)> {
+    use Type::{Boolean, Dynamic, Generic, Number};
+    let a = || Generic(0);
+    let b = || Generic(1);
+
+    vec![
+        ("nil", Scheme::poly(1, a())),
+        ("true", Scheme::mono(Boolean)),
+        ("false", Scheme::mono(Boolean)),
+        ("sin", Scheme::math()),
+        ("cos", Scheme::math()),
+        ("sqrt", Scheme::math()),
+        ("tan", Scheme::math()),
+        ("asin", Scheme::math()),
+        ("acos", Scheme::math()),
+        ("atan", Scheme::math()),
+        ("sinh", Scheme::math()),
+        ("cosh", Scheme::math()),
+        ("tanh", Scheme::math()),
+        ("asinh", Scheme::math()),
+        ("acosh", Scheme::math()),
+        ("atanh", Scheme::math()),
+        ("log10", Scheme::math()),
+        ("log2", Scheme::math()),
+        ("log1p", Scheme::math()),
+        ("expm1", Scheme::math()),
+        ("exp2", Scheme::math()),
+        ("cbrt", Scheme::math()),
+        ("floor", Scheme::math()),
+        ("ceil", Scheme::math()),
+        ("round", Scheme::math()),
+        ("trunc", Scheme::math()),
+        ("fract", Scheme::math()),
+        ("sign", Scheme::math()),
+        ("atan2", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("hypot", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("min", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("max", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("pi", Scheme::mono(Number)),
+        ("e", Scheme::mono(Number)),
+        ("tau", Scheme::mono(Number)),
+        ("inf", Scheme::mono(Number)),
+        ("nan", Scheme::mono(Number)),
+        ("exp", Scheme::math()),
+        ("ln", Scheme::math()),
+        ("abs", Scheme::math()),
+        ("arg", Scheme::math()),
+        ("conj", Scheme::math()),
+        ("re", Scheme::math()),
+        ("im", Scheme::math()),
+        ("diff", Scheme::poly(1, fun(a(), a()))),
+        (
+            "grad",
+            Scheme::poly(1, fun(fun(a(), Number), fun(a(), a()))),
+        ),
+        ("clock", Scheme::poly(1, fun(a(), Number))),
+        ("bool", Scheme::poly(1, fun(a(), Boolean))),
+        ("str", Scheme::poly(1, fun(a(), Type::String))),
+        ("print", Scheme::poly(2, fun(a(), b()))),
+        ("println", Scheme::poly(2, fun(a(), b()))),
+        (
+            "iterate",
+            Scheme::poly(1, fun(fun(a(), a()), fun(a(), stream(a())))),
+        ),
+        ("repeat", Scheme::poly(1, fun(a(), stream(a())))),
+        ("cycle", Scheme::poly(1, fun(list(a()), stream(a())))),
+        (
+            "unfold",
+            Scheme::poly(2, fun(fun(a(), list(Dynamic)), fun(a(), stream(b())))),
+        ),
+        (
+            "map",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), sequence(b())))),
+        ),
+        (
+            "filter",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), sequence(a())))),
+        ),
+        (
+            "take_while",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), sequence(a())))),
+        ),
+        (
+            "take",
+            Scheme::poly(1, fun(Number, fun(sequence(a()), list(a())))),
+        ),
+        (
+            "len",
+            Scheme {
+                generics: 1,
+                constraints: vec![(0, Class::Length)],
+                ty: fun(a(), Number),
+            },
+        ),
+        ("head", Scheme::poly(1, fun(sequence(a()), a()))),
+        ("tail", Scheme::poly(1, fun(sequence(a()), sequence(a())))),
+        (
+            "foldl",
+            Scheme::poly(
+                2,
+                fun(fun(b(), fun(a(), b())), fun(b(), fun(sequence(a()), b()))),
+            ),
+        ),
+        (
+            "foldr",
+            Scheme::poly(
+                2,
+                fun(fun(a(), fun(b(), b())), fun(b(), fun(sequence(a()), b()))),
+            ),
+        ),
+        (
+            "zip",
+            Scheme::poly(
+                2,
+                fun(sequence(a()), fun(sequence(b()), list(list(Dynamic)))),
+            ),
+        ),
+        (
+            "enumerate",
+            Scheme::poly(1, fun(list(a()), list(list(Dynamic)))),
+        ),
+        ("reverse", Scheme::poly(1, fun(list(a()), list(a())))),
+        ("sort", Scheme::poly(1, fun(list(a()), list(a())))),
+        (
+            "sort_by",
+            Scheme::poly(2, fun(fun(a(), b()), fun(list(a()), list(a())))),
+        ),
+        (
+            "any",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), Boolean))),
+        ),
+        (
+            "all",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), Boolean))),
+        ),
+        (
+            "find",
+            Scheme::poly(2, fun(fun(a(), b()), fun(sequence(a()), a()))),
+        ),
+        ("flatten", Scheme::poly(1, fun(list(list(a())), list(a())))),
+        (
+            "range",
+            Scheme::mono(fun(Number, fun(Number, list(Number)))),
+        ),
+        ("memo", Scheme::poly(2, fun(fun(a(), b()), fun(a(), b())))),
+        (
+            "memo_bounded",
+            Scheme::poly(2, fun(Number, fun(fun(a(), b()), fun(a(), b())))),
+        ),
+        ("memo_clear", Scheme::poly(2, fun(a(), b()))),
+        ("memo_size", Scheme::poly(1, fun(a(), Number))),
+        ("to", Scheme::mono(fun(Dynamic, fun(Dynamic, Dynamic)))),
+        ("poly", Scheme::mono(fun(Type::Formula, Dynamic))),
+        ("mean", Scheme::mono(fun(list(Number), Number))),
+        ("median", Scheme::mono(fun(list(Number), Number))),
+        ("mode", Scheme::mono(fun(list(Number), Number))),
+        ("variance", Scheme::mono(fun(list(Number), Number))),
+        ("pvariance", Scheme::mono(fun(list(Number), Number))),
+        ("stddev", Scheme::mono(fun(list(Number), Number))),
+        ("pstddev", Scheme::mono(fun(list(Number), Number))),
+        (
+            "quantile",
+            Scheme::mono(fun(list(Number), fun(Number, Number))),
+        ),
+        ("covariance", Scheme::mono(paired_statistic(Number))),
+        ("pcovariance", Scheme::mono(paired_statistic(Number))),
+        ("correlation", Scheme::mono(paired_statistic(Number))),
+        (
+            "histogram",
+            Scheme::mono(fun(list(Number), fun(Number, list(Number)))),
+        ),
+        (
+            "linear_regression",
+            Scheme::mono(paired_statistic(list(Number))),
+        ),
+        ("gcd", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("lcm", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("factorial", Scheme::mono(fun(Number, Number))),
+        ("binomial", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("is_prime", Scheme::mono(fun(Number, Boolean))),
+        ("primes", Scheme::mono(fun(Number, list(Number)))),
+        ("factorize", Scheme::mono(fun(Number, list(Number)))),
+        (
+            "mod_pow",
+            Scheme::mono(fun(Number, fun(Number, fun(Number, Number)))),
+        ),
+        (
+            "mod_inverse",
+            Scheme::mono(fun(Number, fun(Number, Number))),
+        ),
+        (
+            "permutations",
+            Scheme::poly(1, fun(list(a()), list(list(a())))),
+        ),
+        (
+            "combinations",
+            Scheme::poly(1, fun(list(a()), fun(Number, list(list(a()))))),
+        ),
+        ("gamma", Scheme::math()),
+        ("beta", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("seed", Scheme::poly(1, fun(Number, a()))),
+        ("random", Scheme::poly(1, fun(a(), Number))),
+        ("randint", Scheme::mono(fun(Number, fun(Number, Number)))),
+        ("shuffle", Scheme::poly(1, fun(list(a()), list(a())))),
+        ("choice", Scheme::poly(1, fun(list(a()), a()))),
+        (
+            "random_uniform",
+            Scheme::mono(fun(Number, fun(Number, Number))),
+        ),
+        (
+            "random_normal",
+            Scheme::mono(fun(Number, fun(Number, Number))),
+        ),
+        ("random_exponential", Scheme::mono(fun(Number, Number))),
+        ("random_poisson", Scheme::mono(fun(Number, Number))),
+        (
+            "random_binomial",
+            Scheme::mono(fun(Number, fun(Number, Number))),
+        ),
+        ("uniform_pdf", Scheme::mono(distribution(2))),
+        ("uniform_cdf", Scheme::mono(distribution(2))),
+        ("normal_pdf", Scheme::mono(distribution(2))),
+        ("normal_cdf", Scheme::mono(distribution(2))),
+        ("exponential_pdf", Scheme::mono(distribution(1))),
+        ("exponential_cdf", Scheme::mono(distribution(1))),
+        ("poisson_pmf", Scheme::mono(distribution(1))),
+        ("poisson_cdf", Scheme::mono(distribution(1))),
+        ("binomial_pmf", Scheme::mono(distribution(2))),
+        ("binomial_cdf", Scheme::mono(distribution(2))),
+        ("integrate", Scheme::mono(interval_method())),
+        (
+            "integrate_tol",
+            Scheme::mono(fun(Number, interval_method())),
+        ),
+        ("root", Scheme::mono(interval_method())),
+        ("root_tol", Scheme::mono(fun(Number, interval_method()))),
+        ("newton", Scheme::mono(point_method())),
+        ("newton_tol", Scheme::mono(fun(Number, point_method()))),
+        (
+            "minimize",
+            Scheme::poly(1, fun(fun(a(), Number), fun(a(), a()))),
+        ),
+        (
+            "minimize_tol",
+            Scheme::poly(1, fun(Number, fun(fun(a(), Number), fun(a(), a())))),
+        ),
+    ]
+}
+
+/// Infers the types of a program before it runs, reporting type errors
+/// with the location they were found at.
+///
+/// This is Hindley-Milner inference with let-polymorphism, extended with a
+/// `Dynamic` type for values whose type can only be known at runtime.
+/// Like the interpreter, `nil` may be used where any type is expected,
+/// and any value may be used as a condition.
+pub struct TypeChecker {
+    vars: Vec<VarState>,
+    scopes: Vec<HashMap<String, Scheme>>,
+    level: usize,
+    constraints: Vec<Constraint>,
+    /// Locations of the enclosing expressions, for errors in expressions
+    /// without a location of their own.
+    locations: Vec<(usize, usize)>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        let signatures: HashMap<&str, Scheme> = builtin_signatures().into_iter().collect();
+        // The random builtins are bound by the interpreter, which owns the generator.
+        let environment = Environment::new();
+        let globals = environment
+            .names()
+            .map(String::as_str)
+            .chain(builtins::RANDOM_BUILTINS)
+            .map(|name| {
+                let scheme = signatures
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| Scheme::mono(Type::Dynamic));
+                (name.to_string(), scheme)
+            })
+            .collect();
+
+        Self {
+            vars: vec![],
+            scopes: vec![globals],
+            level: 0,
+            constraints: vec![],
+            locations: vec![],
+        }
+    }
+
+    pub fn report(errors: &[String]) -> String {
+        format!("Type errors:\n{}", errors.join("\n"))
+    }
+
+    /// Checks a program, returning the type of its last statement.
+    pub fn check(&mut self, expr: &Expr) -> Result<Type, Vec<String>> {
+        let statements = match expr {
+            Expr::Program { statements } => statements.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+
+        let mut errors = vec![];
+        let mut result = Type::Dynamic;
+        self.scopes.push(HashMap::new());
+        self.declare_bindings(statements);
+
+        for stmt in statements {
+            self.locations.clear();
+            match self.infer(stmt).and_then(|ty| {
+                self.check_constraints()?;
+                Ok(ty)
+            }) {
+                Ok(ty) => result = ty,
+                Err(message) => {
+                    errors.push(message);
+                    // Avoid cascading errors from uses of a binding that failed.
+                    if let Expr::Binding { name, .. } = stmt {
+                        self.bind(name.clone(), Scheme::mono(Type::Dynamic));
+                    }
+                    result = Type::Dynamic;
+                }
+            }
+        }
+        self.scopes.pop();
+
+        if errors.is_empty() {
+            let ty = self.resolve(&result);
+            Ok(Self::normalise(&ty, &mut HashMap::new()))
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn format_error(&self, location: Location, message: &str) -> String {
+        match location.or(self.locations.last().copied()) {
+            Some((line, column)) => format!("[Line {line}, Col {column}] {message}"),
+            None => message.to_string(),
+        }
+    }
+
+    fn token_location(token: &Token) -> Location {
+        Some((token.line, token.column))
+    }
+
+    /// Finds the location of an expression, from the first token inside it.
+    fn locate(expr: &Expr) -> Location {
+        match expr {
+            Expr::Binary { op, .. } | Expr::Unary { op, .. } | Expr::Section { op, .. } => {
+                Self::token_location(op)
+            }
+            Expr::FunctionCall { func, paren, .. } => {
+                Self::locate(func).or(Self::token_location(paren))
+            }
+            Expr::Grouping(expr) => Self::locate(expr),
+            Expr::Assign { expr, equal, .. } => Self::locate(expr).or(Self::token_location(equal)),
+            Expr::Binding { expr, .. } => Self::locate(expr),
+            Expr::FunctionDef { body, .. } => Self::locate(body),
+            Expr::If { keyword, .. } => Self::token_location(keyword),
+            Expr::Program { statements }
+            | Expr::Block { statements }
+            | Expr::List {
+                elements: statements,
+            } => statements.iter().find_map(Self::locate),
+            Expr::Matrix { rows } => rows.iter().flatten().find_map(Self::locate),
+            Expr::Interval { low, high } => Self::locate(low).or_else(|| Self::locate(high)),
+            Expr::Symbolic(expr) => Self::locate(expr),
+            Expr::Match { arms } => arms.iter().find_map(|arm| Self::locate(&arm.pattern)),
+            Expr::Variable(_) | Expr::Literal(_) | Expr::Empty => None,
+        }
+    }
+
+    // Type variables
+
+    fn fresh(&mut self) -> Type {
+        self.vars.push(VarState::Unbound { level: self.level });
+        Type::Var(self.vars.len() - 1)
+    }
+
+    /// Follows bound type variables, at the top level of the type only.
+    fn shallow_resolve(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::Var(id) = ty {
+            match &self.vars[id] {
+                VarState::Bound(bound) => ty = bound.clone(),
+                VarState::Unbound { .. } => break,
+            }
+        }
+        ty
+    }
+
+    /// Replaces every bound type variable in the type.
+    fn resolve(&self, ty: &Type) -> Type {
+        match self.shallow_resolve(ty) {
+            Type::List(element) => list(self.resolve(&element)),
+            Type::Stream(element) => stream(self.resolve(&element)),
+            Type::Sequence(element) => sequence(self.resolve(&element)),
+            Type::Function(param, result) => fun(self.resolve(&param), self.resolve(&result)),
+            other => other,
+        }
+    }
+
+    /// Renames unknown types to type parameters, for display.
+    fn normalise(ty: &Type, names: &mut HashMap<usize, usize>) -> Type {
+        match ty {
+            Type::Var(id) => {
+                let next = names.len();
+                Type::Generic(*names.entry(*id).or_insert(next))
+            }
+            Type::List(element) => list(Self::normalise(element, names)),
+            Type::Stream(element) => stream(Self::normalise(element, names)),
+            Type::Sequence(element) => sequence(Self::normalise(element, names)),
+            Type::Function(param, result) => fun(
+                Self::normalise(param, names),
+                Self::normalise(result, names),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Describes types for an error message, naming unknown types consistently.
+    fn describe(&self, types: &[&Type]) -> Vec<String> {
+        let mut names = HashMap::new();
+        types
+            .iter()
+            .map(|ty| Self::normalise(&self.resolve(ty), &mut names).to_string())
+            .collect()
+    }
+
+    // Unification
+
+    fn unify(&mut self, expected: &Type, found: &Type, location: Location) -> Result<(), String> {
+        self.unify_types(expected, found).map_err(|_| {
+            let described = self.describe(&[expected, found]);
+            self.format_error(
+                location,
+                &format!(
+                    "Type mismatch: expected {}, found {}",
+                    described[0], described[1]
+                ),
+            )
+        })
+    }
+
+    fn unify_types(&mut self, a: &Type, b: &Type) -> Result<(), ()> {
+        let a = self.shallow_resolve(a);
+        let b = self.shallow_resolve(b);
+        match (&a, &b) {
+            (Type::Dynamic | Type::Formula, _) | (_, Type::Dynamic | Type::Formula) => Ok(()),
+            (Type::Var(x), Type::Var(y)) if x == y => Ok(()),
+            (Type::Var(id), other) | (other, Type::Var(id)) => self.bind_var(*id, other),
+            (Type::Number, Type::Number)
+            | (Type::String, Type::String)
+            | (Type::Boolean, Type::Boolean) => Ok(()),
+            (Type::List(x), Type::List(y))
+            | (Type::Stream(x), Type::Stream(y))
+            | (Type::Sequence(x), Type::List(y) | Type::Stream(y) | Type::Sequence(y))
+            | (Type::List(x) | Type::Stream(x), Type::Sequence(y)) => self.unify_types(x, y),
+            (Type::Function(p1, r1), Type::Function(p2, r2)) => {
+                self.unify_types(p1, p2)?;
+                self.unify_types(r1, r2)
+            }
+            _ => Err(()),
+        }
+    }
+
+    fn bind_var(&mut self, id: usize, ty: &Type) -> Result<(), ()> {
+        let level = match self.vars[id] {
+            VarState::Unbound { level } => level,
+            VarState::Bound(_) => unreachable!("Type variables are resolved before binding"),
+        };
+        // A type cannot contain itself, e.g. x |-> x(x)
+        if self.occurs(id, level, ty) {
+            return Err(());
+        }
+        self.vars[id] = VarState::Bound(ty.clone());
+        Ok(())
+    }
+
+    /// Checks if variable `id` occurs in `ty`, and lowers the level of the
+    /// variables in `ty` to `level`, so they are not generalised too early.
+    fn occurs(&mut self, id: usize, level: usize, ty: &Type) -> bool {
+        match self.shallow_resolve(ty) {
+            Type::Var(other) if other == id => true,
+            Type::Var(other) => {
+                self.lower_level(other, level);
+                false
+            }
+            Type::List(element) | Type::Stream(element) | Type::Sequence(element) => {
+                self.occurs(id, level, &element)
+            }
+            Type::Function(param, result) => {
+                self.occurs(id, level, &param) || self.occurs(id, level, &result)
+            }
+            _ => false,
+        }
+    }
+
+    fn lower_level(&mut self, id: usize, level: usize) {
+        if let VarState::Unbound { level: current } = self.vars[id] {
+            self.vars[id] = VarState::Unbound {
+                level: current.min(level),
+            };
+        }
+    }
+
+    fn lower_levels(&mut self, ty: &Type, level: usize) {
+        match self.shallow_resolve(ty) {
+            Type::Var(id) => self.lower_level(id, level),
+            Type::List(element) | Type::Stream(element) | Type::Sequence(element) => {
+                self.lower_levels(&element, level)
+            }
+            Type::Function(param, result) => {
+                self.lower_levels(&param, level);
+                self.lower_levels(&result, level);
+            }
+            _ => {}
+        }
+    }
+
+    fn constrain(&mut self, ty: Type, class: Class, location: Location) {
+        self.constraints.push(Constraint {
+            ty,
+            class,
+            location,
+        });
+    }
+
+    /// Reports constraints on types that are now known not to satisfy them.
+    fn check_constraints(&mut self) -> Result<(), String> {
+        let mut pending: Vec<Constraint> = std::mem::take(&mut self.constraints)
+            .into_iter()
+            .rev()
+            .collect();
+        let mut result = Ok(());
+        while let Some(constraint) = pending.pop() {
+            match self.resolve(&constraint.ty) {
+                Type::Var(_) => self.constraints.push(constraint),
+                Type::Dynamic | Type::Formula => {}
+                Type::List(element) | Type::Sequence(element)
+                    if constraint.class.applies_to_elements() =>
+                {
+                    pending.push(Constraint {
+                        ty: *element,
+                        ..constraint
+                    });
+                }
+                ty if constraint.class.accepts(&ty) => {}
+                ty => {
+                    if result.is_ok() {
+                        let described = self.describe(&[&ty]);
+                        result = Err(self.format_error(
+                            constraint.location,
+                            &constraint.class.unsupported(&described[0]),
+                        ));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    // Polymorphism
+
+    fn instantiate(&mut self, scheme: &Scheme, location: Location) -> Type {
+        let vars: Vec<Type> = (0..scheme.generics).map(|_| self.fresh()).collect();
+        for (index, class) in &scheme.constraints {
+            self.constrain(vars[*index].clone(), *class, location);
+        }
+        Self::substitute(&scheme.ty, &vars)
+    }
+
+    fn substitute(ty: &Type, vars: &[Type]) -> Type {
+        match ty {
+            Type::Generic(index) => vars[*index].clone(),
+            Type::List(element) => list(Self::substitute(element, vars)),
+            Type::Stream(element) => stream(Self::substitute(element, vars)),
+            Type::Sequence(element) => sequence(Self::substitute(element, vars)),
+            Type::Function(param, result) => fun(
+                Self::substitute(param, vars),
+                Self::substitute(result, vars),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Turns the unknown types created inside a binding into type parameters.
+    fn generalise(&mut self, ty: &Type) -> Scheme {
+        let mut generics = HashMap::new();
+        let ty = self.generalise_type(ty, &mut generics);
+
+        // Constraints on the new type parameters become part of the scheme.
+        let mut constraints = vec![];
+        self.constraints.retain(|constraint| {
+            let mut ty = constraint.ty.clone();
+            loop {
+                match ty {
+                    Type::Var(id) => match &self.vars[id] {
+                        VarState::Bound(bound) => ty = bound.clone(),
+                        VarState::Unbound { .. } => break,
+                    },
+                    Type::List(element) | Type::Sequence(element)
+                        if constraint.class.applies_to_elements() =>
+                    {
+                        ty = *element;
+                    }
+                    _ => break,
+                }
+            }
+            match ty {
+                Type::Var(id) if generics.contains_key(&id) => {
+                    constraints.push((generics[&id], constraint.class));
+                    false
+                }
+                _ => true,
+            }
+        });
+
+        Scheme {
+            generics: generics.len(),
+            constraints,
+            ty,
+        }
+    }
+
+    fn generalise_type(&self, ty: &Type, generics: &mut HashMap<usize, usize>) -> Type {
+        match self.shallow_resolve(ty) {
+            Type::Var(id) => match self.vars[id] {
+                VarState::Unbound { level } if level > self.level => {
+                    let next = generics.len();
+                    Type::Generic(*generics.entry(id).or_insert(next))
+                }
+                _ => Type::Var(id),
+            },
+            Type::List(element) => list(self.generalise_type(&element, generics)),
+            Type::Stream(element) => stream(self.generalise_type(&element, generics)),
+            Type::Sequence(element) => sequence(self.generalise_type(&element, generics)),
+            Type::Function(param, result) => fun(
+                self.generalise_type(&param, generics),
+                self.generalise_type(&result, generics),
+            ),
+            other => other,
+        }
+    }
+
+    /// Only values are generalised, so that a binding to the result of
+    /// a call keeps a single type.
+    fn is_value(expr: &Expr) -> bool {
+        match expr {
+            Expr::FunctionDef { .. } | Expr::Section { .. } | Expr::Literal(_) => true,
+            Expr::Variable(_) => true,
+            Expr::Grouping(expr) => Self::is_value(expr),
+            _ => false,
+        }
+    }
+
+    // Scopes
+
+    fn lookup(&self, name: &str) -> Option<Scheme> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+    }
+
+    fn bind(&mut self, name: String, scheme: Scheme) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, scheme);
+        }
+    }
+
+    /// Declares the bindings of a block up front, so that functions
+    /// can refer to functions defined after them.
+    fn declare_bindings(&mut self, statements: &[Expr]) {
+        for stmt in statements {
+            if let Expr::Binding { name, .. } = stmt {
+                let ty = self.fresh();
+                self.bind(name.clone(), Scheme::mono(ty));
+            }
+        }
+    }
+
+    // Inference
+
+    fn infer(&mut self, expr: &Expr) -> Result<Type, String> {
+        let location = Self::locate(expr);
+        if let Some(location) = location {
+            self.locations.push(location);
+        }
+        let result = self.infer_expr(expr);
+        if location.is_some() {
+            self.locations.pop();
+        }
+        result
+    }
+
+    fn infer_expr(&mut self, expr: &Expr) -> Result<Type, String> {
+        match expr {
+            Expr::Program { statements } | Expr::Block { statements } => {
+                self.infer_block(statements)
+            }
+            Expr::Literal(literal) => Ok(match literal {
+                LiteralValue::Number(_)
+                | LiteralValue::Integer(_)
+                | LiteralValue::Rational(_)
+                | LiteralValue::Imaginary(_) => Type::Number,
+                // Dimensions are only checked at runtime.
+                LiteralValue::Quantity(..) => Type::Dynamic,
+                LiteralValue::String(_) => Type::String,
+                LiteralValue::Boolean(_) => Type::Boolean,
+                LiteralValue::Nil => self.fresh(),
+            }),
+            Expr::Binary { left, op, right } => self.infer_binary(left, op, right),
+            Expr::Unary { op, right } => {
+                let right_type = self.infer(right)?;
+                match op.kind {
+                    // Negation maps over lists, like the math builtins.
+                    TokenType::Minus => {
+                        self.constrain(right_type.clone(), Class::Math, Self::token_location(op));
+                        Ok(right_type)
+                    }
+                    _ => Ok(Type::Boolean),
+                }
+            }
+            Expr::Grouping(expr) => self.infer(expr),
+            Expr::Section { op, left, right } => {
+                self.infer_section(op, left.as_deref(), right.as_deref())
+            }
+            Expr::Variable(name) => match self.lookup(name) {
+                Some(scheme) => {
+                    let location = self.locations.last().copied();
+                    Ok(self.instantiate(&scheme, location))
+                }
+                None => Err(self.format_error(None, &format!("Name '{name}' is not defined"))),
+            },
+            Expr::Assign { name, expr, .. } => {
+                let scheme = self.lookup(name).ok_or_else(|| {
+                    self.format_error(None, &format!("Name '{name}' is not defined"))
+                })?;
+                let expected = self.instantiate(&scheme, None);
+                let found = self.infer(expr)?;
+                self.unify(&expected, &found, Self::locate(expr))?;
+                Ok(found)
+            }
+            Expr::Binding {
+                name,
+                annotation,
+                expr,
+            } => self.infer_binding(name, annotation.as_ref(), expr),
+            Expr::FunctionDef {
+                param,
+                param_type,
+                body,
+                return_type,
+            } => {
+                let param_type = self.annotated_type(param_type.as_ref());
+                self.scopes.push(HashMap::from([(
+                    param.clone(),
+                    Scheme::mono(param_type.clone()),
+                )]));
+                let body_type = self.infer(body);
+                self.scopes.pop();
+                let body_type = body_type?;
+
+                let return_type = self.annotated_type(return_type.as_ref());
+                self.unify(&return_type, &body_type, Self::locate(body))?;
+                Ok(fun(param_type, body_type))
+            }
+            Expr::FunctionCall { func, paren, arg } => {
+                let func_type = self.infer(func)?;
+                let arg_type = if self.is_free_polynomial(&func_type, arg) {
+                    Type::Dynamic
+                } else {
+                    self.infer(arg)?
+                };
+                let location = Self::locate(arg).or(Self::token_location(paren));
+
+                match self.shallow_resolve(&func_type) {
+                    Type::Function(param, result) => {
+                        self.unify(&param, &arg_type, location)?;
+                        Ok(*result)
+                    }
+                    Type::Var(_) | Type::Dynamic => {
+                        let result = self.fresh();
+                        self.unify(&func_type, &fun(arg_type, result.clone()), location)?;
+                        Ok(result)
+                    }
+                    other => {
+                        let described = self.describe(&[&other]);
+                        Err(self.format_error(
+                            Self::token_location(paren),
+                            &format!("Only functions are callable, found {}", described[0]),
+                        ))
+                    }
+                }
+            }
+            Expr::If {
+                cond_expr,
+                then_expr,
+                else_expr,
+                ..
+            } => {
+                // Any value can be used as a condition.
+                self.infer(cond_expr)?;
+                let then_type = self.infer(then_expr)?;
+                let else_type = self.infer(else_expr)?;
+                self.unify(&then_type, &else_type, Self::locate(else_expr))?;
+                Ok(then_type)
+            }
+            Expr::Match { arms } => self.infer_match(arms),
+            Expr::List { elements } => self.infer_list(elements),
+            Expr::Matrix { rows } => {
+                for element in rows.iter().flatten() {
+                    let ty = self.infer(element)?;
+                    self.unify(&Type::Number, &ty, Self::locate(element))?;
+                }
+                // Shapes are only checked at runtime.
+                Ok(Type::Dynamic)
+            }
+            // An interval stands for an uncertain number.
+            Expr::Interval { low, high } => {
+                for bound in [low, high] {
+                    let ty = self.infer(bound)?;
+                    self.unify(&Type::Number, &ty, Self::locate(bound))?;
+                }
+                Ok(Type::Number)
+            }
+            // The names in a formula do not have to be bound.
+            Expr::Symbolic(_) => Ok(Type::Dynamic),
+            Expr::Empty => unreachable!("The program should never contain Empty expressions"),
+        }
+    }
+
+    /// Checks for a call like `poly(x^2 - 3x + 2)`, whose argument is a formula
+    /// in a variable that is not defined, as the interpreter reads it.
+    fn is_free_polynomial(&self, func_type: &Type, arg: &Expr) -> bool {
+        matches!(
+            self.shallow_resolve(func_type),
+            Type::Function(param, _) if self.shallow_resolve(&param) == Type::Formula
+        ) && matches!(
+            symbolic::polynomial(arg),
+            Some((Some(variable), _)) if self.lookup(&variable).is_none()
+        )
+    }
+
+    fn infer_block(&mut self, statements: &[Expr]) -> Result<Type, String> {
+        self.scopes.push(HashMap::new());
+        self.declare_bindings(statements);
+
+        let mut result = Ok(self.fresh()); // An empty block is nil
+        for stmt in statements {
+            result = self.infer(stmt);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.scopes.pop();
+        result
+    }
+
+    /// The type an annotation stands for, or an unknown type without one.
+    fn annotated_type(&mut self, annotation: Option<&TypeAnnotation>) -> Type {
+        match annotation {
+            Some(TypeAnnotation::Number) => Type::Number,
+            Some(TypeAnnotation::String) => Type::String,
+            Some(TypeAnnotation::Boolean) => Type::Boolean,
+            Some(TypeAnnotation::Function) => {
+                let param = self.fresh();
+                let result = self.fresh();
+                fun(param, result)
+            }
+            Some(TypeAnnotation::Stream) => {
+                let element = self.fresh();
+                stream(element)
+            }
+            Some(TypeAnnotation::List(element)) => {
+                let element = self.annotated_type(Some(element));
+                list(element)
+            }
+            Some(TypeAnnotation::Any) | None => self.fresh(),
+        }
+    }
+
+    fn infer_binding(
+        &mut self,
+        name: &str,
+        annotation: Option<&TypeAnnotation>,
+        expr: &Expr,
+    ) -> Result<Type, String> {
+        // Bind the name first, so that the expression can be recursive.
+        self.level += 1;
+        let recursive = self.annotated_type(annotation);
+        self.scopes.push(HashMap::from([(
+            name.to_string(),
+            Scheme::mono(recursive.clone()),
+        )]));
+        let found = self.infer(expr);
+        self.scopes.pop();
+        let found = found.and_then(|found| {
+            self.unify(&recursive, &found, Self::locate(expr))?;
+            Ok(found)
+        });
+        self.level -= 1;
+        let found = found?;
+
+        let scheme = if Self::is_value(expr) {
+            self.generalise(&found)
+        } else {
+            // Move the unknown types to this level, so that only an
+            // enclosing binding can generalise them.
+            self.lower_levels(&found, self.level);
+            Scheme::mono(found.clone())
+        };
+
+        // Uses of the name before this binding must agree with its type.
+        if let Some(declared) = self.scopes.last().and_then(|scope| scope.get(name))
+            && declared.generics == 0
+            && let Type::Var(_) = self.shallow_resolve(&declared.ty)
+        {
+            let declared = declared.ty.clone();
+            let instance = self.instantiate(&scheme, None);
+            self.unify(&declared, &instance, Self::locate(expr))?;
+        }
+
+        self.bind(name.to_string(), scheme);
+        Ok(found)
+    }
+
+    fn infer_binary(&mut self, left: &Expr, op: &Token, right: &Expr) -> Result<Type, String> {
+        let left_type = self.infer(left)?;
+        let right_type = self.infer(right)?;
+        let location = Self::token_location(op);
+
+        match op.kind {
+            TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Div
+            | TokenType::Caret => {
+                self.unify(&Type::Number, &left_type, location)?;
+                self.unify(&Type::Number, &right_type, location)?;
+                Ok(Type::Number)
+            }
+            TokenType::Plus => {
+                self.unify(&left_type, &right_type, location)?;
+                self.constrain(left_type.clone(), Class::Add, location);
+                Ok(left_type)
+            }
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual => {
+                self.unify(&left_type, &right_type, location)?;
+                self.constrain(left_type, Class::Ord, location);
+                Ok(Type::Boolean)
+            }
+            // Values of any two types can be compared for equality.
+            TokenType::EqualEqual | TokenType::BangEqual => Ok(Type::Boolean),
+            // Element-wise operators accept numbers and lists of any depth.
+            TokenType::Dotted(_) => Ok(Type::Dynamic),
+            _ => unreachable!("There should be no other binary operators"),
+        }
+    }
+
+    /// The type of a binary operator as a curried function.
+    fn operator_type(&mut self, op: &Token) -> Type {
+        let location = Self::token_location(op);
+        match op.kind {
+            TokenType::Plus => {
+                let a = self.fresh();
+                self.constrain(a.clone(), Class::Add, location);
+                fun(a.clone(), fun(a.clone(), a))
+            }
+            TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual => {
+                let a = self.fresh();
+                self.constrain(a.clone(), Class::Ord, location);
+                fun(a.clone(), fun(a, Type::Boolean))
+            }
+            TokenType::EqualEqual | TokenType::BangEqual => {
+                let a = self.fresh();
+                let b = self.fresh();
+                fun(a, fun(b, Type::Boolean))
+            }
+            TokenType::Dotted(_) => fun(Type::Dynamic, fun(Type::Dynamic, Type::Dynamic)),
+            _ => fun(Type::Number, fun(Type::Number, Type::Number)),
+        }
+    }
+
+    fn infer_section(
+        &mut self,
+        op: &Token,
+        left: Option<&Expr>,
+        right: Option<&Expr>,
+    ) -> Result<Type, String> {
+        let location = Self::token_location(op);
+        let (left_param, rest) = match self.operator_type(op) {
+            Type::Function(param, rest) => (*param, *rest),
+            _ => unreachable!("Operator types are curried functions"),
+        };
+        let (right_param, result) = match rest {
+            Type::Function(param, result) => (*param, *result),
+            _ => unreachable!("Operator types are curried functions"),
+        };
+
+        match (left, right) {
+            (Some(left), _) => {
+                let left_type = self.infer(left)?;
+                self.unify(&left_param, &left_type, location)?;
+                Ok(fun(right_param, result))
+            }
+            (_, Some(right)) => {
+                let right_type = self.infer(right)?;
+                self.unify(&right_param, &right_type, location)?;
+                Ok(fun(left_param, result))
+            }
+            (None, None) => Ok(fun(left_param, fun(right_param, result))),
+        }
+    }
+
+    fn infer_match(&mut self, arms: &[MatchArm]) -> Result<Type, String> {
+        // A match without a matching arm returns nil, which has any type.
+        let result = self.fresh();
+        for arm in arms {
+            self.infer(&arm.pattern)?;
+            let body_type = self.infer(&arm.body)?;
+            self.unify(&result, &body_type, Self::locate(&arm.body))?;
+        }
+        Ok(result)
+    }
+
+    fn infer_list(&mut self, elements: &[Expr]) -> Result<Type, String> {
+        let element_type = self.fresh();
+        let mut types = vec![];
+        for element in elements {
+            types.push(self.infer(element)?);
+        }
+
+        // Lists with elements of different types are allowed,
+        // but their elements can only be checked at runtime.
+        let snapshot = self.vars.clone();
+        for ty in &types {
+            if self.unify_types(&element_type, ty).is_err() {
+                self.vars = snapshot;
+                return Ok(list(Type::Dynamic));
+            }
+        }
+        Ok(list(element_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let ty = fun(fun(Type::Generic(0), Type::Number), list(Type::Generic(1)));
+        assert_eq!(ty.to_string(), "(a -> Number) -> List<b>");
+    }
+
+    #[test]
+    fn test_occurs_check() {
+        let mut checker = TypeChecker::new();
+        let var = checker.fresh();
+        assert!(checker.unify_types(&var, &list(var.clone())).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_unifies_with_anything() {
+        let mut checker = TypeChecker::new();
+        assert!(checker.unify_types(&Type::Dynamic, &Type::Number).is_ok());
+        assert!(
+            checker
+                .unify_types(&fun(Type::Number, Type::Dynamic), &Type::Dynamic)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use std::fmt::Display;
+
+/// The SI base units, in the order of the exponents in a `Dimension`.
+const BASE_UNITS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+/// The exponents of the SI base units that make up a physical dimension,
+/// e.g. acceleration is `m^1 * s^-2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimension([i8; 7]);
+
+impl Dimension {
+    pub const NONE: Self = Self([0; 7]);
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    fn combine(self, other: Self, sign: i8) -> Self {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0) {
+            *exponent += sign * other;
+        }
+        Self(exponents)
+    }
+
+    fn pow(self, power: i32) -> Self {
+        Self(self.0.map(|exponent| exponent * power as i8))
+    }
+}
+
+/// Writes unit terms as `kg*m/s^2`, with every negative power after a `/`,
+/// or as `s^-1` if there are only negative powers.
+fn write_terms<'a>(
+    f: &mut std::fmt::Formatter<'_>,
+    terms: impl Iterator<Item = (&'a str, i32)> + Clone,
+) -> std::fmt::Result {
+    let has_numerator = terms.clone().any(|(_, power)| power > 0);
+    let mut first = true;
+    let leading = terms
+        .clone()
+        .filter(|(_, power)| *power > 0 || (!has_numerator && *power < 0));
+    for (symbol, power) in leading {
+        if !first {
+            write!(f, "*")?;
+        }
+        first = false;
+        write!(f, "{symbol}")?;
+        if power != 1 {
+            write!(f, "^{power}")?;
+        }
+    }
+    if !has_numerator {
+        return Ok(());
+    }
+    for (symbol, power) in terms.filter(|(_, power)| *power < 0) {
+        write!(f, "/{symbol}")?;
+        if power != -1 {
+            write!(f, "^{}", -power)?;
+        }
+    }
+    Ok(())
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_none() {
+            return write!(f, "dimensionless");
+        }
+        let terms = BASE_UNITS
+            .iter()
+            .zip(self.0)
+            .map(|(symbol, exponent)| (*symbol, exponent as i32));
+        write_terms(f, terms)
+    }
+}
+
+const fn dimension(m: i8, kg: i8, s: i8, a: i8, k: i8, mol: i8, cd: i8) -> Dimension {
+    Dimension([m, kg, s, a, k, mol, cd])
+}
+
+const LENGTH: Dimension = dimension(1, 0, 0, 0, 0, 0, 0);
+const MASS: Dimension = dimension(0, 1, 0, 0, 0, 0, 0);
+const TIME: Dimension = dimension(0, 0, 1, 0, 0, 0, 0);
+const CURRENT: Dimension = dimension(0, 0, 0, 1, 0, 0, 0);
+const TEMPERATURE: Dimension = dimension(0, 0, 0, 0, 1, 0, 0);
+const AMOUNT: Dimension = dimension(0, 0, 0, 0, 0, 1, 0);
+const LUMINOSITY: Dimension = dimension(0, 0, 0, 0, 0, 0, 1);
+const AREA: Dimension = dimension(2, 0, 0, 0, 0, 0, 0);
+const VOLUME: Dimension = dimension(3, 0, 0, 0, 0, 0, 0);
+const FREQUENCY: Dimension = dimension(0, 0, -1, 0, 0, 0, 0);
+const FORCE: Dimension = dimension(1, 1, -2, 0, 0, 0, 0);
+const ENERGY: Dimension = dimension(2, 1, -2, 0, 0, 0, 0);
+const POWER: Dimension = dimension(2, 1, -3, 0, 0, 0, 0);
+const PRESSURE: Dimension = dimension(-1, 1, -2, 0, 0, 0, 0);
+const CHARGE: Dimension = dimension(0, 0, 1, 1, 0, 0, 0);
+const VOLTAGE: Dimension = dimension(2, 1, -3, -1, 0, 0, 0);
+const RESISTANCE: Dimension = dimension(2, 1, -3, -2, 0, 0, 0);
+
+/// The built-in units, with their size in SI base units.
+const UNITS: &[(&str, f64, Dimension)] = &[
+    ("m", 1.0, LENGTH),
+    ("km", 1e3, LENGTH),
+    ("cm", 1e-2, LENGTH),
+    ("mm", 1e-3, LENGTH),
+    ("um", 1e-6, LENGTH),
+    ("nm", 1e-9, LENGTH),
+    ("ft", 0.3048, LENGTH),
+    ("mi", 1609.344, LENGTH),
+    ("kg", 1.0, MASS),
+    ("g", 1e-3, MASS),
+    ("mg", 1e-6, MASS),
+    ("t", 1e3, MASS),
+    ("lb", 0.453_592_37, MASS),
+    ("s", 1.0, TIME),
+    ("ms", 1e-3, TIME),
+    ("us", 1e-6, TIME),
+    ("ns", 1e-9, TIME),
+    ("min", 60.0, TIME),
+    ("h", 3600.0, TIME),
+    ("day", 86400.0, TIME),
+    ("A", 1.0, CURRENT),
+    ("mA", 1e-3, CURRENT),
+    ("K", 1.0, TEMPERATURE),
+    ("mol", 1.0, AMOUNT),
+    ("cd", 1.0, LUMINOSITY),
+    ("ha", 1e4, AREA),
+    ("L", 1e-3, VOLUME),
+    ("mL", 1e-6, VOLUME),
+    ("Hz", 1.0, FREQUENCY),
+    ("kHz", 1e3, FREQUENCY),
+    ("MHz", 1e6, FREQUENCY),
+    ("GHz", 1e9, FREQUENCY),
+    ("N", 1.0, FORCE),
+    ("kN", 1e3, FORCE),
+    ("J", 1.0, ENERGY),
+    ("kJ", 1e3, ENERGY),
+    ("kWh", 3.6e6, ENERGY),
+    ("cal", 4.184, ENERGY),
+    ("eV", 1.602_176_634e-19, ENERGY),
+    ("W", 1.0, POWER),
+    ("kW", 1e3, POWER),
+    ("MW", 1e6, POWER),
+    ("Pa", 1.0, PRESSURE),
+    ("kPa", 1e3, PRESSURE),
+    ("bar", 1e5, PRESSURE),
+    ("atm", 101_325.0, PRESSURE),
+    ("C", 1.0, CHARGE),
+    ("V", 1.0, VOLTAGE),
+    ("Ohm", 1.0, RESISTANCE),
+];
+
+/// A unit made of named units raised to integer powers, such as `km/h`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    terms: Vec<(String, i32)>,
+    /// The size of the unit in SI base units.
+    scale: f64,
+    dimension: Dimension,
+}
+
+impl Unit {
+    /// The unit of plain numbers.
+    pub fn none() -> Self {
+        Self {
+            terms: vec![],
+            scale: 1.0,
+            dimension: Dimension::NONE,
+        }
+    }
+
+    /// Looks up a single built-in unit like `km`.
+    pub fn named(name: &str) -> Result<Self, String> {
+        let (symbol, scale, dimension) = UNITS
+            .iter()
+            .find(|(symbol, _, _)| *symbol == name)
+            .ok_or_else(|| format!("Unknown unit '{name}'"))?;
+        Ok(Self {
+            terms: vec![(symbol.to_string(), 1)],
+            scale: *scale,
+            dimension: *dimension,
+        })
+    }
+
+    /// Parses a unit like `m`, `m/s^2` or `kg*m^2/s^2`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut unit = Self::none();
+        let mut dividing = false;
+        let mut rest = text.trim();
+        loop {
+            let end = rest.find(['*', '/']).unwrap_or(rest.len());
+            let term = rest[..end].trim();
+            let (name, power) = match term.split_once('^') {
+                Some((name, power)) => {
+                    let power = power
+                        .trim()
+                        .parse::<i32>()
+                        .map_err(|_| format!("Expected an integer power in unit '{text}'"))?;
+                    (name.trim(), power)
+                }
+                None => (term, 1),
+            };
+            if name.is_empty() {
+                return Err(format!("Expected a unit name in '{text}'"));
+            }
+
+            let term = Self::named(name)?.pow(power);
+            unit = if dividing {
+                unit.div(&term)
+            } else {
+                unit.mul(&term)
+            };
+
+            match rest[end..].chars().next() {
+                Some(op) => {
+                    dividing = op == '/';
+                    rest = &rest[end + 1..];
+                }
+                None => return Ok(unit),
+            }
+        }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn combine(&self, other: &Self, sign: i32) -> Self {
+        let mut terms = self.terms.clone();
+        for (symbol, power) in &other.terms {
+            match terms.iter_mut().find(|(existing, _)| existing == symbol) {
+                Some((_, existing)) => *existing += sign * power,
+                None => terms.push((symbol.clone(), sign * power)),
+            }
+        }
+        terms.retain(|(_, power)| *power != 0);
+
+        Self {
+            terms,
+            scale: self.scale * other.scale.powi(sign),
+            dimension: self.dimension.combine(other.dimension, sign as i8),
+        }
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        self.combine(other, 1)
+    }
+
+    pub fn div(&self, other: &Self) -> Self {
+        self.combine(other, -1)
+    }
+
+    pub fn pow(&self, power: i32) -> Self {
+        Self {
+            terms: self
+                .terms
+                .iter()
+                .filter(|_| power != 0)
+                .map(|(symbol, existing)| (symbol.clone(), existing * power))
+                .collect(),
+            scale: self.scale.powi(power),
+            dimension: self.dimension.pow(power),
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(symbol, power)| (symbol.as_str(), *power));
+        write_terms(f, terms)
+    }
+}
+
+/// A number with a unit, such as `9.81 m/s^2`.
+///
+/// The magnitude is stored in SI base units, so quantities in different
+/// units of the same dimension can be combined directly.
+#[derive(Clone, Debug)]
+pub struct Quantity {
+    magnitude: f64,
+    unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self {
+            magnitude: value * unit.scale,
+            unit,
+        }
+    }
+
+    /// The value in the quantity's own unit.
+    pub fn value(&self) -> f64 {
+        self.magnitude / self.unit.scale
+    }
+
+    /// The value in SI base units.
+    pub fn magnitude(&self) -> f64 {
+        self.magnitude
+    }
+
+    pub fn unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension
+    }
+
+    /// Expresses the quantity in another unit of the same dimension.
+    pub fn to(&self, unit: Unit) -> Result<Self, String> {
+        if unit.dimension != self.dimension() {
+            return Err(format!(
+                "Cannot convert {self} to {unit}, as their dimensions differ: {} and {}",
+                self.dimension(),
+                unit.dimension
+            ));
+        }
+        Ok(Self {
+            magnitude: self.magnitude,
+            unit,
+        })
+    }
+
+    fn expect_same_dimension(&self, other: &Self, op: &str) -> Result<(), String> {
+        if self.dimension() == other.dimension() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Incompatible dimensions for '{op}': {} and {}",
+                self.dimension(),
+                other.dimension()
+            ))
+        }
+    }
+
+    /// Adds two quantities of the same dimension, in the unit of `self`.
+    pub fn add(&self, other: &Self) -> Result<Self, String> {
+        self.expect_same_dimension(other, "+")?;
+        Ok(Self {
+            magnitude: self.magnitude + other.magnitude,
+            unit: self.unit.clone(),
+        })
+    }
+
+    pub fn sub(&self, other: &Self) -> Result<Self, String> {
+        self.expect_same_dimension(other, "-")?;
+        Ok(Self {
+            magnitude: self.magnitude - other.magnitude,
+            unit: self.unit.clone(),
+        })
+    }
+
+    /// Compares two quantities of the same dimension, for the operator `op`.
+    pub fn less(&self, other: &Self, op: &str) -> Result<bool, String> {
+        self.expect_same_dimension(other, op)?;
+        Ok(self.magnitude < other.magnitude)
+    }
+
+    pub fn less_equal(&self, other: &Self, op: &str) -> Result<bool, String> {
+        self.expect_same_dimension(other, op)?;
+        Ok(self.magnitude <= other.magnitude)
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        Self {
+            magnitude: self.magnitude * other.magnitude,
+            unit: self.unit.mul(&other.unit),
+        }
+    }
+
+    pub fn div(&self, other: &Self) -> Self {
+        Self {
+            magnitude: self.magnitude / other.magnitude,
+            unit: self.unit.div(&other.unit),
+        }
+    }
+
+    pub fn pow(&self, power: i32) -> Self {
+        Self {
+            magnitude: self.magnitude.powi(power),
+            unit: self.unit.pow(power),
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            magnitude: -self.magnitude,
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.dimension() == other.dimension() && self.magnitude == other.magnitude
+    }
+}
+
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self.dimension() == other.dimension() {
+            self.magnitude.partial_cmp(&other.magnitude)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.value(), self.unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unit() {
+        let unit = Unit::parse("kg*m/s^2").unwrap();
+        assert_eq!(unit.dimension(), FORCE);
+        assert_eq!(unit.to_string(), "kg*m/s^2");
+        assert_eq!(Unit::parse("km/h").unwrap().scale(), 1000.0 / 3600.0);
+        assert!(Unit::parse("furlong").is_err());
+        assert!(Unit::parse("m^x").is_err());
+    }
+
+    #[test]
+    fn test_cancelling_terms() {
+        let unit = Unit::parse("m*s/s").unwrap();
+        assert_eq!(unit.to_string(), "m");
+        assert_eq!(unit.dimension(), LENGTH);
+    }
+
+    #[test]
+    fn test_dimension_display() {
+        assert_eq!(ENERGY.to_string(), "m^2*kg/s^2");
+        assert_eq!(FREQUENCY.to_string(), "s^-1");
+        assert_eq!(Dimension::NONE.to_string(), "dimensionless");
+    }
+}
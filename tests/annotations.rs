@@ -0,0 +1,94 @@
+use mathfp::runtime::RuntimeValue;
+use mathfp::{check, execute, execute_or_panic};
+
+#[test]
+fn test_annotated_parameters() {
+    let input = "
+        scale := (k: Number, xs: List<Number>) |-> map(x |-> x * k, xs) : List<Number>;
+        scale(2, [1, 2])
+    ";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![RuntimeValue::Number(2.0), RuntimeValue::Number(4.0)]
+        }
+    );
+}
+
+#[test]
+fn test_unannotated_parameter_list() {
+    assert_eq!(
+        execute("add := (a, b) |-> a + b; add(1, 2)"),
+        Ok(RuntimeValue::Number(3.0))
+    );
+}
+
+#[test]
+fn test_single_annotated_parameter() {
+    assert_eq!(
+        execute("double := x: Number |-> x * 2; double(4)"),
+        Ok(RuntimeValue::Number(8.0))
+    );
+}
+
+#[test]
+fn test_annotated_binding() {
+    assert_eq!(
+        execute("x: Number := 1; x = 2; x"),
+        Ok(RuntimeValue::Number(2.0))
+    );
+}
+
+#[test]
+fn test_any_and_function_annotations() {
+    let input = "
+        apply := (f: Function, x: Any) |-> f(x);
+        apply(str, true)
+    ";
+    assert_eq!(execute_or_panic(input), RuntimeValue::String("true".into()));
+}
+
+#[test]
+#[should_panic(expected = "Parameter 'x' expects Number, found [2]")]
+fn test_parameter_contract() {
+    execute_or_panic("double := x: Number |-> x * 2; double([2])");
+}
+
+#[test]
+#[should_panic(expected = "Parameter 'xs' expects List<Number>, found [1, true]")]
+fn test_list_element_contract() {
+    execute_or_panic("total := (xs: List<Number>) |-> 0; total([1, true])");
+}
+
+#[test]
+#[should_panic(expected = "Function of 'x' expects to return Number, found true")]
+fn test_return_contract() {
+    execute_or_panic("is_positive := x |-> x > 0 : Number; is_positive(1)");
+}
+
+#[test]
+#[should_panic(expected = "Binding 'name' expects String, found 5")]
+fn test_binding_contract() {
+    execute_or_panic("name: String := 5");
+}
+
+#[test]
+#[should_panic(expected = "Cannot assign true to 'x', which expects Number")]
+fn test_assignment_contract() {
+    execute_or_panic("x: Number := 1; x = true");
+}
+
+#[test]
+#[should_panic(expected = "Unknown type 'Integer'")]
+fn test_unknown_type() {
+    execute_or_panic("x: Integer := 1");
+}
+
+#[test]
+fn test_annotations_guide_type_checking() {
+    assert_eq!(
+        check("id := x: Number |-> x; id").unwrap().to_string(),
+        "Number -> Number"
+    );
+    assert!(check("f := x |-> x : String; f(1) - 1").is_err());
+}
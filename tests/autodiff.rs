@@ -0,0 +1,126 @@
+mod common;
+
+use common::display;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_grad() {
+    assert_eq!(display("grad(x |-> x ^ 3)(2)"), "12");
+    assert_eq!(display("grad(x |-> sin(x) * exp(x))(0)"), "1");
+    assert_eq!(display("grad(x |-> 5)(2)"), "0");
+    assert_eq!(display("grad((x: Number) |-> x / 4)(1)"), "0.25");
+    assert_eq!(
+        display("abs(grad(x |-> 2 ^ x)(1) - 2 * ln(2)) < 0.000000001"),
+        "true"
+    );
+}
+
+#[test]
+fn test_control_flow_and_recursion() {
+    assert_eq!(display("grad(x |-> if x > 0 then x ^ 2 else -x)(-3)"), "-1");
+    let input = "
+        power := n |-> x |-> if n == 0 then 1 else x * power(n - 1)(x);
+        grad(power(5))(2)";
+    assert_eq!(display(input), "80");
+    assert_eq!(
+        display("abs(grad(x |-> memo(sin)(x))(1) - cos(1)) < 0.000000001"),
+        "true"
+    );
+}
+
+#[test]
+fn test_lists_and_jacobians() {
+    assert_eq!(display("grad(p |-> 3)([1, 2])"), "[0, 0]");
+    assert_eq!(display("jacobian(p |-> p .* p)([1, 2])"), "[|2, 0; 0, 4|]");
+    assert_eq!(
+        display("jacobian(x |-> [x, x ^ 2, sin(x)])(0)"),
+        "[|1, 0, 1|]"
+    );
+}
+
+/// Checks `grad(function)(x)` against an exact derivative.
+fn assert_grad(function: &str, x: &str, expected: f64) {
+    let actual = execute_or_panic(&format!("grad({function})({x})"))
+        .as_f64()
+        .expect("grad should return a number");
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "grad({function})({x}) is {actual}, expected {expected}"
+    );
+}
+
+#[test]
+fn test_special_functions() {
+    let euler_gamma = 0.5772156649015329;
+    // gamma'(x) = gamma(x) * digamma(x)
+    assert_grad("gamma", "1", -euler_gamma);
+    assert_grad("gamma", "3", 2.0 * (1.5 - euler_gamma));
+    // d/da beta(a, 1) = d/da 1 / a
+    assert_grad("a |-> beta(a, 1)", "2", -0.25);
+    assert_grad("b |-> beta(2, b)", "1", -0.75);
+}
+
+#[test]
+fn test_distributions() {
+    let density = (-0.5f64).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    assert_grad("x |-> normal_pdf(0, 1, x)", "1", -density);
+    assert_grad("mu |-> normal_pdf(mu, 1, 1)", "0", density);
+    assert_grad("sigma |-> normal_pdf(0, sigma, 1)", "1", 0.0);
+    assert_grad("x |-> normal_cdf(0, 1, x)", "1", density);
+    assert_grad("sigma |-> normal_cdf(0, sigma, 1)", "1", -density);
+    assert_grad("x |-> uniform_pdf(0, x, 1)", "2", -0.25);
+    assert_grad("x |-> uniform_cdf(0, 4, x)", "1", 0.25);
+    assert_grad("x |-> uniform_cdf(x, 4, 1)", "0", -3.0 / 16.0);
+    assert_grad("rate |-> exponential_pdf(rate, 1)", "1", 0.0);
+    assert_grad("x |-> exponential_cdf(2, x)", "0", 2.0);
+    let e = std::f64::consts::E;
+    assert_grad("mean |-> poisson_pmf(mean, 1)", "1", 0.0);
+    assert_grad("mean |-> poisson_cdf(mean, 0)", "1", -1.0 / e);
+    assert_grad("p |-> binomial_pmf(2, p, 1)", "0.25", 1.0);
+    assert_grad("p |-> binomial_cdf(2, p, 0)", "0.25", -1.5);
+}
+
+#[test]
+fn test_statistics() {
+    assert_grad("x |-> mean([x, 2 * x, 3])", "1", 1.0);
+    assert_grad("x |-> variance([x, 1, 2])", "0", -1.0);
+    assert_grad("x |-> pvariance([x, 2])", "0", -1.0);
+    assert_grad("x |-> stddev([x, 0])", "2", 2f64.sqrt() / 2.0);
+    assert_grad("x |-> pstddev([x, 0])", "2", 0.5);
+    assert_grad("x |-> median([x, 5, 1])", "3", 1.0);
+    assert_grad("x |-> median([x, 5, 1, 2])", "3", 0.5);
+    assert_grad("x |-> quantile([1, x, 5], 0.25)", "3", 0.5);
+    assert_grad("q |-> quantile([1, 3, 5], q)", "0.25", 4.0);
+}
+
+#[test]
+fn test_not_differentiable() {
+    assert_eq!(
+        execute("grad(x |-> mode([x, 1]))(2)"),
+        Err("mode() cannot be differentiated, found 2 + 1ε".into())
+    );
+    assert_eq!(
+        execute("grad(x |-> factorial(x))(3)"),
+        Err("factorial() cannot be differentiated, found 3 + 1ε".into())
+    );
+    assert_eq!(
+        execute("grad(x |-> correlation([x, 1], [1, 2]))(3)"),
+        Err("correlation() cannot be differentiated, found 3 + 1ε".into())
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        execute("grad(x |-> [x])(1)"),
+        Err("grad() expects the function to return a real number, found [1 + 1ε]".into())
+    );
+    assert_eq!(
+        execute("grad(grad(x |-> x ^ 3))(2)"),
+        Err("grad() cannot be nested".into())
+    );
+    assert_eq!(
+        execute("grad(1)"),
+        Err("grad() expects a function, found 1".into())
+    );
+}
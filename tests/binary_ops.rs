@@ -1,6 +1,9 @@
+mod common;
+
+use common::display;
 use mathfp::interpreter::Interpreter;
 use mathfp::runtime::RuntimeValue;
-use mathfp::{execute_env_or_panic, execute_or_panic};
+use mathfp::{execute, execute_env_or_panic, execute_or_panic};
 
 // assert helper
 fn assert_bool(interpreter: &Interpreter, name: &str, cond: bool) {
@@ -97,3 +100,44 @@ fn test_off_by_one() {
     assert_bool(&interpreter, "test4", true);
     assert_bool(&interpreter, "test5", true);
 }
+
+#[test]
+fn test_sum_binds_looser_than_comparison() {
+    // `+` and `-` bind more loosely than comparisons, so this is 1 + (2 < 4).
+    assert_eq!(
+        execute("1 + 2 < 4"),
+        Err("Unsupported operands for '+': 1, true".to_string())
+    );
+    assert_eq!(execute_or_panic("(1 + 2) < 4"), RuntimeValue::Boolean(true));
+    assert_eq!(execute_or_panic("2 * 3 == 6"), RuntimeValue::Boolean(true));
+    assert_eq!(
+        execute_or_panic("1 < 2 == 2 < 3"),
+        RuntimeValue::Boolean(true)
+    );
+}
+
+#[test]
+fn test_coefficient_before_name() {
+    // A number directly before a name multiplies it, so 2x^2 is 2 * x^2.
+    assert_eq!(display("x := 3; 2x"), "6");
+    assert_eq!(display("x := 3; 2x^2 + 1"), "19");
+    assert_eq!(display("x := 3; -1.5x"), "-4.5");
+    assert_eq!(display("f := x |-> 4x; f(2)"), "8");
+    assert_eq!(display("[3i, 1/2r]"), "[0 + 3i, 1/2]");
+    assert!(execute("x := 3; 2 x").is_err());
+}
+
+#[test]
+fn test_exponent_literals() {
+    // An exponent belongs to the number, so 1e-3 is not 1 * e - 3.
+    assert_eq!(display("1e-3"), "0.001");
+    assert_eq!(display("1e3"), "1000");
+    assert_eq!(display("2.5E+2"), "250");
+    assert_eq!(display("det([|1e-20, 1; 1, 1|])"), "-1");
+    assert_eq!(display("2exp(0)"), "2");
+    assert!(
+        execute("2e")
+            .unwrap_err()
+            .contains("Expected digits after the exponent in '2e'")
+    );
+}
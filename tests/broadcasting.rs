@@ -0,0 +1,127 @@
+mod common;
+
+use common::display;
+use mathfp::execute_or_panic;
+
+#[test]
+fn test_list_and_scalar() {
+    assert_eq!(display("[1, 2, 3] .* 2"), "[2, 4, 6]");
+    assert_eq!(display("10 .- [1, 2]"), "[9, 8]");
+    assert_eq!(display("[1, 2] ./ 4"), "[1/4, 1/2]");
+    assert_eq!(display("2 .^ [1, 2, 3]"), "[2, 4, 8]");
+    assert_eq!(display("[7, 8, 9] .% 3"), "[1, 2, 0]");
+}
+
+#[test]
+fn test_list_and_list() {
+    assert_eq!(display("[1, 2, 3] .+ [10, 20, 30]"), "[11, 22, 33]");
+    assert_eq!(
+        display("[[1, 2], [3, 4]] .* [[2, 2], [3, 3]]"),
+        "[[2, 4], [9, 12]]"
+    );
+}
+
+#[test]
+fn test_mixed_ranks_align_innermost_level() {
+    // Like NumPy, a list with fewer levels is matched against the innermost level.
+    assert_eq!(
+        display("[[1, 2], [3, 4]] .* [10, 20]"),
+        "[[10, 40], [30, 80]]"
+    );
+    assert_eq!(
+        display("[10, 20] .+ [[1, 2], [3, 4]]"),
+        "[[11, 22], [13, 24]]"
+    );
+    assert_eq!(
+        display("[[[1, 2]], [[3, 4]]] .- [1, 1]"),
+        "[[[0, 1]], [[2, 3]]]"
+    );
+    assert_eq!(
+        display("[[1, 2], [3, 4]] .> [2, 2]"),
+        "[[false, false], [true, true]]"
+    );
+}
+
+#[test]
+fn test_length_one_stretches() {
+    assert_eq!(display("[[1], [2]] .* [10, 20]"), "[[10, 20], [20, 40]]");
+    assert_eq!(
+        display("[[1, 2], [3, 4]] .+ [[10], [20]]"),
+        "[[11, 12], [23, 24]]"
+    );
+    assert_eq!(display("[5] .- [1, 2, 3]"), "[4, 3, 2]");
+}
+
+#[test]
+fn test_unary_minus_maps_over_lists() {
+    assert_eq!(display("-[1, -2]"), "[-1, 2]");
+    assert_eq!(display("-[[1, 2/3r], [1.5]]"), "[[-1, -2/3], [-1.5]]");
+    assert_eq!(display("-[]"), "[]");
+}
+
+#[test]
+fn test_plus_still_concatenates() {
+    assert_eq!(display("[1, 2] + [3]"), "[1, 2, 3]");
+    assert_eq!(display("[1, 2] .+ [3, 4]"), "[4, 6]");
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(display("[1, 5, 3] .> 2"), "[false, true, true]");
+    assert_eq!(display("[1, 2] .== [1, 3]"), "[true, false]");
+    assert_eq!(display("[1, 2] == [1, 3]"), "false");
+}
+
+#[test]
+fn test_precedence() {
+    assert_eq!(display("[1, 2] .+ [3, 4] .* 2"), "[7, 10]");
+    assert_eq!(display("2 .* [1, 2] .^ 2"), "[2, 8]");
+}
+
+#[test]
+fn test_sections() {
+    assert_eq!(display("(.* 10)([1, [2, 3]])"), "[10, [20, 30]]");
+    assert_eq!(display("(1 ./)([2, 4])"), "[1/2, 1/4]");
+    assert_eq!(display("(.+)([1], [2])"), "[3]");
+}
+
+#[test]
+fn test_math_functions_map_over_lists() {
+    assert_eq!(display("sqrt([4, [9, 16]])"), "[2, [3, 4]]");
+    assert_eq!(display("abs([-1, 2/3 - 1])"), "[1, 1/3]");
+    assert_eq!(display("sqrt([-4])"), "[0 + 2i]");
+}
+
+#[test]
+fn test_matrices() {
+    assert_eq!(
+        display("[|1, 2; 3, 4|] .* [|1, 2; 3, 4|]"),
+        "[|1, 4; 9, 16|]"
+    );
+    assert_eq!(display("[|1, 2|] .^ 2"), "[|1, 4|]");
+    assert_eq!(display("1 ./ [|2, 4|]"), "[|0.5, 0.25|]");
+}
+
+#[test]
+#[should_panic(expected = "Cannot apply '.+' to lists of lengths 3 and 2")]
+fn test_length_mismatch() {
+    execute_or_panic("[1, 2, 3] .+ [1, 2]");
+}
+
+#[test]
+#[should_panic(expected = "Cannot apply '.+' to lists of lengths 3 and 2")]
+fn test_nested_length_mismatch() {
+    execute_or_panic("[[1, 2, 3], [4, 5]] .+ [[1, 2], [3, 4]]");
+}
+
+#[test]
+#[should_panic(expected = "Cannot apply '.*' to matrices of shapes 2x1 and 1x2")]
+fn test_matrix_shape_mismatch() {
+    execute_or_panic("[|1, 2|] .* [|1, 2;|]");
+}
+
+#[test]
+#[should_panic(expected = "Unsupported operands for '-'")]
+fn test_unsupported_element() {
+    execute_or_panic("[1, \"a\"] .- 1");
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Writes `source` to a temporary file and runs `mathfp check` on it.
+fn check_source(name: &str, source: &str) -> Output {
+    let path: PathBuf = std::env::temp_dir().join(format!("mathfp_cli_{name}.mfp"));
+    fs::write(&path, source).expect("write the program");
+    let output = Command::new(env!("CARGO_BIN_EXE_mathfp"))
+        .arg("check")
+        .arg(&path)
+        .output()
+        .expect("run mathfp");
+    let _ = fs::remove_file(&path);
+    output
+}
+
+#[test]
+fn test_check_well_typed_program_succeeds() {
+    let output = check_source("well_typed", "double := x |-> 2 * x;\ndouble(3);\n");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "Number");
+}
+
+#[test]
+fn test_check_ill_typed_program_fails() {
+    let output = check_source("ill_typed", "1 + \"two\";\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Type mismatch"));
+}
+
+#[test]
+fn test_check_locates_mismatched_literals() {
+    let output = check_source("mismatched_branches", "if 1 then 2 else \"a\";\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[Line 1, Col 2] Type mismatch"), "{stderr}");
+
+    let output = check_source("mismatched_assignment", "x := 1;\nx = \"s\";\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[Line 2, Col 4] Type mismatch"), "{stderr}");
+}
@@ -0,0 +1,6 @@
+use mathfp::execute_or_panic;
+
+/// Runs a program and formats its result as the REPL prints it.
+pub fn display(input: &str) -> String {
+    execute_or_panic(input).to_string()
+}
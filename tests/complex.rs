@@ -0,0 +1,82 @@
+mod common;
+
+use common::display;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_imaginary_literals() {
+    assert_eq!(display("4i"), "0 + 4i");
+    assert_eq!(display("3 + 4i"), "3 + 4i");
+    assert_eq!(display("3 - 4i"), "3 - 4i");
+    assert_eq!(display("-(2 + 1.5i)"), "-2 - 1.5i");
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(display("(1 + 2i) * (3 - 1i)"), "5 + 5i");
+    assert_eq!(display("(1 + 2i) - (1 + 2i)"), "0 + 0i");
+    assert_eq!(display("1 / (1 + 1i)"), "0.5 - 0.5i");
+    assert_eq!(display("1i ^ 2"), "-1 + 0i");
+    assert_eq!(display("(1 + 1i) ^ -2"), "0 - 0.5i");
+}
+
+#[test]
+fn test_equality_with_reals() {
+    assert_eq!(execute("1i * 1i == -1"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(
+        execute("conj(3 + 4i) == (3 - 4i)"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+    assert_eq!(execute("1i == 1"), Ok(RuntimeValue::Boolean(false)));
+}
+
+#[test]
+fn test_parts() {
+    assert_eq!(execute("re(2 + 5i)"), Ok(RuntimeValue::Number(2.0)));
+    assert_eq!(execute("im(2 + 5i)"), Ok(RuntimeValue::Number(5.0)));
+    assert_eq!(execute("abs(3 + 4i)"), Ok(RuntimeValue::Number(5.0)));
+    assert_eq!(
+        execute("arg(1i)"),
+        Ok(RuntimeValue::Number(std::f64::consts::FRAC_PI_2))
+    );
+    assert_eq!(execute("im(7)"), Ok(RuntimeValue::Number(0.0)));
+}
+
+#[test]
+fn test_real_functions_go_complex_without_a_real_result() {
+    assert_eq!(display("sqrt(-1)"), "0 + 1i");
+    assert_eq!(display("sqrt(-4)"), "0 + 2i");
+    assert_eq!(execute("sqrt(4)"), Ok(RuntimeValue::Number(2.0)));
+    assert_eq!(display("ln(-1)"), "0 + 3.141592653589793i");
+    assert_eq!(execute("ln(1)"), Ok(RuntimeValue::Number(0.0)));
+    assert_eq!(execute("abs(0 - 3)"), Ok(RuntimeValue::Number(3.0)));
+}
+
+#[test]
+fn test_negative_base_with_fractional_power() {
+    assert_eq!(
+        execute("abs((-8) ^ (1/3) - (1 + sqrt(3) * 1i)) < 0.000000001"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+    assert_eq!(execute("(-8) ^ 2"), Ok(RuntimeValue::Number(64.0)));
+}
+
+#[test]
+fn test_complex_functions() {
+    assert_eq!(
+        execute("abs(exp(3.141592653589793i) + 1) < 0.000000001"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+    assert_eq!(display("sqrt(-3 + 4i)"), "1 + 2i");
+    assert_eq!(
+        execute("abs(exp(ln(2 + 3i)) - (2 + 3i)) < 0.000000001"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Complex numbers cannot be ordered")]
+fn test_no_ordering() {
+    execute_or_panic("1i < 2i");
+}
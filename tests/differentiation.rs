@@ -0,0 +1,87 @@
+mod common;
+
+use common::display;
+use mathfp::execute;
+
+#[test]
+fn test_polynomials() {
+    assert_eq!(display("diff(x |-> x ^ 3)"), "x |-> 3 * x ^ 2");
+    assert_eq!(
+        display("diff(x |-> 3 * x ^ 2 + 2 * x + 1)"),
+        "x |-> 6 * x + 2"
+    );
+    assert_eq!(display("diff(x |-> 1 / x)"), "x |-> -1 / x ^ 2");
+    assert_eq!(display("diff(x |-> x / 2)"), "x |-> 1/2r");
+    assert_eq!(display("diff(x |-> x ^ 0.5)"), "x |-> 0.5 / x ^ 0.5");
+}
+
+#[test]
+fn test_products_and_quotients() {
+    assert_eq!(
+        display("diff(x |-> x * sin(x))"),
+        "x |-> x * cos(x) + sin(x)"
+    );
+    assert_eq!(
+        display("diff(x |-> exp(2 * x) / x)"),
+        "x |-> (2 * x * exp(2 * x) - exp(2 * x)) / x ^ 2"
+    );
+}
+
+#[test]
+fn test_chain_rule() {
+    assert_eq!(
+        display("diff(x |-> sin(x ^ 2))"),
+        "x |-> 2 * x * cos(x ^ 2)"
+    );
+    assert_eq!(display("diff(x |-> sqrt(x))"), "x |-> 1 / (2 * sqrt(x))");
+    assert_eq!(display("diff(x |-> ln(cos(x)))"), "x |-> -sin(x) / cos(x)");
+    assert_eq!(display("diff(x |-> 2 ^ x)"), "x |-> 2 ^ x * ln(2)");
+    assert_eq!(display("diff(x |-> x ^ x)"), "x |-> x ^ x * (ln(x) + 1)");
+}
+
+#[test]
+fn test_user_functions() {
+    let input = "
+        g := t |-> t ^ 2 + 1;
+        f := x |-> g(sin(x));
+        diff(f)";
+    assert_eq!(display(input), "x |-> cos(x) * g'(sin(x))");
+
+    let input = "
+        g := t |-> t ^ 2 + 1;
+        f := x |-> g(sin(x));
+        abs(diff(f)(1) - 2 * sin(1) * cos(1)) < 0.000000001";
+    assert_eq!(display(input), "true");
+}
+
+#[test]
+fn test_derivatives_are_callable() {
+    assert_eq!(display("diff(x |-> x ^ 2)(3)"), "6");
+    assert_eq!(display("diff(diff(x |-> x ^ 4))"), "x |-> 12 * x ^ 2");
+    assert_eq!(display("k := 5; diff(x |-> k * x ^ 2)(1)"), "10");
+}
+
+#[test]
+fn test_branches_and_partial_derivatives() {
+    assert_eq!(
+        display("diff(x |-> if x > 0 then x ^ 2 else -x)"),
+        "x |-> if x > 0 then 2 * x else -1"
+    );
+    assert_eq!(display("diff((x, y) |-> x * y + y ^ 2)"), "x |-> y |-> y");
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        execute("diff(sin)"),
+        Err("diff() expects a user-defined function, found <native function sin>".into())
+    );
+    assert_eq!(
+        execute("f := n |-> if n < 1 then 1 else n * f(n - 1); diff(f)"),
+        Err("diff() cannot differentiate the recursive function f".into())
+    );
+    assert_eq!(
+        execute("diff(x |-> str(x))"),
+        Err("diff() does not know the derivative of str".into())
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::display;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_large_powers_are_exact() {
+    assert_eq!(display("2 ^ 100"), "1267650600228229401496703205376");
+    assert_eq!(display("(-3) ^ 41"), "-36472996377170786403");
+    assert_eq!(display("2 ^ 64 - 1"), "18446744073709551615");
+}
+
+#[test]
+fn test_factorial_past_float_precision() {
+    let input = "fact := n |-> if n == 0 then 1 else n * fact(n - 1); fact(30)";
+    assert_eq!(display(input), "265252859812191058636308480000000");
+}
+
+#[test]
+fn test_large_literals() {
+    assert_eq!(
+        display("123456789012345678901234567890 + 1"),
+        "123456789012345678901234567891"
+    );
+    assert_eq!(
+        execute("9007199254740993 == 9007199254740992"),
+        Ok(RuntimeValue::Boolean(false))
+    );
+}
+
+#[test]
+fn test_floored_division_and_remainder() {
+    assert_eq!(display("7 div 2"), "3");
+    assert_eq!(display("(-7) div 2"), "-4");
+    assert_eq!(display("7 % 3"), "1");
+    assert_eq!(display("(-7) % 3"), "2");
+    assert_eq!(display("7 % -3"), "-2");
+    assert_eq!(display("10 ^ 30 % 7"), "1");
+}
+
+#[test]
+fn test_division_gives_fraction_unless_whole() {
+    assert_eq!(display("8 / 2"), "4");
+    assert_eq!(display("7 / 2"), "7/2");
+    assert_eq!(display("2 ^ -2"), "1/4");
+    assert_eq!(display("7/2 + 1/2"), "4");
+}
+
+#[test]
+fn test_demotes_to_float() {
+    assert_eq!(execute("7 / 2.0"), Ok(RuntimeValue::Number(3.5)));
+    assert_eq!(execute("2 ^ 0.5"), Ok(RuntimeValue::Number(2f64.sqrt())));
+    assert_eq!(execute("sqrt(16)"), Ok(RuntimeValue::Number(4.0)));
+    assert_eq!(execute("7.5 % 2"), Ok(RuntimeValue::Number(1.5)));
+    assert_eq!(execute("7.5 div 2"), Ok(RuntimeValue::Number(3.0)));
+}
+
+#[test]
+fn test_operator_sections() {
+    assert_eq!(display("(% 3)(10)"), "1");
+    assert_eq!(display("(div)(9, 2)"), "4");
+}
+
+#[test]
+fn test_division_by_zero() {
+    // `/` divides by zero as floats do, while `div` and `%` have no result.
+    assert_eq!(display("1 / 0"), "inf");
+    assert_eq!(display("-1 / 0"), "-inf");
+    assert_eq!(display("0 / 0"), "NaN");
+    assert_eq!(display("0 ^ -1"), "inf");
+    assert!(execute("7 % 0").is_err());
+}
+
+#[test]
+#[should_panic(expected = "Division by zero")]
+fn test_integer_division_by_zero() {
+    execute_or_panic("1 div 0");
+}
+
+#[test]
+#[should_panic(expected = "too large for an exact power")]
+fn test_huge_power_is_refused() {
+    execute_or_panic("10 ^ 100000000");
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::display;
+use mathfp::{check, execute};
+
+#[test]
+fn test_literals() {
+    assert_eq!(display("[1.5 .. 2.25]"), "[1.5 .. 2.25]");
+    assert_eq!(display("[1..2]"), "[1 .. 2]");
+    assert_eq!(display("a := 3; [a - 1 .. a + 1]"), "[2 .. 4]");
+    assert_eq!(display("interval(1, 2) == [1 .. 2]"), "true");
+    assert_eq!(display("[1, 2]"), "[1, 2]");
+}
+
+#[test]
+fn test_inexact_bounds_round_outward() {
+    // No float is exactly 0.1, so the bounds are the floats on either side of it.
+    assert_eq!(
+        display("[0.1 .. 0.1]"),
+        "[0.09999999999999999 .. 0.10000000000000002]"
+    );
+    assert_eq!(display("interval(0.1, 0.1) == [0.1 .. 0.1]"), "true");
+    assert_eq!(display("contains([0.1 .. 0.1], [1/10r .. 1/10r])"), "true");
+    assert_eq!(
+        display("[1.9 .. 2.1]"),
+        "[1.8999999999999997 .. 2.1000000000000005]"
+    );
+    assert_eq!(display("[0.1 .. 0.5]"), "[0.09999999999999999 .. 0.5]");
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(display("[1 .. 2] + [0.5 .. 1]"), "[1.5 .. 3]");
+    assert_eq!(display("[1 .. 2] - 1"), "[0 .. 1]");
+    assert_eq!(display("[-1 .. 2] * [3 .. 4]"), "[-4 .. 8]");
+    assert_eq!(display("[2 .. 4] / [1 .. 2]"), "[1 .. 4]");
+    assert_eq!(display("[-1 .. 2] ^ 2"), "[0 .. 4]");
+    assert_eq!(display("-[1 .. 2]"), "[-2 .. -1]");
+    // The bounds of 0.1 and their sum with 0.2 are rounded outward, so 0.3 is inside.
+    assert_eq!(
+        display("[0.1 .. 0.1] + 0.2"),
+        "[0.3 .. 0.30000000000000004]"
+    );
+    assert_eq!(
+        display("[1 .. 1] / 3"),
+        "[0.3333333333333333 .. 0.33333333333333337]"
+    );
+}
+
+#[test]
+fn test_functions() {
+    assert_eq!(display("sqrt([4 .. 9])"), "[2 .. 3]");
+    assert_eq!(display("cos([0 .. 7])"), "[-1 .. 1]");
+    assert_eq!(display("contains(sin([1 .. 2]), 1)"), "true");
+    assert_eq!(display("contains(sin([1 .. 2]), sin(1))"), "true");
+    assert_eq!(display("contains(sin([1 .. 2]), sin(0.9))"), "false");
+    assert_eq!(display("width([1.5 .. 2])"), "0.5");
+    assert_eq!(display("mid([1 .. 2])"), "1.5");
+    assert_eq!(display("contains([1 .. 3], [2 .. 3])"), "true");
+    assert_eq!(display("contains([1 .. 3], 4)"), "false");
+    assert_eq!(display("poly([1, 0, -1])([1 .. 2])"), "[0 .. 3]");
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(display("[1 .. 2] < 3"), "true");
+    assert_eq!(display("[1 .. 2] > [3 .. 4]"), "false");
+    assert_eq!(display("[1 .. 2] < 1.5"), "nil");
+    assert_eq!(display("[1 .. 2] <= [2 .. 3]"), "true");
+    assert_eq!(
+        display("x := [1.9 .. 2.1]; if x * x < 5 then \"safe\" else \"check\""),
+        "\"safe\""
+    );
+}
+
+#[test]
+fn test_type_check() {
+    assert_eq!(check("sin([1 .. 2])").unwrap().to_string(), "Number");
+    assert!(check("[1 .. \"a\"]").is_err());
+}
+
+#[test]
+fn test_errors() {
+    assert!(execute("[2 .. 1]").is_err());
+    assert!(execute("[1 .. \"a\"]").is_err());
+    assert!(execute("[1 .. 2] / [-1 .. 1]").is_err());
+    assert!(execute("sqrt([-1 .. 1])").is_err());
+    assert!(execute("[1 .. 2] ^ 0.5").is_err());
+    assert!(execute("width(2)").is_err());
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::display;
+use mathfp::{check, execute};
+
+fn error(input: &str) -> String {
+    execute(input).unwrap_err()
+}
+
+#[test]
+fn test_access() {
+    assert_eq!(display("len([1, 2, 3]);"), "3");
+    assert_eq!(display("len([]);"), "0");
+    assert_eq!(display("len(\"hello\");"), "5");
+    assert_eq!(display("head([1, 2, 3]);"), "1");
+    assert_eq!(display("tail([1, 2, 3]);"), "[2, 3]");
+    assert_eq!(display("tail([1]);"), "[]");
+    assert_eq!(display("head(tail(iterate(x |-> x + 1, 0)));"), "1");
+    assert!(error("head([]);").contains("head() expects a non-empty list or stream"));
+    assert!(error("tail(take(0, repeat(1)));").contains("tail() expects a non-empty"));
+}
+
+#[test]
+fn test_folds() {
+    assert_eq!(
+        display("foldl((acc, x) |-> acc + x, 0, [1, 2, 3, 4]);"),
+        "10"
+    );
+    assert_eq!(display("foldl((acc, x) |-> acc - x, 10, [1, 2, 3]);"), "4");
+    assert_eq!(display("foldr((x, acc) |-> x - acc, 0, [1, 2, 3]);"), "2");
+    assert_eq!(
+        display("foldr((x, acc) |-> [x, acc], nil, [1, 2]);"),
+        "[1, [2, nil]]"
+    );
+    assert_eq!(display("foldl((acc, x) |-> acc * x, 1, []);"), "1");
+    // Folds consume finite streams too.
+    assert_eq!(
+        display(
+            "foldl((acc, x) |-> acc + x, 0, take_while(x |-> x < 100, iterate(x |-> x * 2, 1)));"
+        ),
+        "127"
+    );
+    // Partial application gives a reusable function.
+    assert_eq!(
+        display("sum := foldl((a, b) |-> a + b, 0); sum([1, 2]) + sum([3]);"),
+        "6"
+    );
+}
+
+#[test]
+fn test_pairs() {
+    assert_eq!(
+        display("zip([1, 2, 3], [\"a\", \"b\"]);"),
+        "[[1, \"a\"], [2, \"b\"]]"
+    );
+    assert_eq!(display("zip([1, 2], repeat(0));"), "[[1, 0], [2, 0]]");
+    assert_eq!(
+        display("enumerate([\"a\", \"b\"]);"),
+        "[[0, \"a\"], [1, \"b\"]]"
+    );
+    assert_eq!(display("enumerate([]);"), "[]");
+    assert!(error("zip(1, [2]);").contains("zip() expects a list or stream"));
+}
+
+#[test]
+fn test_ordering() {
+    assert_eq!(display("reverse([1, 2, 3]);"), "[3, 2, 1]");
+    assert_eq!(display("sort([3, 1.5, 2, 1/2r]);"), "[1/2, 1.5, 2, 3]");
+    assert_eq!(
+        display("sort([\"pear\", \"apple\"]);"),
+        "[\"apple\", \"pear\"]"
+    );
+    assert_eq!(display("sort_by(x |-> -x, [1, 3, 2]);"), "[3, 2, 1]");
+    // Sorting is stable, so equal keys keep their order.
+    assert_eq!(
+        display("sort_by(p |-> head(p), [[2, \"a\"], [1, \"b\"], [2, \"c\"]]);"),
+        "[[1, \"b\"], [2, \"a\"], [2, \"c\"]]"
+    );
+    assert!(error("sort([1, \"a\"]);").contains("sort() cannot compare"));
+    assert!(error("sort([[1], [2]]);").contains("sort() cannot compare ["));
+}
+
+#[test]
+fn test_searching() {
+    assert_eq!(display("any(x |-> x > 2, [1, 2, 3]);"), "true");
+    assert_eq!(display("any(x |-> x > 5, []);"), "false");
+    assert_eq!(display("all(x |-> x > 0, [1, 2, 3]);"), "true");
+    assert_eq!(display("all(x |-> x > 1, [1, 2, 3]);"), "false");
+    assert_eq!(display("find(x |-> x > 1, [1, 2, 3]);"), "2");
+    assert_eq!(display("find(x |-> x > 5, [1, 2, 3]);"), "nil");
+    // These stop at the first element that decides the result, even in an infinite stream.
+    assert_eq!(
+        display("find(n |-> n * n > 50, iterate(n |-> n + 1, 1));"),
+        "8"
+    );
+    assert_eq!(display("any(x |-> x == 0, cycle([1, 0]));"), "true");
+    assert_eq!(
+        display("all(x |-> x < 3, iterate(x |-> x + 1, 0));"),
+        "false"
+    );
+}
+
+#[test]
+fn test_flatten_and_range() {
+    assert_eq!(
+        display("flatten([[1, 2], [], [3, [4]]]);"),
+        "[1, 2, 3, [4]]"
+    );
+    assert!(error("flatten([1, [2]]);").contains("flatten() expects a list of lists"));
+    assert_eq!(display("range(0, 5);"), "[0, 1, 2, 3, 4]");
+    assert_eq!(display("range(-2, 1);"), "[-2, -1, 0]");
+    assert_eq!(display("range(3, 3);"), "[]");
+    assert_eq!(display("range(5, 0);"), "[]");
+    assert_eq!(
+        display("range(2 ^ 64, 2 ^ 64 + 2);"),
+        "[18446744073709551616, 18446744073709551617]"
+    );
+    assert!(error("range(0, 10 ^ 12);").contains("range() builds at most"));
+    assert!(error("range(0.5, 3);").contains("range() expects an integer"));
+}
+
+#[test]
+fn test_with_map_and_filter() {
+    assert_eq!(
+        display(
+            "foldl((a, b) |-> a + b, 0, map(x |-> x * x, filter(x |-> x % 2 == 0, range(0, 10))));"
+        ),
+        "120"
+    );
+}
+
+#[test]
+fn test_types() {
+    assert!(check("xs: List<Number> := sort(range(0, 3));").is_ok());
+    assert!(check("n: Number := foldl((a, b) |-> a + b, 0, [1, 2]);").is_ok());
+    assert!(check("ok: Boolean := all(x |-> x > 0, [1]);").is_ok());
+    assert!(check("reverse(1);").is_err());
+    assert!(check("flatten([1, 2]);").is_err());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::display;
+use mathfp::{check, execute};
+
+#[test]
+fn test_constants() {
+    assert_eq!(display("pi;"), "3.141592653589793");
+    assert_eq!(display("e;"), "2.718281828459045");
+    assert_eq!(display("tau == 2 * pi;"), "true");
+    assert_eq!(display("[inf, -inf];"), "[inf, -inf]");
+    assert_eq!(display("nan == nan;"), "false");
+    assert!(execute("pi := 3;").is_err());
+}
+
+#[test]
+fn test_trigonometry() {
+    assert_eq!(display("tan(pi / 4);"), "0.9999999999999999");
+    assert_eq!(display("asin(1) == pi / 2;"), "true");
+    assert_eq!(display("acos(1);"), "0");
+    assert_eq!(display("atan(1) == pi / 4;"), "true");
+    assert_eq!(display("atan2(1, -1) == 3 * pi / 4;"), "true");
+    assert_eq!(display("atan2(0, -1) == pi;"), "true");
+    assert_eq!(display("hypot(3, 4);"), "5");
+    assert_eq!(display("asin(2);"), "NaN");
+}
+
+#[test]
+fn test_hyperbolic() {
+    assert_eq!(display("sinh(0);"), "0");
+    assert_eq!(display("cosh(0);"), "1");
+    assert_eq!(display("tanh(1000);"), "1");
+    assert_eq!(display("asinh(sinh(2));"), "2");
+    assert_eq!(display("acosh(1);"), "0");
+    assert_eq!(display("atanh(0);"), "0");
+}
+
+#[test]
+fn test_logarithms_and_exponentials() {
+    assert_eq!(display("log10(1000);"), "3");
+    assert_eq!(display("log2(1024);"), "10");
+    assert_eq!(display("exp2(10);"), "1024");
+    assert_eq!(display("cbrt(-27);"), "-3");
+    // These keep their precision where the naive formulas lose it.
+    assert_eq!(
+        display("log1p(0.000000000000001);"),
+        "0.0000000000000009999999999999995"
+    );
+    assert_eq!(
+        display("expm1(0.000000000000001);"),
+        "0.0000000000000010000000000000007"
+    );
+    assert_eq!(display("log10(-100);"), "2 + 1.3643763538418412i");
+}
+
+#[test]
+fn test_rounding() {
+    assert_eq!(
+        display("[floor(2.5), ceil(2.5), round(2.5), trunc(-2.5)];"),
+        "[2, 3, 3, -2]"
+    );
+    assert_eq!(display("round(-2.5);"), "-3");
+    assert_eq!(display("fract(-2.25);"), "-0.25");
+    assert_eq!(display("[sign(-3), sign(0), sign(0.5)];"), "[-1, 0, 1]");
+    // Exact numbers round to exact integers.
+    assert_eq!(display("floor(-7/2r);"), "-4");
+    assert_eq!(display("ceil(7/2r);"), "4");
+    assert_eq!(display("round(-7/2r);"), "-4");
+    assert_eq!(display("trunc(-7/2r);"), "-3");
+    assert_eq!(display("fract(7/2r);"), "1/2");
+    assert_eq!(
+        display("floor(10^30 + 1/2r);"),
+        "1000000000000000000000000000000"
+    );
+    assert_eq!(display("floor([1.5, 2.5]);"), "[1, 2]");
+}
+
+#[test]
+fn test_min_max() {
+    assert_eq!(display("min(3, 4);"), "3");
+    assert_eq!(display("max(3, 4);"), "4");
+    assert_eq!(display("max(1/3r, 1/4r);"), "1/3");
+    assert_eq!(display("min(10^20, 10^20 + 1);"), "100000000000000000000");
+    assert_eq!(display("min(nan, 1);"), "1");
+    assert_eq!(display("lower := min(0); lower(-2);"), "-2");
+    assert!(execute("min(1, \"a\");").is_err());
+}
+
+#[test]
+fn test_derivatives() {
+    assert_eq!(display("grad(x |-> tan(x), 0);"), "1");
+    assert_eq!(display("grad(x |-> atan(x), 1);"), "0.5");
+    assert_eq!(display("grad(x |-> log2(x), 1) == 1 / ln(2);"), "true");
+    assert_eq!(display("grad(x |-> hypot(x, 4), 3);"), "0.6");
+    assert_eq!(display("grad(x |-> max(x, 2) * 3, 5);"), "3");
+    assert_eq!(display("grad(x |-> floor(x), 1.5);"), "0");
+    assert_eq!(display("diff(x |-> sinh(x))(0);"), "1");
+    assert_eq!(display("diff(x |-> atan(x))(1);"), "1/2");
+}
+
+#[test]
+fn test_intervals() {
+    assert_eq!(display("floor([0.5 .. 2.5]);"), "[0 .. 2]");
+    assert_eq!(
+        display("y := atan([0 .. 1]); [contains(y, 0), contains(y, pi / 4)];"),
+        "[true, true]"
+    );
+    assert_eq!(display("max([0 .. 2], [1 .. 3]);"), "[1 .. 3]");
+    assert_eq!(display("contains(exp([0 .. 1]), e);"), "true");
+    assert!(execute("asin([0 .. 2]);").is_err());
+    assert!(execute("ln([-1 .. 1]);").is_err());
+    assert!(execute("tan([0 .. 1]);").is_err());
+}
+
+#[test]
+fn test_types() {
+    assert!(check("x: Number := floor(pi) + max(e, 2);").is_ok());
+    assert!(check("tan(\"a\");").is_err());
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::display;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_literals() {
+    assert_eq!(display("[|1, 2; 3, 4|]"), "[|1, 2; 3, 4|]");
+    assert_eq!(display("[|1, 2, 3|]"), "[|1, 2, 3|]");
+    assert_eq!(display("[|1, 2, 3;|]"), "[|1, 2, 3;|]");
+    assert_eq!(display("[|1; 2|]"), "[|1, 2|]");
+    assert_eq!(
+        display("x := 2; [|x, x ^ 2; 1/2, -x|]"),
+        "[|2, 4; 0.5, -2|]"
+    );
+}
+
+#[test]
+fn test_element_wise_arithmetic() {
+    assert_eq!(display("[|1, 2; 3, 4|] + [|1, 1; 1, 1|]"), "[|2, 3; 4, 5|]");
+    assert_eq!(display("[|1, 2|] - [|3, 5|]"), "[|-2, -3|]");
+    assert_eq!(display("2 * [|1, 2|]"), "[|2, 4|]");
+    assert_eq!(display("[|1, 2|] / 4"), "[|0.25, 0.5|]");
+    assert_eq!(display("-[|1, 2|]"), "[|-1, -2|]");
+}
+
+#[test]
+fn test_matrix_multiplication() {
+    assert_eq!(
+        display("[|1, 2; 3, 4|] * [|5, 6; 7, 8|]"),
+        "[|19, 22; 43, 50|]"
+    );
+    assert_eq!(display("[|2, 1; 1, 3|] * [|1, 2|]"), "[|4, 7|]");
+    assert_eq!(display("transpose([|1, 2, 3|]) * [|1, 2, 3|]"), "[|14|]");
+}
+
+#[test]
+fn test_transpose_and_shape() {
+    assert_eq!(
+        display("transpose([|1, 2, 3; 4, 5, 6|])"),
+        "[|1, 4; 2, 5; 3, 6|]"
+    );
+    assert_eq!(display("transpose([|1, 2|])"), "[|1, 2;|]");
+    assert_eq!(display("shape([|1, 2, 3; 4, 5, 6|])"), "[2, 3]");
+    assert_eq!(display("identity(2)"), "[|1, 0; 0, 1|]");
+}
+
+#[test]
+fn test_determinant_and_inverse() {
+    assert_eq!(
+        execute("det([|2, 1; 1, 3|])"),
+        Ok(RuntimeValue::Number(5.0))
+    );
+    assert_eq!(
+        execute("det([|1, 2; 2, 4|])"),
+        Ok(RuntimeValue::Number(0.0))
+    );
+    assert_eq!(
+        display("inverse([|2, 1; 1, 3|])"),
+        "[|0.6, -0.2; -0.2, 0.4|]"
+    );
+    assert_eq!(
+        execute("a := [|4, 7; 2, 6|]; abs(det(a * inverse(a)) - 1) < 0.000000001"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+}
+
+#[test]
+fn test_solve() {
+    assert_eq!(display("solve([|2, 1; 1, 3|], [|3, 5|])"), "[|0.8, 1.4|]");
+    assert_eq!(
+        display("solve([|1, 0; 0, 2|])([|1, 2; 3, 4|])"),
+        "[|1, 2; 1.5, 2|]"
+    );
+}
+
+#[test]
+fn test_eigenvalues() {
+    assert_eq!(
+        display("eigenvalues([|2, 0, 0; 0, 3, 4; 0, 4, 9|])"),
+        "[1, 2, 11]"
+    );
+    assert_eq!(display("eigenvalues([|0, -1; 1, 0|])"), "[0 - 1i, 0 + 1i]");
+    assert_eq!(display("eigenvalues(identity(3))"), "[1, 1, 1]");
+}
+
+#[test]
+fn test_lists_as_matrices() {
+    assert_eq!(display("matrix([[1, 2], [3, 4]])"), "[|1, 2; 3, 4|]");
+    assert_eq!(display("matrix([1, 2])"), "[|1, 2|]");
+    assert_eq!(
+        execute("det([[1, 2], [3, 4]])"),
+        Ok(RuntimeValue::Number(-2.0))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot multiply a 2x2 matrix by a 3x1 matrix")]
+fn test_multiplication_shape_error() {
+    execute_or_panic("[|1, 2; 3, 4|] * [|1, 2, 3|]");
+}
+
+#[test]
+#[should_panic(expected = "Cannot add matrices of shapes 2x1 and 1x2")]
+fn test_addition_shape_error() {
+    execute_or_panic("[|1, 2|] + [|1, 2;|]");
+}
+
+#[test]
+#[should_panic(expected = "det() expects a square matrix, found shape 2x3")]
+fn test_non_square_error() {
+    execute_or_panic("det([|1, 2, 3; 4, 5, 6|])");
+}
+
+#[test]
+#[should_panic(expected = "row 2 has 1 elements, expected 2")]
+fn test_ragged_rows() {
+    execute_or_panic("[|1, 2; 3|]");
+}
+
+#[test]
+#[should_panic(expected = "The matrix is singular")]
+fn test_singular_inverse() {
+    execute_or_panic("inverse([|1, 2; 2, 4|])");
+}
@@ -0,0 +1,98 @@
+use mathfp::interpreter::Interpreter;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_env_or_panic, execute_or_panic};
+
+#[test]
+fn test_memo_fibonacci() {
+    // Without memoization this would take around 2^80 calls.
+    let input = "
+        fib := memo(n |-> if n < 2 then n else fib(n - 1) + fib(n - 2));
+        fib(80)
+    ";
+    assert_eq!(
+        execute(input),
+        Ok(RuntimeValue::Number(23416728348467685.0))
+    );
+}
+
+#[test]
+fn test_memo_caches_calls() {
+    let interpreter = Interpreter::new();
+    let input = "
+        calls := 0;
+        square := memo(x |-> {
+            calls = calls + 1;
+            x * x
+        });
+        square(3) + square(3) + square(4)
+    ";
+    assert_eq!(
+        execute_env_or_panic(input, &interpreter),
+        RuntimeValue::Number(34.0)
+    );
+    assert_eq!(
+        interpreter.value_of("calls"),
+        Some(RuntimeValue::Number(2.0))
+    );
+    assert_eq!(
+        execute_env_or_panic("memo_size(square)", &interpreter),
+        RuntimeValue::Number(2.0)
+    );
+}
+
+#[test]
+fn test_memo_clear() {
+    let interpreter = Interpreter::new();
+    let input = "
+        calls := 0;
+        f := memo(x |-> calls = calls + 1);
+        f(1);
+        memo_clear(f);
+        f(1);
+    ";
+    execute_env_or_panic(input, &interpreter);
+    assert_eq!(
+        interpreter.value_of("calls"),
+        Some(RuntimeValue::Number(2.0))
+    );
+}
+
+#[test]
+fn test_memo_bounded() {
+    let interpreter = Interpreter::new();
+    let input = "
+        f := memo_bounded(2, x |-> x);
+        f(1); f(2); f(3);
+        memo_size(f)
+    ";
+    assert_eq!(
+        execute_env_or_panic(input, &interpreter),
+        RuntimeValue::Number(2.0)
+    );
+}
+
+#[test]
+fn test_memo_list_keys() {
+    let input = "
+        calls := 0;
+        pack := memo(pair |-> {
+            calls = calls + 1;
+            [pair, \"key\", true, nil]
+        });
+        pack([1, [2, \"a\"]]) == pack([1, [2, \"a\"]]);
+        calls
+    ";
+    assert_eq!(execute(input), Ok(RuntimeValue::Number(1.0)));
+}
+
+#[test]
+#[should_panic(expected = "Cannot memoize a call with argument <native function sin>")]
+fn test_memo_unhashable_argument() {
+    execute_or_panic("memo(f |-> f(1))(sin)");
+}
+
+#[test]
+#[should_panic(expected = "memo() expects a function")]
+fn test_memo_non_function() {
+    execute_or_panic("memo(5)");
+}
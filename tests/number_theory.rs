@@ -0,0 +1,123 @@
+mod common;
+
+use common::display;
+use mathfp::{check, execute};
+
+fn error(input: &str) -> String {
+    execute(input).unwrap_err()
+}
+
+#[test]
+fn test_divisibility() {
+    assert_eq!(display("gcd(12, 18);"), "6");
+    assert_eq!(display("gcd(-12, 0);"), "12");
+    assert_eq!(display("lcm(4, 6);"), "12");
+    assert_eq!(display("lcm(2 ^ 70, 3);"), "3541774862152233910272");
+    assert_eq!(display("gcd(12.0, 8);"), "4");
+}
+
+#[test]
+fn test_counting() {
+    assert_eq!(display("factorial(0);"), "1");
+    assert_eq!(
+        display("factorial(30);"),
+        "265252859812191058636308480000000"
+    );
+    assert_eq!(display("binomial(5, 2);"), "10");
+    assert_eq!(display("binomial(2, 5);"), "0");
+    assert_eq!(
+        display("binomial(100, 50);"),
+        "100891344545564193334812497256"
+    );
+    assert_eq!(display("gamma(5);"), "24");
+    assert_eq!(display("gamma(0.5) ^ 2;"), "3.1415926535897927");
+    assert_eq!(display("beta(2, 3);"), "0.08333333333333333");
+}
+
+#[test]
+fn test_primes() {
+    assert_eq!(display("primes(20);"), "[2, 3, 5, 7, 11, 13, 17, 19]");
+    assert_eq!(display("primes(1);"), "[]");
+    assert_eq!(
+        display("[is_prime(2), is_prime(1), is_prime(-7), is_prime(2 ^ 61 - 1)];"),
+        "[true, false, false, true]"
+    );
+    assert_eq!(display("factorize(360);"), "[2, 2, 2, 3, 3, 5]");
+    assert_eq!(display("factorize(1);"), "[]");
+    assert_eq!(
+        display("factorize(1000000007 * 998244353);"),
+        "[998244353, 1000000007]"
+    );
+}
+
+#[test]
+fn test_modular_arithmetic() {
+    assert_eq!(display("mod_pow(4, 13, 497);"), "445");
+    assert_eq!(display("mod_pow(2, 10 ^ 20, 1000000007);"), "855473248");
+    assert_eq!(display("mod_inverse(3, 11);"), "4");
+    assert_eq!(
+        display("(3 * mod_inverse(3, 1000000007)) % 1000000007;"),
+        "1"
+    );
+}
+
+#[test]
+fn test_arrangements() {
+    assert_eq!(
+        display("permutations([1, 2, 3]);"),
+        "[[1, 2, 3], [1, 3, 2], [2, 1, 3], [2, 3, 1], [3, 1, 2], [3, 2, 1]]"
+    );
+    assert_eq!(
+        display("combinations([\"a\", \"b\", \"c\"], 2);"),
+        r#"[["a", "b"], ["a", "c"], ["b", "c"]]"#
+    );
+    assert_eq!(display("combinations([1, 2], 0);"), "[[]]");
+    assert_eq!(display("combinations([1, 2], 3);"), "[]");
+}
+
+#[test]
+fn test_validation() {
+    assert_eq!(
+        error("factorial(2.5);"),
+        "factorial() expects a non-negative integer, found 2.5"
+    );
+    assert_eq!(
+        error("factorial(-1);"),
+        "factorial() expects a non-negative integer, found -1"
+    );
+    assert_eq!(error("gcd(1.5, 2);"), "gcd() expects an integer, found 1.5");
+    assert_eq!(
+        error("factorize(0);"),
+        "factorize() expects a positive integer, found 0"
+    );
+    assert_eq!(
+        error("mod_pow(2, -1, 5);"),
+        "mod_pow() expects a non-negative exponent, found -1"
+    );
+    assert_eq!(
+        error("mod_pow(2, 3, 0);"),
+        "mod_pow() expects a positive modulus, found 0"
+    );
+    assert_eq!(
+        error("mod_inverse(4, 6);"),
+        "mod_inverse() found no inverse of 4 modulo 6, since they share the factor 2"
+    );
+    assert_eq!(
+        error("permutations([1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);"),
+        "permutations() would build 3628800 lists, more than the limit of 1000000"
+    );
+    assert_eq!(
+        error("primes(10 ^ 9);"),
+        "primes() sieves up to at most 100000000, found 1000000000"
+    );
+    assert_eq!(
+        error("factorial(100000);"),
+        "factorial() computes up to at most 20000, found 100000"
+    );
+}
+
+#[test]
+fn test_types() {
+    assert!(check("xs: List<Number> := primes(10); b: Boolean := is_prime(7);").is_ok());
+    assert!(check("gcd(\"a\", 2);").is_err());
+}
@@ -0,0 +1,75 @@
+use mathfp::{execute, execute_or_panic};
+
+fn close(input: &str, expected: f64) -> bool {
+    let value = execute_or_panic(input);
+    match value.as_f64() {
+        Some(actual) => (actual - expected).abs() < 0.000001,
+        None => panic!("expected a number, found {value}"),
+    }
+}
+
+#[test]
+fn test_integrate() {
+    assert!(close("integrate(sin, 0, 3.141592653589793)", 2.0));
+    assert!(close("integrate(x |-> x ^ 2, 0, 3)", 9.0));
+    assert!(close("integrate(x |-> exp(-(x ^ 2)), 0, 0)", 0.0));
+    assert_eq!(
+        execute_or_panic("abs(integrate_tol(0.001, x |-> 1 / x, 1, 2) - ln(2)) < 0.001")
+            .to_string(),
+        "true"
+    );
+}
+
+#[test]
+fn test_root() {
+    assert!(close("root(x |-> x ^ 2 - 2, 0, 2)", 2f64.sqrt()));
+    assert!(close("root(x |-> cos(x) - x, 0, 1)", 0.7390851332151607));
+    assert!(close("root_tol(0.1, x |-> x - 1, 0, 3)", 1.0));
+}
+
+#[test]
+fn test_newton() {
+    assert!(close("newton(x |-> x ^ 3 - 8, 1)", 2.0));
+    assert!(close("newton_tol(0.0001, x |-> exp(x) - 2, 0)", 2f64.ln()));
+}
+
+#[test]
+fn test_minimize() {
+    assert!(close("minimize(x |-> (x - 3) ^ 2 + 1, 0)", 3.0));
+    let input = "
+        distance := p |-> {
+            d := matrix(p) - matrix([1, 2]);
+            det(transpose(d) * d)
+        };
+        minimize(distance, [0, 0])";
+    let value = execute_or_panic(input);
+    let mathfp::runtime::RuntimeValue::List { elements } = value else {
+        panic!("expected a list, found {value}");
+    };
+    assert!((elements[0].as_f64().unwrap() - 1.0).abs() < 0.0001);
+    assert!((elements[1].as_f64().unwrap() - 2.0).abs() < 0.0001);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        execute("root(x |-> x ^ 2 + 1, -1, 1)"),
+        Err("root() expects f(-1) and f(1) to have opposite signs, found 2 and 2".into())
+    );
+    assert_eq!(
+        execute("newton(x |-> 1, 0)"),
+        Err("newton() found a zero derivative at 0, try another starting point".into())
+    );
+    assert_eq!(
+        execute("minimize(x |-> -x, 0)"),
+        Err("minimize() found no minimum near 0, the function may be unbounded below".into())
+    );
+    assert_eq!(
+        execute("integrate(x |-> \"a\", 0, 1)"),
+        Err("integrate() expects the function to return a real number, found \"a\" at 0".into())
+    );
+    assert_eq!(
+        execute("integrate_tol(0, sin, 0, 1)"),
+        Err("integrate_tol() expects a positive tolerance, found 0".into())
+    );
+}
@@ -0,0 +1,77 @@
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+/// The last `[t, y]` point of a trajectory.
+fn last_point(input: &str) -> (f64, RuntimeValue) {
+    let trajectory = execute_or_panic(input);
+    let RuntimeValue::List { elements } = &trajectory else {
+        panic!("expected a trajectory, found {trajectory}");
+    };
+    match elements.last() {
+        Some(RuntimeValue::List { elements }) => {
+            (elements[0].as_f64().unwrap(), elements[1].clone())
+        }
+        _ => panic!("expected [t, y] points, found {trajectory}"),
+    }
+}
+
+#[test]
+fn test_scalar_state() {
+    let (t, y) = last_point("solve_ode((t, y) |-> -y, 0, 1, 1)");
+    assert_eq!(t, 1.0);
+    assert!((y.as_f64().unwrap() - (-1f64).exp()).abs() < 0.000000001);
+}
+
+#[test]
+fn test_rk4_fixed_steps() {
+    assert_eq!(
+        execute_or_panic("solve_ode_rk4(0.5, (t, y) |-> 1, 0, 0, 1)").to_string(),
+        "[[0, 0], [0.5, 0.5], [1, 1]]"
+    );
+}
+
+#[test]
+fn test_vector_states() {
+    let input = "
+        oscillator := (t, y) |-> [|0, 1; -1, 0|] * y;
+        solve_ode_tol(0.000000000001, oscillator, 0, [|1, 0|], 3.141592653589793)";
+    let (_, y) = last_point(input);
+    let RuntimeValue::Matrix(y) = y else {
+        panic!("expected a vector, found {y}");
+    };
+    assert!((y.get(0, 0) + 1.0).abs() < 0.0000001);
+    assert!(y.get(1, 0).abs() < 0.0000001);
+
+    let (_, y) = last_point("solve_ode((t, y) |-> y .* [1, 2], 0, [1, 1], 1)");
+    let RuntimeValue::List { elements } = y else {
+        panic!("expected a list, found {y}");
+    };
+    assert!((elements[0].as_f64().unwrap() - 1f64.exp()).abs() < 0.0000001);
+    assert!((elements[1].as_f64().unwrap() - 2f64.exp()).abs() < 0.0000001);
+}
+
+#[test]
+fn test_events() {
+    // A ball dropped from 10 m hits the ground at sqrt(2 * 10 / 9.81) s.
+    let input = "
+        falling := (t, height) |-> -9.81 * t;
+        hits_ground := (t, height) |-> height <= 0;
+        solve_ode_until(hits_ground, falling, 0, 10, 5)";
+    let (t, y) = last_point(input);
+    assert!((t - (20.0f64 / 9.81).sqrt()).abs() < 0.000000001);
+    assert!(y.as_f64().unwrap().abs() < 0.0000001);
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        execute("solve_ode((t, y) |-> [y], 0, 1, 1)"),
+        Err(
+            "solve_ode() expects the derivative to have the shape of the state 1, found [1]".into()
+        )
+    );
+    assert_eq!(
+        execute("solve_ode_rk4(0, (t, y) |-> y, 0, 1, 1)"),
+        Err("solve_ode_rk4() expects a positive step size, found 0".into())
+    );
+}
@@ -0,0 +1,102 @@
+use mathfp::interpreter::Interpreter;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_env_or_panic, execute_or_panic};
+
+#[test]
+fn test_declare_operator() {
+    let input = "
+        dot := a |-> b |-> a * b;
+        infixl 7 <.> := dot;
+        3 <.> 4
+    ";
+    assert_eq!(execute(input), Ok(RuntimeValue::Number(12.0)));
+}
+
+#[test]
+fn test_operator_precedence() {
+    let input = "
+        infixl 7 <.> := a |-> b |-> a * b;
+        infixl 5 <+> := a |-> b |-> a * 10 + b;
+        [1 + 2 <.> 3, 1 <+> 2 * 3, 1 <+> 2 == 12]
+    ";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![
+                RuntimeValue::Number(7.0),
+                RuntimeValue::Number(16.0),
+                RuntimeValue::Boolean(true),
+            ]
+        }
+    );
+}
+
+#[test]
+fn test_operator_associativity() {
+    let interpreter = Interpreter::new();
+    let input = "
+        sub := a |-> b |-> a - b;
+        infixl 4 -. := sub;
+        infixr 4 ~- := sub;
+        left := 10 -. 5 -. 2;
+        right := 10 ~- 5 ~- 2;
+    ";
+    execute_env_or_panic(input, &interpreter);
+
+    assert_eq!(
+        interpreter.value_of("left"),
+        Some(RuntimeValue::Number(3.0))
+    );
+    assert_eq!(
+        interpreter.value_of("right"),
+        Some(RuntimeValue::Number(7.0))
+    );
+}
+
+#[test]
+fn test_operator_persists_between_inputs() {
+    let interpreter = Interpreter::new();
+    execute_env_or_panic("infix 5 ~= := a |-> b |-> abs(a - b) < 0.01", &interpreter);
+
+    assert_eq!(
+        execute_env_or_panic("1 ~= 1.001", &interpreter),
+        RuntimeValue::Boolean(true)
+    );
+}
+
+#[test]
+fn test_longest_operator_match() {
+    let input = "
+        infixl 6 <+ := a |-> b |-> a + b;
+        infixl 6 <+> := a |-> b |-> a * b;
+        [2 <+ 3, 2 <+> 3, 2 <= 3]
+    ";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![
+                RuntimeValue::Number(5.0),
+                RuntimeValue::Number(6.0),
+                RuntimeValue::Boolean(true),
+            ]
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "Operator '~=' is non-associative")]
+fn test_non_associative_chain() {
+    execute_or_panic("infix 5 ~= := a |-> b |-> a == b; 1 ~= 1 ~= 1");
+}
+
+#[test]
+#[should_panic(expected = "Cannot redeclare the built-in operator '=='")]
+fn test_redeclare_builtin() {
+    execute_or_panic("infixl 4 == := a |-> b |-> a");
+}
+
+#[test]
+#[should_panic(expected = "Expected an operator precedence from 0 to 9")]
+fn test_invalid_precedence() {
+    execute_or_panic("infixl 10 <.> := a |-> b |-> a");
+}
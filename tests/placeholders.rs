@@ -0,0 +1,110 @@
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_placeholder_call_argument() {
+    let input = "
+        clamp := lo |-> hi |-> x |-> if x < lo then lo else if x > hi then hi else x;
+        unit := clamp(0, 1, _);
+        [unit(-3), unit(0.5), unit(7)]
+    ";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![
+                RuntimeValue::Number(0.0),
+                RuntimeValue::Number(0.5),
+                RuntimeValue::Number(1.0),
+            ]
+        }
+    );
+}
+
+#[test]
+fn test_placeholder_binary_operands() {
+    assert_eq!(
+        execute("f := _ * 2 + 1; f(5)"),
+        Ok(RuntimeValue::Number(11.0))
+    );
+    assert_eq!(execute("(_ - 1)(5)"), Ok(RuntimeValue::Number(4.0)));
+}
+
+#[test]
+fn test_multiple_placeholders_are_curried() {
+    assert_eq!(
+        execute("sub := _ - _; sub(10, 3)"),
+        Ok(RuntimeValue::Number(7.0))
+    );
+    assert_eq!(
+        execute("f := a |-> b |-> c |-> a * 100 + b * 10 + c; f(_, 2, _)(1, 3)"),
+        Ok(RuntimeValue::Number(123.0))
+    );
+}
+
+#[test]
+fn test_placeholder_scoped_to_argument() {
+    // The placeholder belongs to the argument, not to the call of map
+    let input = "map(_ * 10, [1, 2])";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![RuntimeValue::Number(10.0), RuntimeValue::Number(20.0)]
+        }
+    );
+}
+
+#[test]
+fn test_nested_placeholders() {
+    // The inner placeholder belongs to the argument of map,
+    // while the outer one belongs to the whole expression
+    let input = "
+        scale := map(_ * 2, _);
+        scale([1, 2])
+    ";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![RuntimeValue::Number(2.0), RuntimeValue::Number(4.0)]
+        }
+    );
+}
+
+#[test]
+fn test_bare_call_argument_extends_to_enclosing_expression() {
+    assert_eq!(
+        execute("inc := x |-> x + 1; f := inc(_) * 2; f(4)"),
+        Ok(RuntimeValue::Number(10.0))
+    );
+}
+
+#[test]
+fn test_underscore_parameter_unchanged() {
+    assert_eq!(
+        execute("f := _ |-> 5; f(nil)"),
+        Ok(RuntimeValue::Number(5.0))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot bind a value to the placeholder '_'")]
+fn test_bind_placeholder() {
+    execute_or_panic("_ := 5");
+}
+
+#[test]
+fn test_placeholder_parameter_names() {
+    // The parameters print as source that can be read back.
+    assert_eq!(
+        execute_or_panic("_ * _").to_string(),
+        "_1 |-> _2 |-> _1 * _2"
+    );
+    assert_eq!(
+        execute("(_1 |-> _2 |-> _1 * _2)(3, 4)"),
+        Ok(RuntimeValue::Integer(12.into()))
+    );
+    // They never capture a name written in the source.
+    assert_eq!(
+        execute("_1 := 10; f := _ + _1; f(1)"),
+        Ok(RuntimeValue::Integer(11.into()))
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::display;
+use mathfp::execute;
+
+#[test]
+fn test_construction() {
+    assert_eq!(display("poly([1, -3, 2])"), "x^2 - 3x + 2");
+    assert_eq!(display("poly(sym(x ^ 2 - 3 * x + 2))"), "x^2 - 3x + 2");
+    assert_eq!(display("poly(t |-> 2 * t ^ 3 - t)"), "2x^3 - x");
+    assert_eq!(display("poly([0, 0, 5])"), "5");
+    assert_eq!(display("poly([0])"), "0");
+    assert_eq!(display("coeffs(poly([1, -3, 2]))"), "[1, -3, 2]");
+    assert_eq!(display("degree(sym(x ^ 4 + 1))"), "4");
+}
+
+#[test]
+fn test_free_variable() {
+    assert_eq!(display("poly(x^2 - 3x + 2)"), "x^2 - 3x + 2");
+    assert_eq!(display("poly(2.5t^3 - t)(2)"), "18");
+    assert_eq!(display("poly(x^2 - 3x + 2) == poly([1, -3, 2])"), "true");
+    // A bound name is a value, not the variable of the polynomial.
+    assert_eq!(display("x := 2; poly([x, 1])"), "2x + 1");
+    assert!(execute("x := 2; poly(x^2 - 3x + 2)").is_err());
+    // The builtin reads the formula, not its name.
+    assert_eq!(display("p := poly; p(x^2 + 1)"), "x^2 + 1");
+    assert!(execute("(poly |-> poly(y + 1))(x |-> x)").is_err());
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(display("poly([1, 1]) * poly([1, -1])"), "x^2 - 1");
+    assert_eq!(display("poly([1, 0]) + 1 - poly([1, 0])"), "1");
+    assert_eq!(display("2 * poly([1, 1]) / 4"), "0.5x + 0.5");
+    assert_eq!(display("poly([1, 1]) ^ 3"), "x^3 + 3x^2 + 3x + 1");
+    assert_eq!(display("-poly([1, -2])"), "-x + 2");
+    assert_eq!(
+        display("poly([1, 0, 0, -1]) div poly([1, -1])"),
+        "x^2 + x + 1"
+    );
+    assert_eq!(display("poly([1, 0, 1]) % poly([1, -1])"), "2");
+    assert_eq!(display("poly([1, -1]) == poly(sym(x - 1))"), "true");
+}
+
+#[test]
+fn test_evaluation() {
+    assert_eq!(display("poly([1, -3, 2])(3)"), "2");
+    assert_eq!(display("poly([1, 0, 1])(1i)"), "0 + 0i");
+    assert_eq!(display("grad(poly([1, -3, 2]))(5)"), "7");
+    assert_eq!(display("diff(poly([1, -3, 2]))"), "2x - 3");
+    assert_eq!(display("integral(poly([3, 0, 1]))"), "x^3 + x");
+}
+
+#[test]
+fn test_roots() {
+    assert_eq!(display("roots(poly([1, -3, 2]))"), "[1, 2]");
+    assert_eq!(display("roots(poly([1, 0, 1]))"), "[0 - 1i, 0 + 1i]");
+    assert_eq!(display("real_roots(poly([1, 0, 0, -8]))"), "[2]");
+    assert_eq!(display("roots(sym((x - 1) * (x + 3)))"), "[-3, 1]");
+    assert_eq!(display("roots(poly([5]))"), "[]");
+    assert_eq!(
+        display("polygcd(poly([1, -3, 2]), poly([1, -2, 1]))"),
+        "x - 1"
+    );
+}
+
+#[test]
+fn test_polyfit() {
+    let program = "
+        p := polyfit([0, 1, 2, 3, 4], [1, 3, 9, 19, 33], 2);
+        [abs(p(5) - 51) < 0.000001, degree(p)];
+    ";
+    assert_eq!(display(program), "[true, 2]");
+    // A line through noisy points is the least-squares line.
+    let program = "
+        p := polyfit([0, 1, 2, 3], [0, 1, 1, 2], 1);
+        [abs(p(0) - 0.1) < 0.000001, abs(p(1) - 0.7) < 0.000001];
+    ";
+    assert_eq!(display(program), "[true, true]");
+}
+
+#[test]
+fn test_errors() {
+    assert!(execute("poly(sym(sin(x)))").is_err());
+    assert!(execute("poly(sym(x * y))").is_err());
+    assert!(execute("poly([1, 1]) / poly([1, -1])").is_err());
+    assert!(execute("poly([1, 1]) div poly([0])").is_err());
+    assert!(execute("poly([1, 1]) ^ 0.5").is_err());
+    assert!(execute("roots(poly([0]))").is_err());
+    assert!(execute("polyfit([1, 2], [1, 2, 3], 1)").is_err());
+    assert!(execute("polyfit([1, 1, 1], [1, 2, 3], 1)").is_err());
+}
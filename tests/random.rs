@@ -0,0 +1,138 @@
+mod common;
+
+use common::display;
+use mathfp::interpreter::Interpreter;
+use mathfp::{check, execute, execute_env};
+
+fn seeded(input: &str, seed: u64) -> String {
+    execute_env(input, &Interpreter::with_seed(seed))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_seed_makes_programs_repeatable() {
+    let program = "[random(), randint(1, 100), shuffle([1, 2, 3, 4, 5]), random_normal(0, 1)];";
+    assert_eq!(seeded(program, 7), seeded(program, 7));
+    assert_ne!(seeded(program, 7), seeded(program, 8));
+    assert_eq!(
+        display("seed(3); a := random(); seed(3); b := random(); a == b;"),
+        "true"
+    );
+
+    let interpreter = Interpreter::new();
+    interpreter.seed(11);
+    let first = execute_env("randint(0, 1000000);", &interpreter).unwrap();
+    interpreter.seed(11);
+    let second = execute_env("randint(0, 1000000);", &interpreter).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_uniform_choices() {
+    assert_eq!(display("x := random(); [x >= 0, x < 1];"), "[true, true]");
+    assert_eq!(display("randint(4, 4);"), "4");
+    assert_eq!(display("choice([7]);"), "7");
+    assert_eq!(display("shuffle([]);"), "[]");
+    assert_eq!(
+        display(
+            "rolls := take(600, map(i |-> randint(1, 6), repeat(0))); [quantile(rolls, 0), quantile(rolls, 1)];"
+        ),
+        "[1, 6]"
+    );
+    assert!(execute("randint(2, 1);").is_err());
+    assert!(execute("randint(1.5, 2);").is_err());
+    assert!(execute("choice([]);").is_err());
+    assert!(execute("shuffle(3);").is_err());
+}
+
+#[test]
+fn test_samplers_have_the_right_moments() {
+    let moments = |sampler: &str| {
+        seeded(
+            &format!(
+                "xs := take(20000, map(i |-> {sampler}, repeat(0))); [mean(xs), variance(xs)];"
+            ),
+            1,
+        )
+    };
+    let parse = |text: String| -> Vec<f64> {
+        text.trim_matches(['[', ']'])
+            .split(", ")
+            .map(|x| x.parse().unwrap())
+            .collect()
+    };
+    let close = |actual: f64, expected: f64| (actual - expected).abs() < 0.05 * expected.max(1.0);
+    for (sampler, mean, variance) in [
+        ("random_uniform(2, 4)", 3.0, 1.0 / 3.0),
+        ("random_normal(10, 2)", 10.0, 4.0),
+        ("random_exponential(2)", 0.5, 0.25),
+        ("random_poisson(4)", 4.0, 4.0),
+        ("random_poisson(250)", 250.0, 250.0),
+        ("random_binomial(10, 0.3)", 3.0, 2.1),
+        ("random_binomial(500, 0.9)", 450.0, 45.0),
+    ] {
+        let sample = parse(moments(sampler));
+        assert!(close(sample[0], mean), "{sampler} has mean {}", sample[0]);
+        assert!(
+            close(sample[1], variance),
+            "{sampler} has variance {}",
+            sample[1]
+        );
+    }
+}
+
+#[test]
+fn test_densities() {
+    assert_eq!(display("uniform_pdf(0, 4, 1);"), "0.25");
+    assert_eq!(display("uniform_cdf(0, 4, 1);"), "0.25");
+    assert_eq!(display("uniform_cdf(0, 4, 5);"), "1");
+    assert_eq!(display("normal_pdf(0, 1, 0);"), "0.3989422804014327");
+    assert_eq!(display("normal_cdf(0, 1, 0);"), "0.5");
+    assert_eq!(
+        display("abs(normal_cdf(100, 15, 130) - 0.9772498680518208) < 0.000000000000001;"),
+        "true"
+    );
+    // The lower tail keeps its precision instead of rounding to 0.
+    assert_eq!(
+        display("abs(normal_cdf(0, 1, -10) * 10^24 - 7.61985302416047) < 0.000000000001;"),
+        "true"
+    );
+    assert_eq!(display("exponential_pdf(2, 0);"), "2");
+    assert_eq!(display("exponential_cdf(1, -1);"), "0");
+    assert_eq!(display("exponential_cdf(1, 1);"), "0.6321205588285577");
+    assert_eq!(display("poisson_pmf(2, 0);"), "0.1353352832366127");
+    assert_eq!(display("poisson_pmf(2, 1.5);"), "0");
+    assert_eq!(display("binomial_pmf(4, 0.5, 2);"), "0.375");
+    assert_eq!(display("binomial_pmf(3, 1, 3);"), "1");
+    assert_eq!(display("binomial_cdf(4, 0.5, 2);"), "0.6875");
+    assert_eq!(display("binomial_cdf(4, 0.5, 4);"), "1");
+    assert!(execute("normal_pdf(0, 0, 1);").is_err());
+    assert!(execute("binomial_pmf(3, 1.5, 1);").is_err());
+    assert!(execute("poisson_cdf(-1, 1);").is_err());
+}
+
+#[test]
+fn test_cdfs_sum_their_masses() {
+    assert_eq!(
+        display(
+            "pmf := poisson_pmf(3.5); abs(pmf(0) + pmf(1) + pmf(2) + pmf(3) - poisson_cdf(3.5, 3)) < 0.000000000001;"
+        ),
+        "true"
+    );
+    assert_eq!(
+        display(
+            "pmf := binomial_pmf(5, 0.2); abs(pmf(0) + pmf(1) + pmf(2) - binomial_cdf(5, 0.2, 2)) < 0.000000000001;"
+        ),
+        "true"
+    );
+}
+
+#[test]
+fn test_types() {
+    assert!(
+        check("x: Number := random(); xs := shuffle([1, 2]); y: Number := choice(xs);").is_ok()
+    );
+    assert!(check("normal_cdf(0, 1, \"a\");").is_err());
+    assert!(check("randint(1, 2) + 1;").is_ok());
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::display;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_rational_literals() {
+    assert_eq!(display("1/3r"), "1/3");
+    assert_eq!(display("2/4r"), "1/2");
+    assert_eq!(display("0.25r"), "1/4");
+    assert_eq!(display("6/3r"), "2");
+}
+
+#[test]
+fn test_exact_arithmetic() {
+    assert_eq!(display("0.1r + 0.2r"), "3/10");
+    assert_eq!(display("1/3r + 1/6r"), "1/2");
+    assert_eq!(display("1/3r - 1/2r"), "-1/6");
+    assert_eq!(display("2/3r * 3/4r"), "1/2");
+    assert_eq!(display("1/3r / 2r"), "1/6");
+    assert_eq!(display("-(1/3r)"), "-1/3");
+}
+
+#[test]
+fn test_integer_powers_stay_exact() {
+    assert_eq!(display("(2/3r) ^ 3"), "8/27");
+    assert_eq!(display("(2/3r) ^ -2"), "9/4");
+    assert_eq!(display("(2/3r) ^ 2r"), "4/9");
+    assert_eq!(execute("(1/4r) ^ 0.5"), Ok(RuntimeValue::Number(0.5)));
+}
+
+#[test]
+fn test_large_numerators() {
+    assert_eq!(display("(1/3r) ^ 50"), "1/717897987691852588770249");
+    assert_eq!(display("(1/3r) ^ 50 * 3r ^ 50"), "1");
+}
+
+#[test]
+fn test_comparison() {
+    assert_eq!(execute("1/3r < 1/2r"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(execute("2/4r == 1/2r"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(execute("1/2r == 0.5"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(execute("1/3r > 0.3"), Ok(RuntimeValue::Boolean(true)));
+}
+
+#[test]
+fn test_mixing_with_floats_promotes() {
+    assert_eq!(execute("1/4r + 0.5"), Ok(RuntimeValue::Number(0.75)));
+    assert_eq!(execute("2 * 1/4r"), Ok(RuntimeValue::Number(0.5)));
+    assert_eq!(execute("sqrt(1/4r)"), Ok(RuntimeValue::Number(0.5)));
+}
+
+#[test]
+fn test_annotated_as_number() {
+    assert_eq!(display("x: Number := 1/3r; x"), "1/3");
+}
+
+#[test]
+fn test_division_by_zero() {
+    // Like float division, dividing by an exact zero gives an infinity.
+    assert_eq!(display("1/3r / 0r"), "inf");
+    assert_eq!(display("-1/3r / 0"), "-inf");
+}
+
+#[test]
+#[should_panic(expected = "Division by zero")]
+fn test_floor_division_by_zero() {
+    execute_or_panic("1/3r div 0r");
+}
@@ -0,0 +1,105 @@
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_full_section() {
+    assert_eq!(execute("(+)(1, 2)"), Ok(RuntimeValue::Number(3.0)));
+    assert_eq!(execute("(*)(4)(5)"), Ok(RuntimeValue::Number(20.0)));
+    assert_eq!(execute("(-)(10, 4)"), Ok(RuntimeValue::Number(6.0)));
+    assert_eq!(
+        execute("(+)(\"map\", \"fp\")"),
+        Ok(RuntimeValue::String("mapfp".into()))
+    );
+}
+
+#[test]
+fn test_right_section() {
+    assert_eq!(execute("(< 3)(2)"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(execute("(/ 4)(2)"), Ok(RuntimeValue::Number(0.5)));
+    assert_eq!(execute("(== nil)(nil)"), Ok(RuntimeValue::Boolean(true)));
+}
+
+#[test]
+fn test_left_section() {
+    assert_eq!(execute("(2 ^)(10)"), Ok(RuntimeValue::Number(1024.0)));
+    assert_eq!(execute("(4 /)(2)"), Ok(RuntimeValue::Number(2.0)));
+    // The whole expression before the operator is the operand
+    assert_eq!(execute("(1 + 2 *)(3)"), Ok(RuntimeValue::Number(9.0)));
+}
+
+#[test]
+fn test_negation_is_not_a_section() {
+    assert_eq!(execute("(- 3)"), Ok(RuntimeValue::Number(-3.0)));
+    assert_eq!(execute("(2 -)(3)"), Ok(RuntimeValue::Number(-1.0)));
+}
+
+#[test]
+fn test_sections_with_higher_order_functions() {
+    let input = "map((* 2), filter((> 1), [1, 2, 3]))";
+    assert_eq!(
+        execute_or_panic(input),
+        RuntimeValue::List {
+            elements: vec![RuntimeValue::Number(4.0), RuntimeValue::Number(6.0)]
+        }
+    );
+}
+
+#[test]
+fn test_section_operand_evaluated_once() {
+    let input = "
+        calls := 0;
+        next := _ |-> {
+            calls = calls + 1;
+            calls
+        };
+        add := (+ next(nil));
+        add(10) + add(20);
+        calls
+    ";
+    assert_eq!(execute(input), Ok(RuntimeValue::Number(1.0)));
+}
+
+#[test]
+fn test_section_display() {
+    assert_eq!(
+        execute("str((2 ^))"),
+        Ok(RuntimeValue::String("<native function (2 ^)>".into()))
+    );
+}
+
+#[test]
+fn test_power() {
+    assert_eq!(execute("2 ^ 3 ^ 2"), Ok(RuntimeValue::Number(512.0)));
+    assert_eq!(execute("-2 ^ 2"), Ok(RuntimeValue::Number(-4.0)));
+    assert_eq!(execute("2 * 3 ^ 2"), Ok(RuntimeValue::Number(18.0)));
+}
+
+#[test]
+fn test_declared_operator_sections() {
+    let declare = "infixl 6 <+> := a |-> b |-> a * 10 + b;";
+    let execute_with = |input: &str| execute(&format!("{declare} {input}"));
+    assert_eq!(execute_with("(<+>)(1, 2)"), Ok(RuntimeValue::Number(12.0)));
+    assert_eq!(execute_with("(<+> 1)(2)"), Ok(RuntimeValue::Number(21.0)));
+    assert_eq!(execute_with("(1 <+>)(2)"), Ok(RuntimeValue::Number(12.0)));
+    assert_eq!(
+        execute_with("foldl((<+>), 0, [1, 2, 3])"),
+        Ok(RuntimeValue::Number(123.0))
+    );
+    let input = "
+        calls := 0;
+        next := _ |-> {
+            calls = calls + 1;
+            calls
+        };
+        add := (<+> next(nil));
+        add(10) + add(20);
+        calls
+    ";
+    assert_eq!(execute_with(input), Ok(RuntimeValue::Number(1.0)));
+}
+
+#[test]
+#[should_panic(expected = "Unsupported operands for '*'")]
+fn test_section_type_error() {
+    execute_or_panic("(* 2)(\"a\")");
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::display;
+use mathfp::{check, execute};
+
+#[test]
+fn test_averages() {
+    assert_eq!(display("mean([1, 2, 3, 4])"), "2.5");
+    assert_eq!(display("mean([1/2r, 3/2r])"), "1");
+    assert_eq!(display("median([5, 1, 3])"), "3");
+    assert_eq!(display("median([4, 1, 3, 2])"), "2.5");
+    assert_eq!(display("mode([1, 2, 2, 3, 3])"), "2");
+    assert_eq!(display("quantile([1, 2, 3, 4, 5], 0.25)"), "2");
+    assert_eq!(display("quantile([10, 20], 0.5)"), "15");
+}
+
+#[test]
+fn test_nan_propagates() {
+    assert_eq!(display("mean([3, 1, nan])"), "NaN");
+    assert_eq!(display("median([3, 1, nan])"), "NaN");
+    assert_eq!(display("quantile([nan, 2], 0)"), "NaN");
+}
+
+#[test]
+fn test_spread() {
+    assert_eq!(
+        display("variance([2, 4, 4, 4, 5, 5, 7, 9])"),
+        "4.571428571428571"
+    );
+    assert_eq!(display("pvariance([2, 4, 4, 4, 5, 5, 7, 9])"), "4");
+    assert_eq!(display("pstddev([2, 4, 4, 4, 5, 5, 7, 9])"), "2");
+    assert_eq!(display("stddev([1, 3])"), "1.4142135623730951");
+    // The mean is far larger than the spread, which the naive formula loses.
+    assert_eq!(
+        display("variance([1000000004, 1000000007, 1000000013, 1000000016])"),
+        "30"
+    );
+}
+
+#[test]
+fn test_relationships() {
+    assert_eq!(display("covariance([1, 2, 3], [2, 4, 6])"), "2");
+    assert_eq!(
+        display("pcovariance([1, 2, 3], [2, 4, 6])"),
+        "1.3333333333333333"
+    );
+    assert_eq!(display("correlation([1, 2, 3], [6, 4, 2])"), "-1");
+    assert_eq!(
+        display("linear_regression([0, 1, 2, 3], [1, 3, 5, 7])"),
+        "[2, 1, 1]"
+    );
+    let program = "
+        fit := linear_regression([1, 2, 3, 4], [2, 4, 5, 9]);
+        fit == [2.2, -0.5, 121 / 130];
+    ";
+    assert_eq!(display(program), "true");
+}
+
+#[test]
+fn test_histogram() {
+    assert_eq!(display("histogram([1, 2, 2, 3, 4, 5], 2)"), "[3, 3]");
+    assert_eq!(display("histogram([0, 1, 2, 3], 3)"), "[1, 1, 2]");
+}
+
+#[test]
+fn test_type_check() {
+    assert_eq!(check("mean([1, 2])").unwrap().to_string(), "Number");
+    assert_eq!(
+        check("linear_regression([1, 2], [3, 4])")
+            .unwrap()
+            .to_string(),
+        "List<Number>"
+    );
+    assert!(check("mean(1)").is_err());
+}
+
+#[test]
+fn test_errors() {
+    assert!(execute("mean([])").is_err());
+    assert!(execute("mean([1, \"a\"])").is_err());
+    assert!(execute("variance([1])").is_err());
+    assert!(execute("quantile([1, 2], 2)").is_err());
+    assert!(execute("covariance([1, 2], [1])").is_err());
+    assert!(execute("correlation([1, 2], [3, 3])").is_err());
+    assert!(execute("histogram([1, 2], 0)").is_err());
+    assert!(execute("linear_regression([1, 1], [1, 2])").is_err());
+}
@@ -0,0 +1,107 @@
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+// Helper for building a list of numbers
+fn numbers(values: &[f64]) -> RuntimeValue {
+    RuntimeValue::List {
+        elements: values.iter().map(|n| RuntimeValue::Number(*n)).collect(),
+    }
+}
+
+#[test]
+fn test_iterate() {
+    let input = "take(5, iterate(x |-> x * 2, 1))";
+    assert_eq!(
+        execute_or_panic(input),
+        numbers(&[1.0, 2.0, 4.0, 8.0, 16.0])
+    );
+}
+
+#[test]
+fn test_curried_builtins() {
+    let input = "take(3)(iterate(x |-> x + 1)(0))";
+    assert_eq!(execute_or_panic(input), numbers(&[0.0, 1.0, 2.0]));
+}
+
+#[test]
+fn test_repeat_and_cycle() {
+    assert_eq!(execute_or_panic("take(3, repeat(7))"), numbers(&[7.0; 3]));
+    assert_eq!(
+        execute_or_panic("take(5, cycle([1, 2]))"),
+        numbers(&[1.0, 2.0, 1.0, 2.0, 1.0])
+    );
+}
+
+#[test]
+fn test_unfold() {
+    let input = "
+        countdown := n |-> if n > 0 then [n, n - 1] else nil;
+        take(10, unfold(countdown, 3))
+    ";
+    assert_eq!(execute_or_panic(input), numbers(&[3.0, 2.0, 1.0]));
+}
+
+#[test]
+fn test_lazy_map_filter() {
+    let input = "
+        naturals := iterate(n |-> n + 1, 0);
+        large := filter(n |-> n * n > 10, naturals);
+        take(3, map(n |-> n * 10, large))
+    ";
+    assert_eq!(execute_or_panic(input), numbers(&[40.0, 50.0, 60.0]));
+}
+
+#[test]
+fn test_take_while() {
+    let input = "take(100, take_while(x |-> x < 20, iterate(x |-> x * 3, 1)))";
+    assert_eq!(execute_or_panic(input), numbers(&[1.0, 3.0, 9.0]));
+}
+
+#[test]
+fn test_unforced_elements_not_evaluated() {
+    let input = "
+        calls := 0;
+        counted := x |-> {
+            calls = calls + 1;
+            x
+        };
+        s := map(counted, iterate(x |-> x + 1, 0));
+        take(3, s);
+        take(2, s);
+        calls
+    ";
+    assert_eq!(execute(input), Ok(RuntimeValue::Number(3.0)));
+}
+
+#[test]
+fn test_sequence_ops_on_lists() {
+    assert_eq!(
+        execute_or_panic("map(x |-> x + 1, [1, 2, 3])"),
+        numbers(&[2.0, 3.0, 4.0])
+    );
+    assert_eq!(
+        execute_or_panic("filter(x |-> x > 1, [1, 2, 3])"),
+        numbers(&[2.0, 3.0])
+    );
+    assert_eq!(execute_or_panic("take(2, [1, 2, 3])"), numbers(&[1.0, 2.0]));
+}
+
+#[test]
+fn test_stream_display() {
+    assert_eq!(
+        execute_or_panic("str(repeat(1))"),
+        RuntimeValue::String("<stream>".into())
+    );
+}
+
+#[test]
+#[should_panic(expected = "take() expects a non-negative integer")]
+fn test_take_invalid_count() {
+    execute_or_panic("take(-1, repeat(1))");
+}
+
+#[test]
+#[should_panic(expected = "unfold() expects the function to return nil or a [value, seed] pair")]
+fn test_unfold_invalid_result() {
+    execute_or_panic("take(1, unfold(x |-> x, 1))");
+}
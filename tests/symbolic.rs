@@ -0,0 +1,92 @@
+mod common;
+
+use common::display;
+use mathfp::execute;
+
+#[test]
+fn test_simplify() {
+    assert_eq!(display("sym(x ^ 2 + 2 * x * x)"), "3 * x ^ 2");
+    assert_eq!(display("sym(x - x)"), "0");
+    assert_eq!(display("sym(2 * x / 4 + y / 3)"), "x / 2 + y / 3");
+    assert_eq!(display("sym(x ^ 2 / x)"), "x");
+    assert_eq!(display("sym(1 / x + 1 / x)"), "2 / x");
+    assert_eq!(display("sym(x ^ y * x)"), "x ^ (y + 1)");
+    assert_eq!(display("simplify(sym((x + 1) * (x + 1)))"), "(x + 1) ^ 2");
+    assert_eq!(display("sym(x ^ 0)"), "1");
+    assert_eq!(display("sym(x ^ (2 - 2) * y)"), "y");
+}
+
+#[test]
+fn test_expand() {
+    assert_eq!(display("expand(sym((x + 1) ^ 2))"), "x ^ 2 + 2 * x + 1");
+    assert_eq!(display("expand(sym((a - b) * (a + b)))"), "a ^ 2 - b ^ 2");
+    assert_eq!(
+        execute("expand(sym((x + 1) ^ 50))"),
+        Err("expand() multiplies out powers of sums up to 32, found (x + 1) ^ 50".into())
+    );
+}
+
+#[test]
+fn test_factor() {
+    assert_eq!(display("factor(sym(x ^ 2 - 1))"), "(x - 1) * (x + 1)");
+    assert_eq!(
+        display("factor(sym(2 * x ^ 2 + 4 * x + 2))"),
+        "2 * (x + 1) ^ 2"
+    );
+    assert_eq!(display("factor(sym(x ^ 3 - x))"), "x * (x - 1) * (x + 1)");
+    assert_eq!(
+        display("factor(sym(2 * x ^ 2 - 2))"),
+        "2 * (x - 1) * (x + 1)"
+    );
+    assert_eq!(display("factor(sym(1 - x ^ 2))"), "-(x - 1) * (x + 1)");
+    assert_eq!(
+        display("factor(sym(6 * x ^ 2 - 5 * x + 1))"),
+        "(3 * x - 1) * (2 * x - 1)"
+    );
+    assert_eq!(
+        display("factor(sym(x ^ 2 / 2 - 1 / 2))"),
+        "(x - 1) * (x + 1) / 2"
+    );
+    assert_eq!(display("factor(sym(x ^ 2 + 1))"), "x ^ 2 + 1");
+}
+
+#[test]
+fn test_arithmetic_on_formulas() {
+    assert_eq!(display("sym(x) + 1 - sym(x)"), "1");
+    assert_eq!(display("2 * sym(x) ^ 2 + sym(x ^ 2)"), "3 * x ^ 2");
+    assert_eq!(display("-sym(x / y)"), "-x / y");
+    assert_eq!(display("sym(x + 0) == sym(x)"), "true");
+}
+
+#[test]
+fn test_substitute_and_eval() {
+    assert_eq!(display("substitute(sym(a * x + b), \"x\", 2)"), "2 * a + b");
+    assert_eq!(
+        display("substitute(sym(x ^ 2), sym(x), sym(y + 1))"),
+        "(y + 1) ^ 2"
+    );
+    assert_eq!(
+        display("a := 3; b := 1; eval(substitute(sym(a * x + b), \"x\", 2))"),
+        "7"
+    );
+    assert_eq!(display("f := sym(x ^ 2); x := 4; eval(f)"), "16");
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        execute("factor(sym(x * y))"),
+        Err(
+            "factor() expects a polynomial in one variable with exact coefficients, found x * y"
+                .into()
+        )
+    );
+    assert_eq!(
+        execute("expand(1)"),
+        Err("expand() expects a formula like sym(x + 1), found 1".into())
+    );
+    assert_eq!(
+        execute("sym(x) < 1"),
+        Err("Unsupported operands for '<': x, 1".into())
+    );
+}
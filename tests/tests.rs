@@ -1,3 +1,6 @@
+mod common;
+
+use common::display;
 use mathfp::interpreter::Interpreter;
 use mathfp::runtime::RuntimeValue;
 use mathfp::{execute, execute_env_or_panic, execute_or_panic};
@@ -243,3 +246,17 @@ fn test_incomplete_grouping() {
     let input = "f := x + (1";
     execute_or_panic(input);
 }
+
+#[test]
+fn test_function_display() {
+    assert_eq!(display("x |-> (x + 1) * 2"), "x |-> (x + 1) * 2");
+    assert_eq!(display("(a, b) |-> -a ^ 2 - b"), "a |-> b |-> -a ^ 2 - b");
+    assert_eq!(
+        display("x: Number |-> if x > 0 then [x, 1.0] else nil : Number"),
+        "x: Number |-> if x > 0 then [x, 1.0] else nil : Number"
+    );
+    assert_eq!(
+        display("f |-> f(1)(2) + (3 *)(4)"),
+        "f |-> f(1)(2) + (3 *)(4)"
+    );
+}
@@ -0,0 +1,179 @@
+use mathfp::check;
+
+fn type_of(input: &str) -> String {
+    check(input).expect("Program should type check").to_string()
+}
+
+#[test]
+fn test_literals() {
+    assert_eq!(type_of("1"), "Number");
+    assert_eq!(type_of("3 + 4i"), "Number");
+    assert_eq!(type_of("[1, 2] .* 2"), "Dynamic");
+    assert_eq!(type_of("diff(x |-> x ^ 2)"), "Number -> Number");
+    assert_eq!(type_of("grad(x |-> x ^ 2)(3)"), "Number");
+    assert_eq!(type_of("\"hi\""), "String");
+    assert_eq!(type_of("true"), "Boolean");
+    assert_eq!(type_of("[1, 2]"), "List<Number>");
+}
+
+#[test]
+fn test_functions() {
+    assert_eq!(type_of("x |-> x * 2"), "Number -> Number");
+    assert_eq!(type_of("x |-> x"), "a -> a");
+    assert_eq!(type_of("f |-> x |-> f(f(x))"), "(a -> a) -> a -> a");
+    assert_eq!(type_of("x |-> y |-> x < y"), "a -> a -> Boolean");
+    assert_eq!(type_of("n |-> n % 2 + n div 2"), "Number -> Number");
+}
+
+#[test]
+fn test_let_polymorphism() {
+    let input = "
+        id := x |-> x;
+        [id(1), id(2)];
+        id(\"one\")
+    ";
+    assert_eq!(type_of(input), "String");
+}
+
+#[test]
+fn test_recursion() {
+    let input = "
+        fact := n |-> if n <= 1 then 1 else n * fact(n - 1);
+        fact
+    ";
+    assert_eq!(type_of(input), "Number -> Number");
+}
+
+#[test]
+fn test_mutual_recursion() {
+    let input = "
+        is_even := n |-> if n == 0 then true else is_odd(n - 1);
+        is_odd := n |-> if n == 0 then false else is_even(n - 1);
+        is_odd
+    ";
+    assert_eq!(type_of(input), "Number -> Boolean");
+}
+
+#[test]
+fn test_builtins() {
+    assert_eq!(type_of("x |-> sqrt(x) + 1"), "Number -> Number");
+    assert_eq!(type_of("iterate(x |-> x + 1, 0)"), "Stream<Number>");
+    assert_eq!(type_of("str(1) + \"!\""), "String");
+}
+
+#[test]
+fn test_sections() {
+    assert_eq!(type_of("(* 2)"), "Number -> Number");
+    assert_eq!(type_of("(\"a\" +)"), "String -> String");
+}
+
+#[test]
+fn test_mixed_list_is_dynamic() {
+    assert_eq!(type_of("[1, \"two\"]"), "List<Dynamic>");
+}
+
+#[test]
+fn test_nil_has_any_type() {
+    assert_eq!(
+        type_of("x |-> if x > 0 then x else nil"),
+        "Number -> Number"
+    );
+}
+
+#[test]
+#[should_panic(expected = "[Line 1, Col 3] Type mismatch: expected Number, found String")]
+fn test_mismatch_location() {
+    check("1 - \"a\"").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Expected a number or a list of numbers, found Boolean")]
+fn test_wrong_argument() {
+    check("sqrt(true)").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Type mismatch: expected Number, found String")]
+fn test_branches_must_agree() {
+    check("x |-> if x then 1 else \"one\"").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Unsupported operand type for '+': Boolean")]
+fn test_unsupported_operand() {
+    check("true + false").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Name 'y' is not defined")]
+fn test_undefined_name() {
+    check("x |-> y").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Type mismatch: expected a, found a -> b")]
+fn test_infinite_type() {
+    check("x |-> x(x)").unwrap();
+}
+
+#[test]
+fn test_reports_each_statement() {
+    let errors = check("a := 1 - true;\nb := sqrt(\"x\");\na + b").unwrap_err();
+    assert!(errors.starts_with("Type errors:\n"));
+    assert_eq!(errors.lines().count(), 3);
+    assert!(errors.contains("[Line 2"));
+}
+
+#[test]
+fn test_math_builtins_map_over_lists() {
+    assert_eq!(type_of("sin(1)"), "Number");
+    assert_eq!(type_of("sin([0, 1])"), "List<Number>");
+    assert_eq!(type_of("sqrt([[4, 9], [16]])"), "List<List<Number>>");
+    assert_eq!(type_of("xs |-> floor(abs(xs))"), "a -> a");
+    assert_eq!(type_of("f := x |-> exp(x) + 1; f(2)"), "Number");
+    assert_eq!(type_of("-[1, 2]"), "List<Number>");
+    assert!(check("sin(\"one\")").is_err());
+    assert!(check("-\"one\"").is_err());
+    assert!(check("f := x |-> cos(x); f([\"a\"])").is_err());
+}
+
+#[test]
+#[should_panic(expected = "Expected a number or a list of numbers, found String")]
+fn test_math_builtin_of_string() {
+    check("ln([\"e\"])").unwrap();
+}
+
+#[test]
+fn test_sequence_builtins() {
+    // They accept lists and streams, and keep the type of the elements.
+    assert_eq!(type_of("head([1, 2])"), "Number");
+    assert_eq!(type_of("head(iterate(x |-> x + 1, 0))"), "Number");
+    assert_eq!(type_of("xs |-> head(xs) * 2"), "Sequence<Number> -> Number");
+    assert_eq!(
+        type_of("map(x |-> x * 2, [1, 2]) + [3]"),
+        "Sequence<Number>"
+    );
+    assert_eq!(type_of("take(3, repeat(\"a\"))"), "List<String>");
+    assert_eq!(type_of("foldl(acc |-> x |-> acc + x, 0, [1, 2])"), "Number");
+    assert_eq!(type_of("len(\"abc\") + len([1])"), "Number");
+    assert!(check("head([1, 2]) + \"a\"").is_err());
+    assert!(check("find(x |-> x > 1, [\"a\"])").is_err());
+    assert!(check("map(x |-> x, \"abc\")").is_err());
+}
+
+#[test]
+#[should_panic(expected = "len() expects a list or string, found Number")]
+fn test_len_of_number() {
+    check("len(3)").unwrap();
+}
+
+#[test]
+fn test_polynomial_in_free_variable() {
+    assert_eq!(type_of("x |-> 3x^2"), "Number -> Number");
+    assert!(check("poly(x^2 - 3x + 2)").is_ok());
+    assert!(check("p := poly(t^2 + 1); p(2)").is_ok());
+    assert!(check("poly(y + 1)").is_ok());
+    assert!(check("poly(x + sin(\"a\"))").is_err());
+    assert!(check("p := poly; p(x^2 + 1)").is_ok());
+    assert!(check("(poly |-> poly(y + 1))(x |-> x)").is_err());
+}
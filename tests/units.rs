@@ -0,0 +1,113 @@
+mod common;
+
+use common::display;
+use mathfp::runtime::RuntimeValue;
+use mathfp::{execute, execute_or_panic};
+
+#[test]
+fn test_quantity_literals() {
+    assert_eq!(display("9.81 'm/s^2'"), "9.81 m/s^2");
+    assert_eq!(display("3 'kg*m^2'"), "3 kg*m^2");
+    assert_eq!(display("2 's^-1'"), "2 s^-1");
+    assert_eq!(display("2 'Hz'"), "2 Hz");
+}
+
+#[test]
+fn test_add_compatible_units() {
+    assert_eq!(display("3 'km' + 200 'm'"), "3.2 km");
+    assert_eq!(display("1 'h' - 30 'min'"), "0.5 h");
+}
+
+#[test]
+fn test_multiply_and_divide() {
+    assert_eq!(display("2 'kg' * 9.81 'm/s^2'"), "19.62 kg*m/s^2");
+    assert_eq!(display("10 'm' / 2 's'"), "5 m/s");
+    assert_eq!(display("3 * 2 'm'"), "6 m");
+    assert_eq!(display("(3 'm')^2"), "9 m^2");
+}
+
+#[test]
+fn test_units_cancel_to_number() {
+    assert_eq!(execute("1 'km' / 1 'm'"), Ok(RuntimeValue::Number(1000.0)));
+}
+
+#[test]
+fn test_unit_then_division() {
+    assert_eq!(display("t := 4; 10 'm' / t"), "2.5 m");
+    assert_eq!(display("10'm'/4"), "2.5 m");
+}
+
+#[test]
+fn test_names_are_not_units() {
+    // Units are only read in quotes, so names after a number are never units.
+    assert_eq!(display("min(3, 4) * 1 'min'"), "3 min");
+    assert!(execute("h := 2; f := x |-> 2 h; f(1)").is_err());
+    assert!(execute("3 km").is_err());
+}
+
+#[test]
+fn test_convert() {
+    assert_eq!(display("to(90 'km/h', \"m/s\")"), "25 m/s");
+    assert_eq!(display("to(1 'kWh', \"J\")"), "3600000 J");
+    assert_eq!(display("to(2 'N', \"kg*m/s^2\")"), "2 kg*m/s^2");
+    assert_eq!(display("to(1500 'm', 1 'km')"), "1.5 km");
+}
+
+#[test]
+fn test_compare_quantities() {
+    assert_eq!(execute("1 'km' > 999 'm'"), Ok(RuntimeValue::Boolean(true)));
+    assert_eq!(
+        execute("1 'km' == 1000 'm'"),
+        Ok(RuntimeValue::Boolean(true))
+    );
+    assert_eq!(execute("1 'm' == 1 's'"), Ok(RuntimeValue::Boolean(false)));
+    assert_eq!(execute("3 'm' <= 2 'km'"), Ok(RuntimeValue::Boolean(true)));
+    assert!(execute("2 >= 1 'm'").is_err());
+}
+
+#[test]
+fn test_negate_quantity() {
+    assert_eq!(display("-3 'N'"), "-3 N");
+}
+
+#[test]
+#[should_panic(expected = "Incompatible dimensions for '+': m and s")]
+fn test_add_incompatible_dimensions() {
+    execute_or_panic("3 'm' + 2 's'");
+}
+
+#[test]
+#[should_panic(expected = "Incompatible dimensions for '<': m and s")]
+fn test_order_incompatible_dimensions() {
+    execute_or_panic("3 'm' < 2 's'");
+}
+
+#[test]
+#[should_panic(expected = "Incompatible dimensions for '-': m and dimensionless")]
+fn test_subtract_number_from_quantity() {
+    execute_or_panic("3 'm' - 1");
+}
+
+#[test]
+#[should_panic(expected = "Cannot convert 3 m to s, as their dimensions differ: m and s")]
+fn test_convert_incompatible() {
+    execute_or_panic("to(3 'm', \"s\")");
+}
+
+#[test]
+#[should_panic(expected = "Unknown unit 'parsec'")]
+fn test_unknown_unit_literal() {
+    execute_or_panic("1 'parsec'");
+}
+
+#[test]
+#[should_panic(expected = "Unknown unit 'furlong'")]
+fn test_unknown_unit() {
+    execute_or_panic("to(3 'm', \"furlong\")");
+}
+
+#[test]
+#[should_panic(expected = "A quantity can only be raised to an integer power, found 0.5")]
+fn test_fractional_power() {
+    execute_or_panic("(4 'm')^0.5");
+}